@@ -0,0 +1,513 @@
+use search::StripsTask;
+
+pub const DOMAIN: &str = "
+; four-operator blocks world
+(define (domain blocks)
+  (:requirements :strips :typing)
+  (:types block)
+  (:predicates (on ?x ?y - block) (ontable ?x - block) (clear ?x - block)
+               (handempty) (holding ?x - block))
+  (:action pick-up
+    :parameters (?x - block)
+    :precondition (and (clear ?x) (ontable ?x) (handempty))
+    :effect (and (holding ?x)
+                 (not (ontable ?x)) (not (clear ?x)) (not (handempty))))
+  (:action put-down
+    :parameters (?x - block)
+    :precondition (holding ?x)
+    :effect (and (ontable ?x) (clear ?x) (handempty) (not (holding ?x))))
+  (:action stack
+    :parameters (?x ?y - block)
+    :precondition (and (holding ?x) (clear ?y))
+    :effect (and (on ?x ?y) (clear ?x) (handempty)
+                 (not (holding ?x)) (not (clear ?y))))
+  (:action unstack
+    :parameters (?x ?y - block)
+    :precondition (and (on ?x ?y) (clear ?x) (handempty))
+    :effect (and (holding ?x) (clear ?y)
+                 (not (on ?x ?y)) (not (clear ?x)) (not (handempty)))))
+";
+
+/// C on A, B on the table: putting A on B first, or B on C first, must be
+/// undone later
+pub const SUSSMAN_ANOMALY: &str = "
+(define (problem sussman-anomaly)
+  (:domain blocks)
+  (:objects a b c - block)
+  (:init (on c a) (ontable a) (ontable b) (clear c) (clear b) (handempty))
+  (:goal (and (on a b) (on b c))))
+";
+
+/// Problem stacking the blocks into a tower, the first one at the bottom
+pub fn tower(blocks: &[&str]) -> String {
+    let mut init = String::from("(handempty)");
+    for block in blocks {
+        init.push_str(&format!(" (ontable {0}) (clear {0})", block));
+    }
+    let goal: Vec<String> = blocks
+        .windows(2)
+        .map(|pair| format!("(on {} {})", pair[1], pair[0]))
+        .collect();
+    format!(
+        "(define (problem tower) (:domain blocks) (:objects {} - block) (:init {}) (:goal (and {})))",
+        blocks.join(" "),
+        init,
+        goal.join(" ")
+    )
+}
+
+fn main() {
+    use search::BreadthFirstSearch;
+
+    let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+    println!(
+        "{} facts, {} actions",
+        task.facts().len(),
+        task.actions().len()
+    );
+    let result = task.bfs_search().unwrap();
+    let mut states = search::replay(&task, &result.path);
+    println!("{:?}", states.next().unwrap());
+    for (action, state) in result.path.iter().zip(states) {
+        println!("{}\n    {:?}", action, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{
+        replay, validate_plan, AStarSearch, AnytimeConfig, AnytimeSearch, BreadthFirstSearch,
+        EnforcedHillClimbing, FfHeuristic, HAdd, HMax, Heuristic, LandmarkCount, PatternDatabase,
+        PlanError, Space, State, StripsState,
+    };
+
+    const DELIVERY: &str = "
+        (define (domain delivery)
+          (:requirements :strips :typing)
+          (:types location package vehicle - object truck - vehicle)
+          (:predicates (road ?from ?to - location)
+                       (at ?v - vehicle ?l - location)
+                       (parcel-at ?p - package ?l - location)
+                       (in ?p - package ?v - vehicle))
+          (:action drive
+            :parameters (?v - vehicle ?from ?to - location)
+            :precondition (and (at ?v ?from) (road ?from ?to))
+            :effect (and (at ?v ?to) (not (at ?v ?from))))
+          (:action load
+            :parameters (?p - package ?v - vehicle ?l - location)
+            :precondition (and (parcel-at ?p ?l) (at ?v ?l))
+            :effect (and (in ?p ?v) (not (parcel-at ?p ?l))))
+          (:action unload
+            :parameters (?p - package ?v - vehicle ?l - location)
+            :precondition (and (in ?p ?v) (at ?v ?l))
+            :effect (and (parcel-at ?p ?l) (not (in ?p ?v)))))";
+
+    /// One-way roads
+    fn delivery(roads: &[(&str, &str)]) -> String {
+        let roads: Vec<String> = roads
+            .iter()
+            .map(|(from, to)| format!("(road {} {})", from, to))
+            .collect();
+        format!(
+            "(define (problem parcel)
+               (:domain delivery)
+               (:objects l1 l2 l3 - location t1 - truck p1 - package)
+               (:init (at t1 l1) (parcel-at p1 l2) {})
+               (:goal (parcel-at p1 l1)))",
+            roads.join(" ")
+        )
+    }
+
+    #[test]
+    fn sussman_anomaly_needs_six_steps() {
+        let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+        let result = task.bfs_search().unwrap();
+        assert_eq!(
+            task.action_names(&result.path),
+            [
+                "(unstack c a)",
+                "(put-down c)",
+                "(pick-up b)",
+                "(stack b c)",
+                "(pick-up a)",
+                "(stack a b)"
+            ]
+        );
+        let blind = |_: &StripsState| 0;
+        let result = task.astar_search(&blind).unwrap();
+        assert_eq!(result.path.len(), 6);
+        assert!(task.is_goal(&result.end_state));
+    }
+
+    #[test]
+    fn grounding_builds_propositional_states() {
+        let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+        // 9 `on`, 3 `ontable`, 3 `clear`, 3 `holding` and `handempty`; one
+        // `pick-up` and `put-down` per block, one `stack` and `unstack` per
+        // pair of blocks
+        assert_eq!(task.facts().len(), 19);
+        assert_eq!(task.actions().len(), 24);
+        let state = task.initial_state();
+        let facts: Vec<&str> = state
+            .facts()
+            .map(|fact| task.facts()[fact].as_str())
+            .collect();
+        assert_eq!(
+            facts,
+            [
+                "(clear b)",
+                "(clear c)",
+                "(handempty)",
+                "(on c a)",
+                "(ontable a)",
+                "(ontable b)"
+            ]
+        );
+        let names: Vec<String> = state
+            .get_available_actions()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(names, ["(pick-up b)", "(unstack c a)"]);
+
+        let on = task.fact("(on a b)").unwrap();
+        assert_eq!(task.goal(), [on, task.fact("(on b c)").unwrap()]);
+        assert!(!state.holds(on));
+
+        let task = StripsTask::from_pddl(DOMAIN, &tower(&["a", "b", "c", "d"])).unwrap();
+        assert_eq!(task.bfs_search().unwrap().path.len(), 6);
+    }
+
+    #[test]
+    fn static_facts_are_compiled_away() {
+        let task = StripsTask::from_pddl(
+            DELIVERY,
+            &delivery(&[("l1", "l2"), ("l2", "l3"), ("l3", "l1")]),
+        )
+        .unwrap();
+        // roads never change, and the truck is a vehicle
+        assert!(task.facts().iter().all(|fact| !fact.starts_with("(road")));
+        let drives: Vec<&str> = task
+            .actions()
+            .iter()
+            .map(|action| action.name.as_str())
+            .filter(|name| name.starts_with("(drive"))
+            .collect();
+        assert_eq!(
+            drives,
+            ["(drive t1 l1 l2)", "(drive t1 l2 l3)", "(drive t1 l3 l1)"]
+        );
+        let result = task.bfs_search().unwrap();
+        assert_eq!(
+            task.action_names(&result.path),
+            [
+                "(drive t1 l1 l2)",
+                "(load p1 t1 l2)",
+                "(drive t1 l2 l3)",
+                "(drive t1 l3 l1)",
+                "(unload p1 t1 l1)"
+            ]
+        );
+
+        // the truck can't get to the parcel: nothing changes, and only the
+        // goal is left
+        let task = StripsTask::from_pddl(DELIVERY, &delivery(&[("l2", "l1")])).unwrap();
+        assert!(task.actions().is_empty());
+        assert_eq!(task.facts(), ["(parcel-at p1 l1)"]);
+        assert!(task.bfs_search().is_none());
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let error = StripsTask::from_pddl(
+            DOMAIN,
+            "(define (problem p) (:domain blocks)\n (:objects a - block)\n (:init (on a))\n (:goal (clear a)))",
+        )
+        .unwrap_err();
+        assert_eq!(error.file, "problem");
+        assert_eq!(error.line, 3);
+        assert_eq!(
+            error.to_string(),
+            "problem, line 3: `on` takes 2 arguments, not 1"
+        );
+
+        let negative = DOMAIN.replace(
+            "(holding ?x)\n    :effect",
+            "(and (holding ?x) (not (clear ?x)))\n    :effect",
+        );
+        let error = StripsTask::from_pddl(&negative, SUSSMAN_ANOMALY).unwrap_err();
+        assert_eq!(error.file, "domain");
+        assert_eq!(error.message, "`not` is not supported in STRIPS");
+
+        let errors = [
+            (
+                DOMAIN.replace(":typing", ":typing :conditional-effects"),
+                SUSSMAN_ANOMALY.to_string(),
+            ),
+            (
+                DOMAIN.replace("(holding ?x - block)", "(holding ?x - blok)"),
+                SUSSMAN_ANOMALY.to_string(),
+            ),
+            (
+                DOMAIN.to_string(),
+                SUSSMAN_ANOMALY.replace("(clear c)", "(clean c)"),
+            ),
+            (
+                DOMAIN.to_string(),
+                SUSSMAN_ANOMALY.replace("(:domain blocks)", "(:domain logistics)"),
+            ),
+            (
+                DOMAIN.to_string(),
+                SUSSMAN_ANOMALY.replace("(on c a)", "(on c d)"),
+            ),
+            (
+                DOMAIN.to_string(),
+                SUSSMAN_ANOMALY.replace("(handempty))", "(handempty)"),
+            ),
+        ];
+        let messages: Vec<String> = errors
+            .iter()
+            .map(|(domain, problem)| {
+                StripsTask::from_pddl(domain, problem)
+                    .unwrap_err()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "domain, line 4: requirement `:conditional-effects` is not supported",
+                "domain, line 7: type `blok` is not declared",
+                "problem, line 5: predicate `clean` is not declared",
+                "problem, line 3: problem of `logistics`, not of `blocks`",
+                "problem, line 5: `d` is not defined",
+                "problem, line 2: unclosed `(`",
+            ]
+        );
+    }
+
+    #[test]
+    fn relaxation_heuristics_on_the_sussman_anomaly() {
+        let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+        let initial = task.initial_state();
+        // (on b c) takes (pick-up b) then (stack b c), (on a b) takes
+        // (unstack c a), (pick-up a) and (stack a b)
+        assert_eq!(HMax::new(&task).estimate(&initial), 3);
+        assert_eq!(HAdd::new(&task).estimate(&initial), 5);
+        let ff = FfHeuristic::new(&task);
+        assert_eq!(ff.estimate(&initial), 5);
+        let helpful: Vec<String> = ff
+            .helpful_actions(&initial)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(helpful, ["(pick-up b)", "(unstack c a)"]);
+
+        let landmarks = LandmarkCount::new(&task);
+        let names: Vec<&str> = landmarks
+            .landmarks()
+            .iter()
+            .map(|&fact| task.facts()[fact].as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "(clear a)",
+                "(clear b)",
+                "(clear c)",
+                "(handempty)",
+                "(holding a)",
+                "(holding b)",
+                "(on a b)",
+                "(on b c)"
+            ]
+        );
+        assert_eq!(landmarks.estimate(&initial), 5);
+
+        let goal = task.bfs_search().unwrap().end_state;
+        assert_eq!(HMax::new(&task).estimate(&goal), 0);
+        assert_eq!(HAdd::new(&task).estimate(&goal), 0);
+        assert_eq!(ff.estimate(&goal), 0);
+        assert_eq!(landmarks.estimate(&goal), 0);
+    }
+
+    #[test]
+    fn h_max_keeps_a_star_optimal() {
+        let task = StripsTask::from_pddl(DOMAIN, &tower(&["e", "d", "c", "b", "a"])).unwrap();
+        let blind = task.astar_search(&|_: &StripsState| 0).unwrap();
+        let h_max = task.astar_search(&HMax::new(&task)).unwrap();
+        assert_eq!(blind.path.len(), 8);
+        assert_eq!(h_max.path.len(), 8);
+        println!(
+            "Expanded without heuristic: {}, with h_max: {}",
+            blind.expanded, h_max.expanded
+        );
+        assert!(h_max.expanded < blind.expanded);
+
+        for result in [
+            task.astar_search(&HAdd::new(&task)).unwrap(),
+            task.astar_search(&FfHeuristic::new(&task)).unwrap(),
+            task.astar_search(&LandmarkCount::new(&task)).unwrap(),
+        ] {
+            assert!(task.is_goal(&result.end_state));
+            assert!(result.expanded < h_max.expanded);
+        }
+    }
+
+    #[test]
+    fn enforced_hill_climbing_follows_helpful_actions() {
+        let blocks = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let task = StripsTask::from_pddl(DOMAIN, &tower(&blocks)).unwrap();
+        let ff = FfHeuristic::new(&task);
+        let pruned = task.enforced_hill_climbing(&ff, true).unwrap();
+        let full = task.enforced_hill_climbing(&ff, false).unwrap();
+        for result in [&pruned, &full] {
+            let end = result
+                .path
+                .iter()
+                .fold(task.initial_state(), |state, action| state.apply(action));
+            assert_eq!(end, result.end_state);
+            assert!(task.is_goal(&end));
+        }
+        assert_eq!(pruned.path.len(), 14);
+        println!(
+            "Generated with helpful actions: {}, without: {}",
+            pruned.generated, full.generated
+        );
+        assert!(pruned.generated < full.generated);
+
+        let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+        let result = task
+            .enforced_hill_climbing(&FfHeuristic::new(&task), true)
+            .unwrap();
+        // climbing isn't optimal: (stack b c) looks good first, but is undone
+        assert!(task.is_goal(&result.end_state));
+        assert!(result.path.len() > 6);
+    }
+
+    #[test]
+    fn relaxation_heuristics_detect_dead_ends() {
+        let task = StripsTask::from_pddl(DELIVERY, &delivery(&[("l2", "l1")])).unwrap();
+        let initial = task.initial_state();
+        assert_eq!(HMax::new(&task).estimate(&initial), usize::MAX);
+        assert_eq!(HAdd::new(&task).estimate(&initial), usize::MAX);
+        let ff = FfHeuristic::new(&task);
+        assert_eq!(ff.estimate(&initial), usize::MAX);
+        assert!(ff.helpful_actions(&initial).is_empty());
+        assert!(task.astar_search(&HMax::new(&task)).is_none());
+        assert!(task.enforced_hill_climbing(&ff, true).is_none());
+    }
+
+    #[test]
+    fn anytime_search_skips_dead_ends() {
+        // the truck can't leave l3
+        let roads = [("l1", "l2"), ("l2", "l1"), ("l1", "l3")];
+        let task = StripsTask::from_pddl(DELIVERY, &delivery(&roads)).unwrap();
+        let heuristic = HMax::new(&task);
+        let solutions: Vec<_> = task
+            .anytime_search(&heuristic, AnytimeConfig::default())
+            .collect();
+        let last = solutions.last().unwrap();
+        assert!(task.is_goal(&last.result.end_state));
+        assert_eq!(last.cost, 4);
+        assert_eq!(last.bound, 1.0);
+    }
+
+    #[test]
+    fn anytime_search_stops_without_a_solution() {
+        // the truck can't reach the parcel
+        let task = StripsTask::from_pddl(DELIVERY, &delivery(&[("l2", "l1")])).unwrap();
+        let blind = |_: &StripsState| 0;
+        let mut search = task.anytime_search(&blind, AnytimeConfig::default());
+        assert!(search.next().is_none());
+        let weight = search.weight();
+        assert!(weight > 1.0);
+        // no more iterations with lower weights
+        assert!(search.next().is_none());
+        assert_eq!(search.weight(), weight);
+    }
+
+    #[test]
+    fn pattern_databases_follow_one_way_roads() {
+        // the truck can't leave l3
+        let roads = [("l1", "l2"), ("l2", "l1"), ("l1", "l3")];
+        let task = StripsTask::from_pddl(DELIVERY, &delivery(&roads)).unwrap();
+        // location of the truck, then of the parcel
+        let key = |state: &StripsState| {
+            let holds = |name: &str| state.facts().any(|fact| task.facts()[fact] == name);
+            let truck = ["l1", "l2", "l3"]
+                .iter()
+                .position(|location| holds(&format!("(at t1 {})", location)))
+                .unwrap();
+            let parcel = [
+                "(parcel-at p1 l1)",
+                "(parcel-at p1 l2)",
+                "(parcel-at p1 l3)",
+                "(in p1 t1)",
+            ]
+            .iter()
+            .position(|name| holds(name))
+            .unwrap();
+            truck * 4 + parcel
+        };
+        let database = PatternDatabase::build(
+            &[task.initial_state()],
+            StripsState::clone,
+            |state: &StripsState| task.is_goal(state),
+            key,
+            12,
+        );
+        let initial = task.initial_state();
+        assert_eq!(database.distance(key(&initial)), Some(4));
+        // the parcel loaded and the truck stuck in l3
+        assert_eq!(database.distance(2 * 4 + 3), None);
+        // the parcel delivered, the truck in l3
+        assert_eq!(database.distance(2 * 4), Some(0));
+    }
+
+    #[test]
+    fn plans_are_validated_step_by_step() {
+        let task = StripsTask::from_pddl(DOMAIN, SUSSMAN_ANOMALY).unwrap();
+        let result = task.bfs_search().unwrap();
+        assert_eq!(
+            validate_plan(&task, &result.path),
+            Ok(result.end_state.clone())
+        );
+        let states: Vec<StripsState> = replay(&task, &result.path).collect();
+        assert_eq!(states.len(), 7);
+        assert_eq!(states[0], task.initial_state());
+        assert_eq!(states[6], result.end_state);
+        assert_eq!(
+            format!("{:?}", states[1]),
+            "{(clear a), (clear b), (holding c), (ontable a), (ontable b)}"
+        );
+
+        // (put-down c) before c is picked up
+        let mut swapped = result.path.clone();
+        swapped.swap(0, 2);
+        let error = validate_plan(&task, &swapped).unwrap_err();
+        let PlanError::Unavailable { step, action, .. } = &error else {
+            panic!("{}", error);
+        };
+        assert_eq!((*step, action.name()), (1, "(put-down c)"));
+        assert_eq!(
+            error.to_string(),
+            "step 1: (put-down c) is not available in \
+             {(clear c), (holding b), (on c a), (ontable a)}, \
+             only [(put-down b), (stack b c)] are"
+        );
+
+        let error = validate_plan(&task, &result.path[..5]).unwrap_err();
+        assert_eq!(
+            error,
+            PlanError::NotAGoal {
+                state: states[5].clone()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the plan ends in {(clear b), (holding a), (on b c), (ontable c)}, not a goal"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use search::{Action, Game, State, StochasticGame};
+
+/// Scores above the target go back to zero
+const TARGET: usize = 10;
+const FACES: usize = 4;
+const TURNS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Play {
+    Stand,
+    Throw(usize),
+    /// Outcome of a throw: the sum of the dice
+    Rolled(usize),
+}
+
+impl Action for Play {}
+
+/// Two players take turns throwing up to two dice and adding them to their
+/// score. The highest score after `TURNS` turns each wins.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Duel {
+    scores: [usize; 2],
+    to_move: usize,
+    moves_left: usize,
+    /// Dice thrown and not rolled yet
+    thrown: Option<usize>,
+}
+
+impl Duel {
+    pub fn new(scores: [usize; 2], to_move: usize, moves_left: usize) -> Self {
+        Self {
+            scores,
+            to_move,
+            moves_left,
+            thrown: None,
+        }
+    }
+
+    fn end_turn(&self, gained: usize) -> Self {
+        let mut scores = self.scores;
+        scores[self.to_move] += gained;
+        if scores[self.to_move] > TARGET {
+            scores[self.to_move] = 0;
+        }
+        Self {
+            scores,
+            to_move: 1 - self.to_move,
+            moves_left: self.moves_left - 1,
+            thrown: None,
+        }
+    }
+}
+
+/// Probability of every sum of `dice` dice
+fn sums(dice: usize) -> Vec<(usize, f64)> {
+    let mut ways = vec![1.0];
+    for _ in 0..dice {
+        let mut next = vec![0.0; ways.len() + FACES];
+        for (sum, count) in ways.iter().enumerate() {
+            for face in 1..=FACES {
+                next[sum + face] += count / FACES as f64;
+            }
+        }
+        ways = next;
+    }
+    ways.into_iter()
+        .enumerate()
+        .filter(|(_, probability)| *probability > 0.0)
+        .collect()
+}
+
+impl State for Duel {
+    type Action = Play;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        match self.thrown {
+            Some(dice) => sums(dice)
+                .into_iter()
+                .map(|(sum, _)| Play::Rolled(sum))
+                .collect(),
+            None if self.moves_left == 0 => Vec::new(),
+            None => vec![Play::Stand, Play::Throw(1), Play::Throw(2)],
+        }
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        match action {
+            Play::Stand => self.end_turn(0),
+            Play::Throw(dice) => Self {
+                thrown: Some(*dice),
+                ..self.clone()
+            },
+            Play::Rolled(sum) => self.end_turn(*sum),
+        }
+    }
+}
+
+pub struct DiceDuel;
+
+impl Game for DiceDuel {
+    type State = Duel;
+    type Player = usize;
+
+    fn initial_state(&self) -> Self::State {
+        Duel::new([0, 0], 0, 2 * TURNS)
+    }
+
+    fn current_player(&self, state: &Self::State) -> Self::Player {
+        state.to_move
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.moves_left == 0
+    }
+
+    fn utility(&self, state: &Self::State, player: Self::Player) -> f64 {
+        match state.scores[player].cmp(&state.scores[1 - player]) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.0,
+            std::cmp::Ordering::Less => -1.0,
+        }
+    }
+}
+
+impl StochasticGame for DiceDuel {
+    fn is_chance(&self, state: &Self::State) -> bool {
+        state.thrown.is_some()
+    }
+
+    fn outcomes(&self, state: &Self::State) -> Vec<(Play, f64)> {
+        let dice = state.thrown.expect("dice have been thrown");
+        sums(dice)
+            .into_iter()
+            .map(|(sum, probability)| (Play::Rolled(sum), probability))
+            .collect()
+    }
+
+    fn utility_bounds(&self) -> (f64, f64) {
+        (-1.0, 1.0)
+    }
+}
+
+/// Lead over the opponent, within the utility bounds
+fn lead(state: &Duel, player: usize) -> f64 {
+    (state.scores[player] as f64 - state.scores[1 - player] as f64) / TARGET as f64
+}
+
+fn main() {
+    use search::{Star, StarMinimaxSearch};
+
+    let game = DiceDuel;
+    let decision = game.star_minimax(&game.initial_state(), usize::MAX, &lead, Star::Star2);
+    println!(
+        "First player should {:?}, expecting {:.3} ({} expansions)",
+        decision.best_move.unwrap(),
+        decision.value,
+        decision.expanded
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{ExpectimaxSearch, Star, StarMinimaxSearch};
+
+    fn unused(_: &Duel, _: usize) -> f64 {
+        unreachable!()
+    }
+
+    #[test]
+    fn outcomes_are_weighted_by_probability() {
+        let game = DiceDuel;
+        // last move, behind by 4: only two dice can win, with a sum of 5 or
+        // more (10 throws out of 16) and tie with 4 (3 out of 16)
+        let state = Duel::new([4, 0], 1, 1);
+        for decision in [
+            game.expectimax(&state, usize::MAX, &unused),
+            game.star_minimax(&state, usize::MAX, &unused, Star::Star1),
+            game.star_minimax(&state, usize::MAX, &unused, Star::Star2),
+        ] {
+            assert_eq!(decision.best_move, Some(Play::Throw(2)));
+            assert!((decision.value - 7.0 / 16.0).abs() < 1e-9);
+        }
+
+        // ahead on the last move, standing wins for sure
+        let state = Duel::new([4, 5], 1, 1);
+        let decision = game.expectimax(&state, usize::MAX, &unused);
+        assert_eq!(decision.best_move, Some(Play::Stand));
+        assert_eq!(decision.value, 1.0);
+    }
+
+    #[test]
+    fn star_pruning_keeps_the_expectimax_value() {
+        let game = DiceDuel;
+        let states = [
+            game.initial_state(),
+            Duel::new([3, 0], 1, 3),
+            Duel::new([8, 6], 0, 2),
+        ];
+        for state in &states {
+            for depth in [1, 2, 3, 5, usize::MAX] {
+                let expected = game.expectimax(state, depth, &lead);
+                for pruning in [Star::Star1, Star::Star2] {
+                    let decision = game.star_minimax(state, depth, &lead, pruning);
+                    assert!((decision.value - expected.value).abs() < 1e-9);
+                    if pruning == Star::Star1 {
+                        assert!(decision.expanded <= expected.expanded);
+                    }
+                }
+            }
+        }
+
+        let state = game.initial_state();
+        let expectimax = game.expectimax(&state, usize::MAX, &unused);
+        let star1 = game.star_minimax(&state, usize::MAX, &unused, Star::Star1);
+        let star2 = game.star_minimax(&state, usize::MAX, &unused, Star::Star2);
+        println!(
+            "Expectimax: {} expansions, Star1: {}, Star2: {}",
+            expectimax.expanded, star1.expanded, star2.expanded
+        );
+        assert!(star1.expanded < expectimax.expanded);
+        assert!(star2.expanded < expectimax.expanded);
+    }
+}
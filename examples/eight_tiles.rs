@@ -1,4 +1,4 @@
-use search::{self, State, Action};
+use search::{self, State, Action, CostAction};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum EightTilesAction {
@@ -10,6 +10,12 @@ pub enum EightTilesAction {
 
 impl Action for EightTilesAction {}
 
+impl CostAction for EightTilesAction {
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum TileType {
     Empty,
@@ -71,6 +77,20 @@ impl EightTiles {
     fn solved() -> Self {
         EightTiles::new([[1, 2, 3], [4, 5, 6], [7, 8, 0]])
     }
+
+    /// Sum of the distances of every tile from its solved position
+    pub fn manhattan_distance(&self) -> usize {
+        let mut distance = 0;
+        for i in 0..3 {
+            for j in 0..3 {
+                if let TileType::Number(n) = self.tiles[i][j] {
+                    let (goal_i, goal_j) = ((n as usize - 1) / 3, (n as usize - 1) % 3);
+                    distance += goal_i.abs_diff(i) + goal_j.abs_diff(j);
+                }
+            }
+        }
+        distance
+    }
 }
 
 impl State for EightTiles {
@@ -125,6 +145,102 @@ impl std::fmt::Display for EightTiles {
     }
 }
 
+/// Cell of the tiles outside of a pattern, which can't be told apart
+const OTHER: u8 = 9;
+
+/// Eight tiles where only the tiles of a pattern are told apart. Moving a
+/// tile of the pattern costs one, moving another one is free, so that the
+/// costs of disjoint patterns add up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PatternTiles {
+    /// Row by row, zero for the empty cell
+    cells: [u8; 9],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternMove {
+    direction: EightTilesAction,
+    cost: usize,
+}
+
+impl Action for PatternMove {}
+
+impl CostAction for PatternMove {
+    fn cost(&self) -> usize {
+        self.cost
+    }
+}
+
+impl PatternTiles {
+    /// Abstraction of `tiles` keeping the tiles of `pattern`
+    pub fn new(tiles: &EightTiles, pattern: &[u8]) -> Self {
+        let mut cells = [0; 9];
+        for (cell, tile) in cells.iter_mut().zip(tiles.tiles.iter().flatten()) {
+            *cell = match tile {
+                TileType::Empty => 0,
+                TileType::Number(n) if pattern.contains(n) => *n,
+                TileType::Number(_) => OTHER,
+            };
+        }
+        Self { cells }
+    }
+
+    /// Cells of the tiles of `pattern`, in base 9: the position of the empty
+    /// cell doesn't matter
+    pub fn key(&self, pattern: &[u8]) -> usize {
+        pattern.iter().rev().fold(0, |key, tile| {
+            key * 9 + self.cells.iter().position(|cell| cell == tile).unwrap()
+        })
+    }
+}
+
+impl State for PatternTiles {
+    type Action = PatternMove;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        let empty = self.cells.iter().position(|&cell| cell == 0).unwrap();
+        let (x, y) = (empty % 3, empty / 3);
+        [
+            (x > 0, EightTilesAction::Left, empty.wrapping_sub(1)),
+            (x < 2, EightTilesAction::Right, empty + 1),
+            (y > 0, EightTilesAction::Up, empty.wrapping_sub(3)),
+            (y < 2, EightTilesAction::Down, empty + 3),
+        ]
+        .into_iter()
+        .filter(|(possible, ..)| *possible)
+        .map(|(_, direction, moved)| PatternMove {
+            direction,
+            cost: usize::from(self.cells[moved] != OTHER),
+        })
+        .collect()
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        let empty = self.cells.iter().position(|&cell| cell == 0).unwrap();
+        let moved = match action.direction {
+            EightTilesAction::Left => empty - 1,
+            EightTilesAction::Right => empty + 1,
+            EightTilesAction::Up => empty - 3,
+            EightTilesAction::Down => empty + 3,
+        };
+        let mut cells = self.cells;
+        cells.swap(empty, moved);
+        Self { cells }
+    }
+}
+
+/// Database of the costs of moving the tiles of `pattern` to their place
+pub fn pattern_database(pattern: &[u8]) -> search::PatternDatabase {
+    let goal = PatternTiles::new(&EightTiles::solved(), pattern);
+    search::PatternDatabase::build(
+        &[EightTiles::solved()],
+        |tiles: &EightTiles| PatternTiles::new(tiles, pattern),
+        |tiles: &PatternTiles| *tiles == goal,
+        |tiles: &PatternTiles| tiles.key(pattern),
+        9usize.pow(pattern.len() as u32),
+    )
+}
+
 #[derive(Clone)]
 pub struct EightTilesSpace {
     initial_state: EightTiles,
@@ -152,7 +268,12 @@ impl search::Space for EightTilesSpace {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use search::{DepthFirstSearch, BreadthFirstSearch};
+    use search::{
+        AStarSearch, AnytimeConfig, AnytimeSearch, BreadthFirstSearch, DepthFirstSearch, Heuristic,
+        HeuristicCheck, HeuristicCheckConfig, HeuristicViolation, IterativeDeepeningAStar,
+        MemoryBoundedAStar, PlanEdit, PlanShortening, RecursiveBestFirstSearch, SearchLimits,
+        ShortcutConfig, Space,
+    };
 
     mod test_utils {
         use super::*;
@@ -167,9 +288,13 @@ mod tests {
             get_state_space([[1, 2, 3], [4, 5, 6], [7, 0, 8]])
         }
 
-        // pub fn get_hard_problem_space() -> EightTilesSpace {
-        //     get_state_space([[2, 7, 3], [1, 6, 4], [8, 0, 5]])
-        // }
+        pub fn get_medium_problem_space() -> EightTilesSpace {
+            get_state_space([[2, 3, 7], [1, 0, 8], [6, 5, 4]])
+        }
+
+        pub fn get_hard_problem_space() -> EightTilesSpace {
+            get_state_space([[8, 6, 7], [2, 5, 4], [3, 0, 1]])
+        }
     }
 
     #[test]
@@ -205,4 +330,313 @@ mod tests {
         println!("  Path length: {}", result.path.len());
         println!("  Path: {:?}", result.path);
     }
+
+    #[test]
+    fn search_with_astar() {
+        let space = test_utils::get_hard_problem_space();
+        let result = space.astar_search(&EightTiles::manhattan_distance);
+        assert!(result.is_some());
+        let result = result.unwrap();
+        assert!(space.is_goal(&result.end_state));
+        // one of the two hardest eight puzzle instances
+        assert_eq!(result.path.len(), 31);
+        let bfs_result = space.bfs_search().unwrap();
+        assert_eq!(bfs_result.path.len(), 31);
+        assert!(result.expanded < bfs_result.expanded);
+    }
+
+    #[test]
+    fn search_with_anytime_astar() {
+        let space = test_utils::get_hard_problem_space();
+        let heuristic = EightTiles::manhattan_distance;
+        let config = AnytimeConfig {
+            initial_weight: 5.0,
+            weight_step: 1.0,
+            ..AnytimeConfig::default()
+        };
+        let solutions: Vec<_> = space.anytime_search(&heuristic, config).collect();
+        assert!(!solutions.is_empty());
+        for pair in solutions.windows(2) {
+            assert!(pair[1].cost <= pair[0].cost);
+            assert!(pair[1].bound <= pair[0].bound);
+        }
+        for solution in &solutions {
+            assert!(space.is_goal(&solution.result.end_state));
+            assert_eq!(solution.result.path.len(), solution.cost);
+            assert!(solution.cost as f64 <= 31.0 * solution.bound);
+        }
+        let last = solutions.last().unwrap();
+        assert_eq!(last.cost, 31);
+        assert_eq!(last.bound, 1.0);
+        println!("Anytime A* results:");
+        for solution in &solutions {
+            println!("  Cost {} within {:.2} of optimal", solution.cost, solution.bound);
+        }
+    }
+
+    #[test]
+    fn anytime_astar_stops_at_limits() {
+        let space = test_utils::get_hard_problem_space();
+        let heuristic = EightTiles::manhattan_distance;
+        let config = AnytimeConfig {
+            limits: SearchLimits::new().with_max_expanded(10),
+            ..AnytimeConfig::default()
+        };
+        let mut search = space.anytime_search(&heuristic, config);
+        for solution in search.by_ref() {
+            assert!(solution.result.expanded <= 10);
+        }
+        assert!(search.next().is_none());
+    }
+
+    #[test]
+    fn search_with_ida_star() {
+        let space = test_utils::get_medium_problem_space();
+        let optimal = space.bfs_search().unwrap().path.len();
+        let solution = space.ida_star_search(&EightTiles::manhattan_distance);
+        assert!(solution.is_some());
+        let solution = solution.unwrap();
+        assert!(space.is_goal(&solution.result.end_state));
+        assert_eq!(solution.result.path.len(), optimal);
+        // every iteration but the first expands the previous ones again
+        assert!(solution.regenerated > 0);
+        assert!(solution.regenerated < solution.result.expanded);
+        println!("Iterative deepening A* results:");
+        println!("  Expanded: {}", solution.result.expanded);
+        println!("  Regenerated: {}", solution.regenerated);
+    }
+
+    #[test]
+    fn search_with_rbfs() {
+        let space = test_utils::get_medium_problem_space();
+        let optimal = space.bfs_search().unwrap().path.len();
+        let solution = space.rbfs_search(&EightTiles::manhattan_distance);
+        assert!(solution.is_some());
+        let solution = solution.unwrap();
+        assert!(space.is_goal(&solution.result.end_state));
+        assert_eq!(solution.result.path.len(), optimal);
+        println!("Recursive best first search results:");
+        println!("  Expanded: {}", solution.result.expanded);
+        println!("  Regenerated: {}", solution.regenerated);
+    }
+
+    #[test]
+    fn search_with_sma_star() {
+        let space = test_utils::get_medium_problem_space();
+        let optimal = space.bfs_search().unwrap().path.len();
+        let heuristic = EightTiles::manhattan_distance;
+
+        let roomy = space.sma_star_search(&heuristic, 10_000).unwrap();
+        assert!(space.is_goal(&roomy.result.end_state));
+        assert_eq!(roomy.result.path.len(), optimal);
+        assert_eq!(roomy.regenerated, 0);
+
+        let tight = space.sma_star_search(&heuristic, optimal + 5).unwrap();
+        assert!(space.is_goal(&tight.result.end_state));
+        assert_eq!(tight.result.path.len(), optimal);
+        assert!(tight.regenerated > 0);
+        assert!(tight.peak_nodes <= optimal + 5);
+        println!("Simplified memory-bounded A* results:");
+        println!("  Expanded: {}", tight.result.expanded);
+        println!("  Regenerated: {}", tight.regenerated);
+
+        // the solution can't fit in memory
+        assert!(space.sma_star_search(&heuristic, optimal / 2).is_none());
+    }
+
+    #[test]
+    fn search_with_additive_pattern_databases() {
+        const LOW: [u8; 4] = [1, 2, 3, 4];
+        const HIGH: [u8; 4] = [5, 6, 7, 8];
+        let heuristic = search::AdditivePdb::new()
+            .with_database(pattern_database(&LOW), |tiles: &EightTiles| {
+                PatternTiles::new(tiles, &LOW).key(&LOW)
+            })
+            .with_database(pattern_database(&HIGH), |tiles: &EightTiles| {
+                PatternTiles::new(tiles, &HIGH).key(&HIGH)
+            });
+        let space = test_utils::get_hard_problem_space();
+        let result = space.astar_search(&heuristic).unwrap();
+        assert_eq!(result.path.len(), 31);
+        let manhattan = space.astar_search(&EightTiles::manhattan_distance).unwrap();
+        println!(
+            "Expanded with Manhattan distance: {}, with pattern databases: {}",
+            manhattan.expanded, result.expanded
+        );
+        assert!(result.expanded < manhattan.expanded);
+
+        // every move of a tile is counted, at least as many as its distance
+        let mut state = space.initial_state();
+        for (done, action) in result.path.iter().enumerate() {
+            let h = heuristic.estimate(&state);
+            assert!(h >= state.manhattan_distance());
+            assert!(h <= 31 - done);
+            state = state.apply(action);
+        }
+        assert_eq!(heuristic.estimate(&state), 0);
+    }
+
+    #[test]
+    fn pattern_databases_are_saved_and_loaded() {
+        let database = pattern_database(&[1, 2, 3]);
+        assert_eq!(database.size(), 729);
+        // three tiles on nine cells
+        let reached = (0..database.size())
+            .filter(|&key| database.distance(key).is_some())
+            .count();
+        assert_eq!(reached, 9 * 8 * 7);
+
+        let path = std::env::temp_dir().join(format!("eight_tiles_{}.pdb", std::process::id()));
+        database.save(&path).unwrap();
+        let loaded = search::PatternDatabase::load(&path).unwrap();
+        assert_eq!(loaded, database);
+
+        std::fs::write(&path, b"PDB1\x05").unwrap();
+        let error = search::PatternDatabase::load(&path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "key 729 is out of the 729 keys of the database")]
+    fn pattern_databases_check_their_keys() {
+        pattern_database(&[1, 2, 3]).distance(729);
+    }
+
+    /// Manhattan distance when it is even, zero otherwise
+    fn even_manhattan_distance(tiles: &EightTiles) -> usize {
+        let distance = tiles.manhattan_distance();
+        if distance.is_multiple_of(2) {
+            distance
+        } else {
+            0
+        }
+    }
+
+    #[test]
+    fn manhattan_distance_is_admissible_and_consistent() {
+        let space = test_utils::get_hard_problem_space();
+        let report = space.check_heuristic(
+            &EightTiles::manhattan_distance,
+            HeuristicCheckConfig::default(),
+        );
+        assert_eq!(report.states, 10_000);
+        assert!(!report.exhaustive);
+        report.assert_admissible();
+        report.assert_consistent();
+
+        // the region is full before the first state is fully expanded
+        let config = HeuristicCheckConfig { max_states: 2 };
+        let report = space.check_heuristic(&EightTiles::manhattan_distance, config);
+        assert_eq!(report.states, 2);
+        assert!(!report.exhaustive);
+
+        // a region around the goal, where costs are known
+        let space = test_utils::get_easy_problem_space();
+        let report = space.check_heuristic(
+            &EightTiles::manhattan_distance,
+            HeuristicCheckConfig::default(),
+        );
+        report.assert_admissible();
+        report.assert_consistent();
+    }
+
+    #[test]
+    fn heuristic_check_finds_counterexamples() {
+        let space = test_utils::get_easy_problem_space();
+        let config = HeuristicCheckConfig { max_states: 1_000 };
+        let double = |tiles: &EightTiles| 2 * tiles.manhattan_distance();
+        let report = space.check_heuristic(&double, config);
+        assert!(!report.is_admissible());
+        assert!(!report.is_consistent());
+        // the initial state is a move away from the goal
+        let Some(HeuristicViolation::Overestimate {
+            path,
+            estimate,
+            to_goal,
+            cost,
+            ..
+        }) = report.overestimates().next()
+        else {
+            panic!("no overestimate");
+        };
+        assert!(path.is_empty());
+        assert_eq!((*estimate, *cost), (2, 1));
+        assert_eq!(to_goal, &[EightTilesAction::Right]);
+
+        let report = space.check_heuristic(&even_manhattan_distance, config);
+        report.assert_admissible();
+        let Some(HeuristicViolation::Inconsistent {
+            path,
+            estimate,
+            cost,
+            next_estimate,
+            ..
+        }) = report.inconsistencies().next()
+        else {
+            panic!("no inconsistency");
+        };
+        // moving a tile away from its place, then back
+        assert_eq!(path.len(), 1);
+        assert_eq!((*estimate, *cost, *next_estimate), (2, 1, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "heuristic is inconsistent: h = 2 in")]
+    fn inconsistent_heuristics_fail_the_assertion() {
+        let space = test_utils::get_easy_problem_space();
+        let config = HeuristicCheckConfig { max_states: 1_000 };
+        space
+            .check_heuristic(&even_manhattan_distance, config)
+            .assert_consistent();
+    }
+
+    #[test]
+    fn loops_and_moves_past_the_goal_are_cut() {
+        use EightTilesAction::*;
+        let space = test_utils::get_easy_problem_space();
+        let shortened = space
+            .shorten_plan(&[Left, Right, Right, Up, Down], ShortcutConfig::default())
+            .unwrap();
+        assert_eq!(shortened.path, [Right]);
+        assert_eq!((shortened.original_cost, shortened.cost), (5, 1));
+        assert_eq!(
+            shortened.edits,
+            [
+                PlanEdit::PastGoal {
+                    step: 3,
+                    removed: vec![Up, Down]
+                },
+                PlanEdit::Loop {
+                    step: 0,
+                    removed: vec![Left, Right]
+                },
+            ]
+        );
+
+        // plans must reach a goal to be shortened
+        assert!(space
+            .shorten_plan(&[Left, Right], ShortcutConfig::default())
+            .is_err());
+    }
+
+    #[test]
+    fn shortcuts_replace_detours() {
+        use EightTilesAction::*;
+        let space = test_utils::get_easy_problem_space();
+        // the long way around the four cells of the bottom right corner
+        let detour = [
+            Up, Right, Down, Left, Up, Right, Down, Left, Up, Right, Down,
+        ];
+        let config = ShortcutConfig {
+            max_window: 11,
+            ..ShortcutConfig::default()
+        };
+        let shortened = space.shorten_plan(&detour, config).unwrap();
+        assert_eq!(shortened.path, [Right]);
+        assert!(matches!(
+            shortened.edits[..],
+            [PlanEdit::Shortcut { step: 0, .. }]
+        ));
+    }
 }
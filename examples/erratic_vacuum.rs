@@ -0,0 +1,189 @@
+use search::{Action, NondeterministicSpace, Space, State};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    Suck,
+    Left,
+    Right,
+}
+
+impl Action for Command {}
+
+/// Two squares, A on the left and B on the right
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vacuum {
+    /// 0 for A, 1 for B
+    at: usize,
+    dirty: [bool; 2],
+}
+
+impl Vacuum {
+    pub fn new(at: usize, dirty: [bool; 2]) -> Self {
+        Self { at, dirty }
+    }
+}
+
+impl std::fmt::Debug for Vacuum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let square = |i: usize| if self.dirty[i] { "dirty" } else { "clean" };
+        let at = if self.at == 0 { 'A' } else { 'B' };
+        write!(f, "A {}, B {}, at {}", square(0), square(1), at)
+    }
+}
+
+impl State for Vacuum {
+    type Action = Command;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        vec![Command::Suck, Command::Left, Command::Right]
+    }
+
+    /// Outcome when everything goes as intended
+    fn apply(&self, action: &Self::Action) -> Self {
+        let mut next = *self;
+        match action {
+            Command::Suck => next.dirty[self.at] = false,
+            Command::Left => next.at = 0,
+            Command::Right => next.at = 1,
+        }
+        next
+    }
+}
+
+/// Sucking a dirty square sometimes cleans the other one too, sucking a
+/// clean square sometimes leaves dirt on it. With `slippery` wheels, moving
+/// sometimes fails.
+pub struct ErraticVacuum {
+    start: Vacuum,
+    slippery: bool,
+}
+
+impl Space for ErraticVacuum {
+    type State = Vacuum;
+    type Action = Command;
+
+    fn initial_state(&self) -> Self::State {
+        self.start
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        !state.dirty[0] && !state.dirty[1]
+    }
+}
+
+impl NondeterministicSpace for ErraticVacuum {
+    fn results(&self, state: &Vacuum, action: &Command) -> Vec<Vacuum> {
+        let intended = state.apply(action);
+        let mut results = vec![intended];
+        let other = match action {
+            Command::Suck if state.dirty[state.at] => Vacuum::new(state.at, [false, false]),
+            Command::Suck => {
+                let mut dirtier = *state;
+                dirtier.dirty[state.at] = true;
+                dirtier
+            }
+            Command::Left | Command::Right if self.slippery => *state,
+            Command::Left | Command::Right => intended,
+        };
+        if other != intended {
+            results.push(other);
+        }
+        results
+    }
+}
+
+fn main() {
+    use search::AndOrSearch;
+
+    let world = ErraticVacuum {
+        start: Vacuum::new(0, [true, true]),
+        slippery: false,
+    };
+    match world.and_or_search() {
+        Some(plan) => print!("{}", plan),
+        None => println!("No plan"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{AndOrSearch, ConditionalPlan};
+
+    /// Whether the plan reaches a goal whatever the outcomes
+    fn always_succeeds(
+        world: &ErraticVacuum,
+        state: &Vacuum,
+        plan: &ConditionalPlan<Vacuum>,
+    ) -> bool {
+        let Some(action) = plan.action() else {
+            return world.is_goal(state);
+        };
+        world.results(state, action).iter().all(|outcome| {
+            plan.after(outcome)
+                .is_some_and(|rest| always_succeeds(world, outcome, rest))
+        })
+    }
+
+    #[test]
+    fn plan_branches_on_the_outcome_of_sucking() {
+        let world = ErraticVacuum {
+            start: Vacuum::new(0, [true, true]),
+            slippery: false,
+        };
+        let plan = world.and_or_search().unwrap();
+        // [Suck, if B is still dirty then [Right, Suck] else []]
+        assert_eq!(plan.action(), Some(&Command::Suck));
+        let dirty_b = plan.after(&Vacuum::new(0, [false, true])).unwrap();
+        assert_eq!(dirty_b.action(), Some(&Command::Right));
+        assert_eq!(
+            plan.after(&Vacuum::new(0, [false, false])),
+            Some(&ConditionalPlan::Done)
+        );
+        assert_eq!(plan.depth(), 3);
+        assert!(always_succeeds(&world, &world.initial_state(), &plan));
+        assert_eq!(
+            plan.to_string(),
+            "Suck\n\
+             if A clean, B dirty, at A:\n    \
+                 Right\n    \
+                 Suck\n\
+             if A clean, B clean, at A:\n    \
+                 done\n"
+        );
+    }
+
+    #[test]
+    fn every_start_has_a_plan() {
+        for at in 0..2 {
+            for dirty in [[false, false], [true, false], [false, true], [true, true]] {
+                let world = ErraticVacuum {
+                    start: Vacuum::new(at, dirty),
+                    slippery: false,
+                };
+                let plan = world.and_or_search().unwrap();
+                assert!(always_succeeds(&world, &world.initial_state(), &plan));
+                if world.is_goal(&world.initial_state()) {
+                    assert_eq!(plan, ConditionalPlan::Done);
+                    assert_eq!(plan.to_string(), "done\n");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn slippery_wheels_need_cyclic_plans() {
+        let world = ErraticVacuum {
+            start: Vacuum::new(0, [true, true]),
+            slippery: true,
+        };
+        assert!(world.and_or_search().is_none());
+        // nothing to move for
+        let world = ErraticVacuum {
+            start: Vacuum::new(0, [true, false]),
+            slippery: true,
+        };
+        let plan = world.and_or_search().unwrap();
+        assert!(always_succeeds(&world, &world.initial_state(), &plan));
+    }
+}
@@ -0,0 +1,274 @@
+use search::{Action, CostAction, Space, State};
+
+const WIDTH: usize = 12;
+const HEIGHT: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Action for Move {}
+
+impl CostAction for Move {
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    x: usize,
+    y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance(&self, other: &Cell) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl State for Cell {
+    type Action = Move;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        let mut actions = Vec::new();
+        if self.y > 0 {
+            actions.push(Move::Up);
+        }
+        if self.y < HEIGHT - 1 {
+            actions.push(Move::Down);
+        }
+        if self.x > 0 {
+            actions.push(Move::Left);
+        }
+        if self.x < WIDTH - 1 {
+            actions.push(Move::Right);
+        }
+        actions
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        match action {
+            Move::Up => Cell::new(self.x, self.y - 1),
+            Move::Down => Cell::new(self.x, self.y + 1),
+            Move::Left => Cell::new(self.x - 1, self.y),
+            Move::Right => Cell::new(self.x + 1, self.y),
+        }
+    }
+}
+
+/// Open grid; obstacles are reported to the planner as they move
+pub struct GridWorld {
+    start: Cell,
+    goal: Cell,
+}
+
+impl Space for GridWorld {
+    type State = Cell;
+    type Action = Move;
+
+    fn initial_state(&self) -> Self::State {
+        self.start
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        *state == self.goal
+    }
+}
+
+/// Vertical walls sliding one cell down every tick, wrapping around the grid
+fn obstacles_at(tick: usize) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for (x, length) in [(3, 8), (6, 9), (9, 7)] {
+        for offset in 0..length {
+            cells.push(Cell::new(x, (tick * (x / 3) + offset) % HEIGHT));
+        }
+    }
+    cells
+}
+
+fn main() {
+    use search::IncrementalSearch;
+
+    let world = GridWorld {
+        start: Cell::new(0, 0),
+        goal: Cell::new(WIDTH - 1, HEIGHT - 1),
+    };
+    let heuristic = |cell: &Cell| cell.distance(&world.goal);
+    let mut planner = world.incremental_search(&heuristic);
+    let mut obstacles = Vec::new();
+    for tick in 0..10 {
+        for cell in &obstacles {
+            planner.unblock_state(cell);
+        }
+        obstacles = obstacles_at(tick);
+        for cell in &obstacles {
+            planner.block_state(cell);
+        }
+        match planner.compute_path() {
+            Some(result) => println!(
+                "tick {}: path of length {} after {} expansions",
+                tick,
+                result.path.len(),
+                result.expanded
+            ),
+            None => println!("tick {}: goal unreachable", tick),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{
+        DepthFirstSearch, IncrementalSearch, MemoryBoundedAStar, PlanEdit, PlanShortening,
+        ShortcutConfig,
+    };
+
+    fn world() -> GridWorld {
+        GridWorld {
+            start: Cell::new(0, 0),
+            goal: Cell::new(WIDTH - 1, HEIGHT - 1),
+        }
+    }
+
+    fn visited_cells(world: &GridWorld, path: &[Move]) -> Vec<Cell> {
+        let mut cell = world.initial_state();
+        let mut cells = vec![cell];
+        for action in path {
+            cell = cell.apply(action);
+            cells.push(cell);
+        }
+        cells
+    }
+
+    #[test]
+    fn replanning_matches_planning_from_scratch() {
+        let world = world();
+        let heuristic = |cell: &Cell| cell.distance(&world.goal);
+        let mut planner = world.incremental_search(&heuristic);
+        let mut obstacles = Vec::new();
+        let (mut incremental_work, mut scratch_work) = (0, 0);
+        for tick in 0..12 {
+            for cell in &obstacles {
+                planner.unblock_state(cell);
+            }
+            obstacles = obstacles_at(tick);
+            for cell in &obstacles {
+                planner.block_state(cell);
+            }
+            let result = planner.compute_path().unwrap();
+            incremental_work += result.expanded;
+
+            let mut scratch = world.incremental_search(&heuristic);
+            for cell in &obstacles {
+                scratch.block_state(cell);
+            }
+            let expected = scratch.compute_path().unwrap();
+            scratch_work += expected.expanded;
+
+            assert_eq!(result.path.len(), expected.path.len());
+            let cells = visited_cells(&world, &result.path);
+            assert_eq!(*cells.last().unwrap(), result.end_state);
+            assert!(world.is_goal(&result.end_state));
+            assert!(cells.iter().all(|cell| !obstacles.contains(cell)));
+        }
+        println!("Incremental: {incremental_work} expansions, from scratch: {scratch_work}");
+        assert!(incremental_work < scratch_work);
+    }
+
+    #[test]
+    fn transition_costs_can_change() {
+        let world = world();
+        let heuristic = |cell: &Cell| cell.distance(&world.goal);
+        let mut planner = world.incremental_search(&heuristic);
+        assert_eq!(planner.compute_path().unwrap().path.len(), 22);
+
+        // walling off the goal makes it unreachable
+        let goal = world.goal;
+        let neighbours = [Cell::new(goal.x - 1, goal.y), Cell::new(goal.x, goal.y - 1)];
+        for cell in &neighbours {
+            planner.block_transition(cell, &goal);
+        }
+        assert!(planner.compute_path().is_none());
+
+        // reopening one side at a higher price routes through it
+        planner.set_transition_cost(&neighbours[0], &goal, 5);
+        let result = planner.compute_path().unwrap();
+        assert_eq!(result.path.len(), 22);
+        assert_eq!(result.path.last(), Some(&Move::Right));
+
+        planner.restore_transition(&neighbours[1], &goal);
+        let result = planner.compute_path().unwrap();
+        assert_eq!(result.path.last(), Some(&Move::Down));
+    }
+
+    #[test]
+    fn shortened_dfs_plans_stay_valid() {
+        let world = world();
+        let dfs = world.dfs_search().unwrap();
+        let shortened = world
+            .shorten_plan(&dfs.path, ShortcutConfig::default())
+            .unwrap();
+        assert_eq!(shortened.original_cost, dfs.path.len());
+        assert_eq!(shortened.cost, shortened.path.len());
+        assert_eq!(shortened.cost, 22);
+        assert_eq!(
+            search::validate_plan(&world, &shortened.path),
+            Ok(world.goal)
+        );
+        let removed: usize = shortened
+            .edits
+            .iter()
+            .map(|edit| edit.removed().len())
+            .sum();
+        let added: usize = shortened
+            .edits
+            .iter()
+            .map(|edit| match edit {
+                PlanEdit::Shortcut { replacement, .. } => replacement.len(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(dfs.path.len() + added - removed, shortened.path.len());
+        println!(
+            "Depth first plan of {} moves shortened to {} in {} edits",
+            dfs.path.len(),
+            shortened.path.len(),
+            shortened.edits.len()
+        );
+    }
+
+    #[test]
+    fn sma_star_stays_within_its_memory() {
+        let world = GridWorld {
+            start: Cell::new(0, 0),
+            goal: Cell::new(5, 5),
+        };
+        // blind, so that the search wanders around the many cycles of the grid
+        let heuristic = |_: &Cell| 0;
+        for max_nodes in 2..30 {
+            let solution = world.sma_star_search(&heuristic, max_nodes);
+            // the 11 cells of a shortest path must fit
+            assert_eq!(solution.is_some(), max_nodes >= 11);
+            if let Some(solution) = solution {
+                assert!(world.is_goal(&solution.result.end_state));
+                assert_eq!(solution.result.path.len(), 10);
+                assert!(
+                    solution.peak_nodes <= max_nodes,
+                    "{} nodes held with room for {}",
+                    solution.peak_nodes,
+                    max_nodes
+                );
+            }
+        }
+    }
+}
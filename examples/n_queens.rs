@@ -0,0 +1,455 @@
+use search::{Action, Rng, Space, State};
+
+/// Move the queen of a column to another row
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MoveQueen {
+    column: usize,
+    row: usize,
+}
+
+impl Action for MoveQueen {}
+
+/// One queen per column, `rows[c]` is the row of the queen in column `c`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Queens {
+    rows: Vec<usize>,
+}
+
+impl Queens {
+    pub fn new(rows: Vec<usize>) -> Self {
+        Self { rows }
+    }
+
+    pub fn random(n: usize, rng: &mut Rng) -> Self {
+        Self::new((0..n).map(|_| rng.below(n)).collect())
+    }
+
+    /// Pairs of queens attacking each other
+    pub fn attacking_pairs(&self) -> usize {
+        let mut pairs = 0;
+        for a in 0..self.rows.len() {
+            for b in a + 1..self.rows.len() {
+                if self.rows[a] == self.rows[b] || self.rows[a].abs_diff(self.rows[b]) == b - a {
+                    pairs += 1;
+                }
+            }
+        }
+        pairs
+    }
+}
+
+impl State for Queens {
+    type Action = MoveQueen;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        let n = self.rows.len();
+        let mut actions = Vec::new();
+        for column in 0..n {
+            for row in 0..n {
+                if row != self.rows[column] {
+                    actions.push(MoveQueen { column, row });
+                }
+            }
+        }
+        actions
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        let mut rows = self.rows.clone();
+        rows[action.column] = action.row;
+        Self { rows }
+    }
+}
+
+impl std::fmt::Display for Queens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows.len() {
+            for &queen in &self.rows {
+                write!(f, "{}", if queen == row { " Q" } else { " ." })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every queen starts on the first row
+pub struct QueensSpace {
+    n: usize,
+}
+
+impl Space for QueensSpace {
+    type State = Queens;
+    type Action = MoveQueen;
+
+    fn initial_state(&self) -> Self::State {
+        Queens::new(vec![0; self.n])
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        state.attacking_pairs() == 0
+    }
+}
+
+fn attacking_pairs(queens: &Queens) -> f64 {
+    queens.attacking_pairs() as f64
+}
+
+fn main() {
+    use search::{AnnealingConfig, ExponentialCooling, SimulatedAnnealing};
+
+    let space = QueensSpace { n: 8 };
+    let schedule = ExponentialCooling {
+        initial: 2.0,
+        factor: 0.999,
+    };
+    let config = AnnealingConfig {
+        target: Some(0.0),
+        ..AnnealingConfig::default()
+    };
+    let result = space.simulated_annealing(&attacking_pairs, &schedule, config);
+    println!(
+        "{} attacking pairs after {} steps",
+        result.value, result.steps
+    );
+    println!("{}", result.state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{
+        evolve, ActionTabu, AllDifferent, AllDifferentBy, AnnealingConfig, Aspiration,
+        BinaryConstraint, ClimbingStrategy, Csp, ExponentialCooling, GeneticConfig, GeneticSearch,
+        HillClimbing, HillClimbingConfig, LateAcceptanceConfig, LateAcceptanceHillClimbing,
+        LinearCooling, LocalSearchResult, LogarithmicCooling, MinConflicts, MinConflictsConfig,
+        Selection, SimulatedAnnealing, StateGenome, StateTabu, TabuConfig, TabuSearch,
+    };
+    use std::collections::HashSet;
+
+    /// One-point crossover of the queen rows
+    fn splice(a: &Queens, b: &Queens, rng: &mut Rng) -> Queens {
+        let cut = rng.below(a.rows.len());
+        Queens::new(
+            a.rows[..cut]
+                .iter()
+                .chain(&b.rows[cut..])
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// One variable per column, whose value is the row of its queen
+    fn queens_csp(n: usize) -> Csp<usize> {
+        let mut csp = Csp::new();
+        for _ in 0..n {
+            csp.add_variable((0..n).collect());
+        }
+        for a in 0..n {
+            for b in a + 1..n {
+                csp.add_constraint(BinaryConstraint::new(
+                    a,
+                    b,
+                    move |ra: &usize, rb: &usize| ra != rb && ra.abs_diff(*rb) != b - a,
+                ));
+            }
+        }
+        csp
+    }
+
+    /// Same variables, with the rows and the two diagonal directions of the
+    /// queens all different, so the encoding is linear in `n`
+    fn linear_queens_csp(n: usize) -> Csp<usize> {
+        let mut csp = Csp::new();
+        let columns: Vec<usize> = csp.add_variables(n, (0..n).collect()).collect();
+        csp.add_constraint(AllDifferent::new(columns.clone()));
+        csp.add_constraint(AllDifferentBy::new(
+            columns.clone(),
+            |column, row: &usize| row + column,
+        ));
+        csp.add_constraint(AllDifferentBy::new(columns, move |column, row: &usize| {
+            row + n - column
+        }));
+        csp
+    }
+
+    fn assert_trajectory(result: &LocalSearchResult<Queens>) {
+        assert_eq!(result.trajectory.len(), result.steps + 1);
+        assert_eq!(*result.trajectory.last().unwrap(), result.value);
+        assert!(result.trajectory.windows(2).all(|pair| pair[1] <= pair[0]));
+    }
+
+    #[test]
+    fn steepest_ascent_reaches_a_local_optimum() {
+        let space = QueensSpace { n: 8 };
+        let result = space.hill_climbing(&attacking_pairs, HillClimbingConfig::default());
+        assert!(result.value < attacking_pairs(&space.initial_state()));
+        assert_eq!(result.value, attacking_pairs(&result.state));
+        // no neighbour is better
+        for action in result.state.get_available_actions() {
+            assert!(attacking_pairs(&result.state.apply(&action)) >= result.value);
+        }
+    }
+
+    #[test]
+    fn random_restarts_solve_eight_queens() {
+        let space = QueensSpace { n: 8 };
+        for strategy in [
+            ClimbingStrategy::SteepestAscent,
+            ClimbingStrategy::FirstChoice,
+        ] {
+            let config = HillClimbingConfig {
+                strategy,
+                sideways_moves: 20,
+                target: Some(0.0),
+                seed: 7,
+                ..HillClimbingConfig::default()
+            };
+            let result = space.random_restart_hill_climbing(&attacking_pairs, config, 100, |rng| {
+                Queens::random(8, rng)
+            });
+            assert_eq!(result.value, 0.0);
+            assert!(space.is_goal(&result.state));
+            println!(
+                "{:?}: {} restarts, {} steps",
+                strategy, result.restarts, result.steps
+            );
+        }
+    }
+
+    #[test]
+    fn simulated_annealing_solves_eight_queens() {
+        let space = QueensSpace { n: 8 };
+        let config = AnnealingConfig {
+            target: Some(0.0),
+            seed: 42,
+            ..AnnealingConfig::default()
+        };
+        let exponential = ExponentialCooling {
+            initial: 2.0,
+            factor: 0.999,
+        };
+        let linear = LinearCooling {
+            initial: 2.0,
+            steps: 50_000,
+        };
+        let logarithmic = LogarithmicCooling { initial: 1.0 };
+        for result in [
+            space.simulated_annealing(&attacking_pairs, &exponential, config),
+            space.simulated_annealing(&attacking_pairs, &linear, config),
+            space.simulated_annealing(&attacking_pairs, &logarithmic, config),
+        ] {
+            assert_eq!(result.value, 0.0);
+            assert!(space.is_goal(&result.state));
+        }
+    }
+
+    #[test]
+    fn runs_are_reproducible() {
+        let space = QueensSpace { n: 10 };
+        let schedule = |step: usize| 1.0 / (1.0 + step as f64 / 100.0);
+        let config = AnnealingConfig {
+            max_steps: 2_000,
+            seed: 3,
+            ..AnnealingConfig::default()
+        };
+        let first = space.simulated_annealing(&attacking_pairs, &schedule, config);
+        let second = space.simulated_annealing(&attacking_pairs, &schedule, config);
+        assert_eq!(first.state, second.state);
+        assert_eq!(first.evaluations, second.evaluations);
+    }
+
+    #[test]
+    fn tabu_search_solves_twelve_queens() {
+        let space = QueensSpace { n: 12 };
+        let config = TabuConfig {
+            tenure: 5,
+            target: Some(0.0),
+            ..TabuConfig::default()
+        };
+        // moving the same column again is tabu
+        let moved_column = |_: &Queens, action: &MoveQueen, _: &Queens| action.column;
+        let result = space.tabu_search(&attacking_pairs, &moved_column, config);
+        assert_eq!(result.value, 0.0);
+        assert!(space.is_goal(&result.state));
+        assert_trajectory(&result);
+
+        let revisits = StateTabu(|queens: &Queens| queens.rows.clone());
+        let result = space.tabu_search(&attacking_pairs, &revisits, config);
+        assert_eq!(result.value, 0.0);
+        assert_trajectory(&result);
+    }
+
+    #[test]
+    fn tabu_moves_are_avoided_unless_aspired() {
+        let space = QueensSpace { n: 6 };
+        let config = TabuConfig {
+            tenure: 1_000,
+            max_steps: 100,
+            aspiration: Aspiration::Never,
+            target: None,
+        };
+        let result = space.tabu_search(&attacking_pairs, &ActionTabu, config);
+        assert_trajectory(&result);
+        // no action is ever repeated, so the search stops once each of the
+        // 6 * 6 moves has been made
+        assert_eq!(result.steps, 36);
+
+        let config = TabuConfig {
+            aspiration: Aspiration::ImprovesBest,
+            ..config
+        };
+        let result = space.tabu_search(&attacking_pairs, &ActionTabu, config);
+        assert!(result.steps >= 36);
+    }
+
+    #[test]
+    fn late_acceptance_solves_twelve_queens() {
+        let space = QueensSpace { n: 12 };
+        let config = LateAcceptanceConfig {
+            history_length: 20,
+            target: Some(0.0),
+            seed: 11,
+            ..LateAcceptanceConfig::default()
+        };
+        let result = space.late_acceptance_hill_climbing(&attacking_pairs, config);
+        assert_eq!(result.value, 0.0);
+        assert!(space.is_goal(&result.state));
+        assert_trajectory(&result);
+        println!("Late acceptance: {} steps", result.steps);
+    }
+
+    #[test]
+    fn genetic_search_solves_eight_queens() {
+        let space = QueensSpace { n: 8 };
+        for selection in [Selection::Tournament(3), Selection::Roulette] {
+            let config = GeneticConfig {
+                selection,
+                target_fitness: Some(0.0),
+                seed: 5,
+                ..GeneticConfig::default()
+            };
+            let result = space.genetic_search(&attacking_pairs, Some(splice), config);
+            assert_eq!(result.value, 0.0);
+            assert!(space.is_goal(&result.state));
+            assert_trajectory(&result);
+            println!("{:?}: {} generations", selection, result.steps);
+        }
+    }
+
+    #[test]
+    fn evolution_is_reproducible_and_keeps_elites() {
+        let mut rng = Rng::seed_from_u64(1);
+        let population: Vec<_> = (0..30)
+            .map(|_| StateGenome::new(Queens::random(10, &mut rng), &attacking_pairs))
+            .collect();
+        let config = GeneticConfig {
+            population_size: 30,
+            generations: 40,
+            elitism: 2,
+            seed: 9,
+            ..GeneticConfig::default()
+        };
+        let first = evolve(population.clone(), &config);
+        let second = evolve(population, &config);
+        assert_eq!(first.best.state, second.best.state);
+        assert_eq!(first.trajectory, second.trajectory);
+        assert_eq!(first.generations, 40);
+        assert_eq!(first.evaluations, 30 + 40 * 28);
+        assert_eq!(first.generation_best.len(), 41);
+        // elitism never loses the best individual
+        assert!(first
+            .generation_best
+            .windows(2)
+            .all(|pair| pair[1] >= pair[0]));
+    }
+
+    #[test]
+    fn evolution_without_elitism_can_lose_the_best() {
+        let mut rng = Rng::seed_from_u64(1);
+        let population: Vec<_> = (0..30)
+            .map(|_| StateGenome::new(Queens::random(10, &mut rng), &attacking_pairs))
+            .collect();
+        let config = GeneticConfig {
+            population_size: 30,
+            generations: 40,
+            elitism: 0,
+            mutation_rate: 0.9,
+            seed: 9,
+            ..GeneticConfig::default()
+        };
+        let evolution = evolve(population, &config);
+        assert!(evolution
+            .generation_best
+            .windows(2)
+            .any(|pair| pair[1] < pair[0]));
+        // the best found so far is still kept aside
+        assert!(evolution
+            .trajectory
+            .windows(2)
+            .all(|pair| pair[1] >= pair[0]));
+        assert_eq!(evolution.fitness, *evolution.trajectory.last().unwrap());
+    }
+
+    #[test]
+    fn min_conflicts_solves_fifty_queens() {
+        let csp = queens_csp(50);
+        let result = csp.min_conflicts(MinConflictsConfig::default());
+        println!("Min-conflicts: {:?}", result.stats);
+        let rows = result.solution.unwrap();
+        assert!(csp.is_solution(&rows));
+        assert_eq!(Queens::new(rows).attacking_pairs(), 0);
+        assert_eq!(result.stats.backtracks, 0);
+        // the greedy start leaves few conflicts to repair
+        assert!(result.stats.assignments < 50 + 200);
+    }
+
+    /// Solve `n` queens with the linear encoding, checking the solution in
+    /// linear time
+    fn assert_min_conflicts_solves(n: usize) {
+        let csp = linear_queens_csp(n);
+        let result = csp.min_conflicts(MinConflictsConfig::default());
+        println!("Min-conflicts: {:?}", result.stats);
+        let rows = result.solution.unwrap();
+        let rows_used: HashSet<usize> = rows.iter().copied().collect();
+        let sums: HashSet<usize> = rows.iter().enumerate().map(|(c, r)| r + c).collect();
+        let differences: HashSet<usize> = rows.iter().enumerate().map(|(c, r)| r + n - c).collect();
+        assert_eq!(rows_used.len(), n);
+        assert_eq!(sums.len(), n);
+        assert_eq!(differences.len(), n);
+        // a few repairs per queen at most
+        assert!(result.stats.assignments < 2 * n);
+    }
+
+    #[test]
+    fn min_conflicts_scales_to_ten_thousand_queens() {
+        assert_min_conflicts_solves(10_000);
+    }
+
+    #[test]
+    #[ignore = "slow in debug builds"]
+    fn min_conflicts_scales_to_a_hundred_thousand_queens() {
+        assert_min_conflicts_solves(100_000);
+    }
+
+    #[test]
+    fn min_conflicts_is_reproducible_and_gives_up() {
+        let csp = queens_csp(20);
+        let config = MinConflictsConfig {
+            seed: 4,
+            ..MinConflictsConfig::default()
+        };
+        let first = csp.min_conflicts(config);
+        let second = csp.min_conflicts(config);
+        assert_eq!(first.solution, second.solution);
+        assert_eq!(first.stats, second.stats);
+
+        // three queens can't be placed
+        let result = queens_csp(3).min_conflicts(MinConflictsConfig {
+            max_steps: 1_000,
+            ..config
+        });
+        assert!(result.solution.is_none());
+        assert_eq!(result.stats.assignments, 3 + 1_000);
+    }
+}
@@ -0,0 +1,123 @@
+use search::{BoolVar, Cnf, Lit};
+
+/// `pigeons` pigeons in `holes` holes, at most one per hole: unsatisfiable
+/// with more pigeons than holes, and hard for resolution
+fn pigeonhole(pigeons: usize, holes: usize) -> Cnf {
+    let mut cnf = Cnf::new();
+    let sits: Vec<Vec<BoolVar>> = (0..pigeons)
+        .map(|_| (0..holes).map(|_| cnf.new_variable()).collect())
+        .collect();
+    for pigeon in &sits {
+        cnf.add_clause(pigeon.iter().map(|&var| Lit::positive(var)).collect());
+    }
+    // no two pigeons in the same hole
+    for (i, first) in sits.iter().enumerate() {
+        for second in &sits[i + 1..] {
+            for (&a, &b) in first.iter().zip(second) {
+                cnf.add_clause(vec![Lit::negative(a), Lit::negative(b)]);
+            }
+        }
+    }
+    cnf
+}
+
+fn main() {
+    use search::{CdclConfig, CdclSolver};
+
+    let cnf = pigeonhole(7, 6);
+    print!("{}", cnf);
+    let result = cnf.solve_cdcl(CdclConfig::default());
+    println!(
+        "c {} after {:?}",
+        if result.model.is_some() {
+            "SATISFIABLE"
+        } else {
+            "UNSATISFIABLE"
+        },
+        result.stats
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{CdclConfig, CdclSolver, DimacsError};
+
+    #[test]
+    fn pigeons_need_enough_holes() {
+        for holes in 1..=6 {
+            let cnf = pigeonhole(holes + 1, holes);
+            let result = cnf.solve_cdcl(CdclConfig::default());
+            assert!(result.model.is_none());
+            println!("{} holes: {:?}", holes, result.stats);
+
+            let cnf = pigeonhole(holes, holes);
+            let model = cnf.solve_cdcl(CdclConfig::default()).model.unwrap();
+            assert!(cnf.is_satisfied_by(&model));
+        }
+    }
+
+    #[test]
+    fn restarts_and_phases_keep_the_answers() {
+        let configs = [
+            CdclConfig {
+                restart_interval: 1,
+                ..CdclConfig::default()
+            },
+            CdclConfig {
+                activity_decay: 0.5,
+                initial_phase: true,
+                ..CdclConfig::default()
+            },
+        ];
+        for config in configs {
+            let result = pigeonhole(6, 5).solve_cdcl(config);
+            assert!(result.model.is_none());
+            assert!(result.stats.learned_clauses > 0);
+            let cnf = pigeonhole(5, 5);
+            assert!(cnf.is_satisfied_by(&cnf.solve_cdcl(config).model.unwrap()));
+        }
+        let result = pigeonhole(6, 5).solve_cdcl(configs[0]);
+        assert!(result.stats.restarts > 0);
+    }
+
+    #[test]
+    fn dimacs_round_trip() {
+        let cnf = pigeonhole(3, 2);
+        let dimacs = cnf.to_string();
+        assert!(dimacs.starts_with("p cnf 6 9\n1 2 0\n"));
+        assert_eq!(dimacs.parse::<Cnf>(), Ok(cnf));
+
+        let input = "c two clauses\nc over three variables\np cnf 3 2\n1 -3 0\n2 3\n-1 0\n%\n0\n";
+        let cnf: Cnf = input.parse().unwrap();
+        assert_eq!(cnf.variables(), 3);
+        assert_eq!(
+            cnf.clauses(),
+            [
+                vec![Lit::positive(0), Lit::negative(2)],
+                vec![Lit::positive(1), Lit::positive(2), Lit::negative(0)]
+            ]
+        );
+        let model = cnf.solve_cdcl(CdclConfig::default()).model.unwrap();
+        assert!(cnf.is_satisfied_by(&model));
+
+        // empty clauses can't be satisfied
+        let cnf: Cnf = "p cnf 1 2\n1 0\n0\n".parse().unwrap();
+        assert!(cnf.solve_cdcl(CdclConfig::default()).model.is_none());
+    }
+
+    #[test]
+    fn malformed_dimacs() {
+        let line = |input: &str| {
+            input
+                .parse::<Cnf>()
+                .map_err(|error: DimacsError| error.line)
+        };
+        assert_eq!(line("1 2 0\n"), Err(1));
+        assert_eq!(line("c\np cnf 2\n"), Err(2));
+        assert_eq!(line("p cnf 2 1\n1 x 0\n"), Err(2));
+        assert_eq!(line("p cnf 2 1\n1 3 0\n"), Err(2));
+        assert_eq!(line("p cnf 2 2\n1 2 0\n"), Err(2));
+        assert_eq!(line("p cnf 2 1\np cnf 2 1\n"), Err(2));
+    }
+}
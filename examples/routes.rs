@@ -0,0 +1,115 @@
+use search::{Action, MultiCostAction, Space, State};
+
+/// Travel along a road, spending time and energy
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Travel {
+    to: char,
+    time: usize,
+    energy: usize,
+}
+
+impl Action for Travel {}
+
+impl MultiCostAction for Travel {
+    fn costs(&self) -> Vec<usize> {
+        vec![self.time, self.energy]
+    }
+}
+
+const ROADS: [(char, char, usize, usize); 8] = [
+    ('A', 'B', 1, 5),
+    ('A', 'C', 3, 1),
+    ('A', 'E', 2, 2),
+    ('B', 'C', 1, 1),
+    ('B', 'D', 1, 5),
+    ('C', 'D', 3, 1),
+    ('D', 'F', 1, 1),
+    ('E', 'F', 3, 3),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct City(char);
+
+impl State for City {
+    type Action = Travel;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        ROADS
+            .iter()
+            .filter(|(from, ..)| *from == self.0)
+            .map(|&(_, to, time, energy)| Travel { to, time, energy })
+            .collect()
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        City(action.to)
+    }
+}
+
+pub struct RouteSpace {
+    from: char,
+    to: char,
+}
+
+impl Space for RouteSpace {
+    type State = City;
+    type Action = Travel;
+
+    fn initial_state(&self) -> Self::State {
+        City(self.from)
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        state.0 == self.to
+    }
+}
+
+fn main() {
+    use search::ParetoSearch;
+
+    let space = RouteSpace { from: 'A', to: 'F' };
+    for solution in space.pareto_search() {
+        let stops: String = solution
+            .result
+            .path
+            .iter()
+            .map(|travel| travel.to)
+            .collect();
+        println!(
+            "time {:2} energy {:2} via A{}",
+            solution.cost[0], solution.cost[1], stops
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{dominates, ParetoSearch};
+
+    #[test]
+    fn pareto_front_of_routes() {
+        let space = RouteSpace { from: 'A', to: 'F' };
+        let front = space.pareto_search();
+        let mut costs: Vec<Vec<usize>> =
+            front.iter().map(|solution| solution.cost.clone()).collect();
+        costs.sort();
+        assert_eq!(costs, vec![vec![3, 11], vec![5, 5], vec![7, 3]]);
+        for solution in &front {
+            assert!(space.is_goal(&solution.result.end_state));
+            let total = solution.result.path.iter().fold(vec![0, 0], |acc, travel| {
+                vec![acc[0] + travel.time, acc[1] + travel.energy]
+            });
+            assert_eq!(total, solution.cost);
+            assert!(front
+                .iter()
+                .all(|other| !dominates(&other.cost, &solution.cost)));
+        }
+    }
+
+    #[test]
+    fn pareto_front_of_unreachable_goal() {
+        let space = RouteSpace { from: 'F', to: 'A' };
+        assert!(space.pareto_search().is_empty());
+    }
+}
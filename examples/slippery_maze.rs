@@ -0,0 +1,273 @@
+use search::{Action, Mdp, State};
+
+const SIZE: usize = 15;
+const GOAL: Position = Position {
+    x: SIZE - 1,
+    y: SIZE - 1,
+};
+/// Probability that the wheels spin and the robot stays in place
+const SLIP: f64 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Action for Step {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    x: usize,
+    y: usize,
+}
+
+impl Position {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Two walls to go around: one open at the top, the other at the bottom
+    fn is_wall(&self) -> bool {
+        (self.x == 5 && self.y < SIZE - 3) || (self.x == 10 && self.y > 2)
+    }
+
+    fn distance_to_goal(&self) -> usize {
+        self.x.abs_diff(GOAL.x) + self.y.abs_diff(GOAL.y)
+    }
+}
+
+impl State for Position {
+    type Action = Step;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        if *self == GOAL {
+            return Vec::new();
+        }
+        [Step::North, Step::South, Step::West, Step::East]
+            .into_iter()
+            .filter(|step| self.apply(step) != *self)
+            .collect()
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        let next = match action {
+            Step::North if self.y + 1 < SIZE => Position::new(self.x, self.y + 1),
+            Step::South if self.y > 0 => Position::new(self.x, self.y - 1),
+            Step::West if self.x > 0 => Position::new(self.x - 1, self.y),
+            Step::East if self.x + 1 < SIZE => Position::new(self.x + 1, self.y),
+            _ => *self,
+        };
+        if next.is_wall() {
+            *self
+        } else {
+            next
+        }
+    }
+}
+
+/// Stochastic shortest path: every step costs 1, even when slipping
+pub struct SlipperyMaze;
+
+impl Mdp for SlipperyMaze {
+    type State = Position;
+
+    fn initial_state(&self) -> Self::State {
+        Position::new(0, 0)
+    }
+
+    fn transitions(&self, state: &Position, action: &Step) -> Vec<(Position, f64)> {
+        vec![(state.apply(action), 1.0 - SLIP), (*state, SLIP)]
+    }
+
+    fn reward(&self, _: &Position, _: &Step, _: &Position) -> f64 {
+        -1.0
+    }
+
+    fn discount(&self) -> f64 {
+        1.0
+    }
+}
+
+fn main() {
+    use search::{LaoStar, MdpConfig};
+
+    let maze = SlipperyMaze;
+    let solution = maze.lao_star(&Position::distance_to_goal, MdpConfig::default());
+    for y in (0..SIZE).rev() {
+        for x in 0..SIZE {
+            let position = Position::new(x, y);
+            let symbol = match solution.policy.get(&position) {
+                _ if position.is_wall() => '#',
+                _ if position == GOAL => '*',
+                Some(Step::North) => '^',
+                Some(Step::South) => 'v',
+                Some(Step::West) => '<',
+                Some(Step::East) => '>',
+                None => '.',
+            };
+            print!("{}", symbol);
+        }
+        println!();
+    }
+    println!(
+        "Expected cost {:.2} after {} backups",
+        -solution.values[&maze.initial_state()],
+        solution.backups
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{LabelledRtdp, LaoStar, MdpConfig, MdpSolution, RtdpConfig, ValueIteration};
+
+    /// Every outcome of the policy is a goal or has an action itself
+    fn assert_closed(solution: &MdpSolution<Position>) {
+        let maze = SlipperyMaze;
+        assert!(solution.policy.contains_key(&maze.initial_state()));
+        for (position, step) in &solution.policy {
+            for (next, _) in maze.transitions(position, step) {
+                assert!(next == GOAL || solution.policy.contains_key(&next));
+            }
+        }
+    }
+
+    #[test]
+    fn heuristic_search_matches_value_iteration() {
+        let maze = SlipperyMaze;
+        let start = maze.initial_state();
+        let exact = maze.value_iteration(MdpConfig::default());
+        assert!(exact.converged);
+        let heuristic = Position::distance_to_goal;
+        let lao = maze.lao_star(&heuristic, MdpConfig::default());
+        let rtdp = maze.labelled_rtdp(&heuristic, RtdpConfig::default());
+        println!(
+            "Backups: value iteration {}, LAO* {}, labelled RTDP {}",
+            exact.backups, lao.backups, rtdp.backups
+        );
+        for solution in [&lao, &rtdp] {
+            assert!(solution.converged);
+            assert_closed(solution);
+            assert!((solution.values[&start] - exact.values[&start]).abs() < 1e-4);
+            // only the states on the way are covered
+            assert!(solution.policy.len() < exact.policy.len() / 2);
+            assert!(solution.backups < exact.backups);
+        }
+        // the shortest path has 48 steps, each taking 1 / (1 - SLIP) tries
+        assert!((exact.values[&start] + 48.0 / (1.0 - SLIP)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn uninformed_heuristic_still_converges() {
+        let maze = SlipperyMaze;
+        let start = maze.initial_state();
+        let zero = |_: &Position| 0;
+        let informed = maze.lao_star(&Position::distance_to_goal, MdpConfig::default());
+        let lao = maze.lao_star(&zero, MdpConfig::default());
+        let rtdp = maze.labelled_rtdp(
+            &zero,
+            RtdpConfig {
+                seed: 3,
+                ..RtdpConfig::default()
+            },
+        );
+        for solution in [&lao, &rtdp] {
+            assert!(solution.converged);
+            assert_closed(solution);
+            assert!((solution.values[&start] - informed.values[&start]).abs() < 1e-4);
+        }
+        assert!(informed.backups < lao.backups);
+    }
+
+    /// Same maze, where the goal is a dead end instead of a terminal state
+    struct UnmarkedGoal;
+
+    impl Mdp for UnmarkedGoal {
+        type State = Position;
+
+        fn initial_state(&self) -> Self::State {
+            SlipperyMaze.initial_state()
+        }
+
+        fn transitions(&self, state: &Position, action: &Step) -> Vec<(Position, f64)> {
+            SlipperyMaze.transitions(state, action)
+        }
+
+        fn reward(&self, state: &Position, action: &Step, next: &Position) -> f64 {
+            SlipperyMaze.reward(state, action, next)
+        }
+
+        fn discount(&self) -> f64 {
+            1.0
+        }
+
+        fn is_terminal(&self, _: &Position) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn dead_ends_are_worth_nothing_more() {
+        let start = SlipperyMaze.initial_state();
+        let heuristic = Position::distance_to_goal;
+        let exact = UnmarkedGoal.value_iteration(MdpConfig::default());
+        let lao = UnmarkedGoal.lao_star(&heuristic, MdpConfig::default());
+        let rtdp = UnmarkedGoal.labelled_rtdp(&heuristic, RtdpConfig::default());
+        for solution in [&lao, &rtdp] {
+            assert!(solution.converged);
+            assert_closed(solution);
+            assert_eq!(solution.values[&GOAL], 0.0);
+            assert!((solution.values[&start] - exact.values[&start]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn trials_are_cut_at_the_length_limit() {
+        let maze = SlipperyMaze;
+        let heuristic = Position::distance_to_goal;
+        let config = RtdpConfig {
+            max_trials: 1,
+            ..RtdpConfig::default()
+        };
+        let full = maze.labelled_rtdp(&heuristic, config);
+        let short = maze.labelled_rtdp(
+            &heuristic,
+            RtdpConfig {
+                max_trial_length: 3,
+                ..config
+            },
+        );
+        println!(
+            "Backups: full trial {}, short trial {}",
+            full.backups, short.backups
+        );
+        // the goal is 48 steps away
+        assert!(full.backups >= 48);
+        assert!(short.backups < 10);
+        assert!(short.policy.contains_key(&maze.initial_state()));
+    }
+
+    #[test]
+    fn limits_leave_a_partial_policy() {
+        let maze = SlipperyMaze;
+        let heuristic = Position::distance_to_goal;
+        let config = MdpConfig {
+            max_iterations: 5,
+            ..MdpConfig::default()
+        };
+        let lao = maze.lao_star(&heuristic, config);
+        assert!(!lao.converged);
+        assert_eq!(lao.iterations, 5);
+        let config = RtdpConfig {
+            max_trials: 1,
+            ..RtdpConfig::default()
+        };
+        let rtdp = maze.labelled_rtdp(&heuristic, config);
+        assert!(!rtdp.converged);
+        assert_eq!(rtdp.iterations, 1);
+        assert!(rtdp.policy.contains_key(&maze.initial_state()));
+    }
+}
@@ -0,0 +1,224 @@
+use search::{Action, Mdp, State};
+
+const WIDTH: usize = 4;
+const HEIGHT: usize = 3;
+const WALL: Cell = Cell { x: 1, y: 1 };
+const GOAL: Cell = Cell { x: 3, y: 2 };
+const PIT: Cell = Cell { x: 3, y: 1 };
+/// Reward of every move, encouraging short paths
+const STEP: f64 = -0.04;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Action for Move {}
+
+impl Move {
+    /// Directions the robot may slip to, at right angles
+    fn sideways(self) -> [Move; 2] {
+        match self {
+            Move::Up | Move::Down => [Move::Left, Move::Right],
+            Move::Left | Move::Right => [Move::Up, Move::Down],
+        }
+    }
+}
+
+/// Cell of the grid, `y` going up from the bottom row
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    x: usize,
+    y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+}
+
+impl State for Cell {
+    type Action = Move;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        if *self == GOAL || *self == PIT {
+            Vec::new()
+        } else {
+            vec![Move::Up, Move::Down, Move::Left, Move::Right]
+        }
+    }
+
+    /// Intended move, staying in place when blocked
+    fn apply(&self, action: &Self::Action) -> Self {
+        let next = match action {
+            Move::Up if self.y + 1 < HEIGHT => Cell::new(self.x, self.y + 1),
+            Move::Down if self.y > 0 => Cell::new(self.x, self.y - 1),
+            Move::Left if self.x > 0 => Cell::new(self.x - 1, self.y),
+            Move::Right if self.x + 1 < WIDTH => Cell::new(self.x + 1, self.y),
+            _ => *self,
+        };
+        if next == WALL {
+            *self
+        } else {
+            next
+        }
+    }
+}
+
+/// The robot moves as intended 80% of the time, and slips to either side
+/// otherwise. Reaching the goal is worth 1, falling in the pit -1.
+pub struct SlipperyGrid {
+    discount: f64,
+}
+
+impl Mdp for SlipperyGrid {
+    type State = Cell;
+
+    fn initial_state(&self) -> Self::State {
+        Cell::new(0, 0)
+    }
+
+    fn transitions(&self, state: &Cell, action: &Move) -> Vec<(Cell, f64)> {
+        let [left, right] = action.sideways();
+        vec![
+            (state.apply(action), 0.8),
+            (state.apply(&left), 0.1),
+            (state.apply(&right), 0.1),
+        ]
+    }
+
+    fn reward(&self, _: &Cell, _: &Move, next: &Cell) -> f64 {
+        match *next {
+            GOAL => STEP + 1.0,
+            PIT => STEP - 1.0,
+            _ => STEP,
+        }
+    }
+
+    fn discount(&self) -> f64 {
+        self.discount
+    }
+}
+
+fn main() {
+    use search::{MdpConfig, ValueIteration};
+
+    let grid = SlipperyGrid { discount: 1.0 };
+    let solution = grid.value_iteration(MdpConfig::default());
+    for y in (0..HEIGHT).rev() {
+        for x in 0..WIDTH {
+            let cell = Cell::new(x, y);
+            let symbol = match solution.policy.get(&cell) {
+                Some(action) => format!("{:?}", action),
+                None if cell == GOAL => "+1".to_string(),
+                None if cell == PIT => "-1".to_string(),
+                None => "#".to_string(),
+            };
+            print!("{:>6}", symbol);
+        }
+        println!();
+    }
+    println!(
+        "Expected reward from the start: {:.3}",
+        solution.values[&grid.initial_state()]
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{MdpConfig, MdpSolution, PolicyIteration, ValueIteration};
+
+    /// Moves of the optimal policy with the reward of the steps counted on
+    /// entering the terminal states as well
+    fn optimal_moves() -> Vec<(Cell, Move)> {
+        vec![
+            (Cell::new(0, 0), Move::Up),
+            (Cell::new(1, 0), Move::Left),
+            (Cell::new(2, 0), Move::Left),
+            (Cell::new(3, 0), Move::Left),
+            (Cell::new(0, 1), Move::Up),
+            (Cell::new(2, 1), Move::Up),
+            (Cell::new(0, 2), Move::Right),
+            (Cell::new(1, 2), Move::Right),
+            (Cell::new(2, 2), Move::Right),
+        ]
+    }
+
+    fn assert_optimal(solution: &MdpSolution<Cell>) {
+        assert!(solution.converged);
+        assert_eq!(solution.policy.len(), 9);
+        for (cell, action) in optimal_moves() {
+            assert_eq!(solution.policy[&cell], action, "in {:?}", cell);
+        }
+        assert_eq!(solution.values[&GOAL], 0.0);
+        assert!(!solution.values.contains_key(&WALL));
+    }
+
+    #[test]
+    fn value_iteration_finds_the_textbook_values() {
+        let grid = SlipperyGrid { discount: 1.0 };
+        let solution = grid.value_iteration(MdpConfig::default());
+        assert_optimal(&solution);
+        // utilities of the cells, known to three decimals
+        for (cell, value) in [
+            (Cell::new(0, 0), 0.705),
+            (Cell::new(3, 0), 0.388),
+            (Cell::new(0, 2), 0.812),
+            (Cell::new(2, 2), 0.918),
+            (Cell::new(2, 1), 0.660),
+        ] {
+            assert!(
+                (solution.values[&cell] - value).abs() < 1e-3,
+                "in {:?}",
+                cell
+            );
+        }
+    }
+
+    #[test]
+    fn policy_iterations_agree_with_value_iteration() {
+        let grid = SlipperyGrid { discount: 0.9 };
+        let config = MdpConfig::default();
+        let values = grid.value_iteration(config);
+        let policy = grid.policy_iteration(config);
+        let modified = grid.modified_policy_iteration(5, config);
+        let without_sweeps = grid.modified_policy_iteration(0, config);
+        println!(
+            "Backups: value iteration {}, policy iteration {}, modified {}",
+            values.backups, policy.backups, modified.backups
+        );
+        for solution in [&policy, &modified, &without_sweeps] {
+            assert_eq!(solution.policy, values.policy);
+            for (cell, value) in &values.values {
+                assert!((solution.values[cell] - value).abs() < 1e-4);
+            }
+        }
+        assert!(policy.iterations < values.iterations);
+        assert!(modified.iterations < values.iterations);
+        assert_eq!(without_sweeps.iterations, values.iterations);
+    }
+
+    #[test]
+    fn iteration_limit() {
+        let grid = SlipperyGrid { discount: 1.0 };
+        let config = MdpConfig {
+            max_iterations: 3,
+            ..MdpConfig::default()
+        };
+        let solution = grid.value_iteration(config);
+        assert!(!solution.converged);
+        assert_eq!(solution.iterations, 3);
+        assert_eq!(solution.backups, 3 * 9);
+        // three steps from the start can't reach the goal yet
+        assert!((solution.values[&Cell::new(0, 0)] - 3.0 * STEP).abs() < 1e-12);
+
+        // policies that bump into walls forever have no finite value
+        let solution = grid.policy_iteration(MdpConfig::default());
+        assert_optimal(&solution);
+    }
+}
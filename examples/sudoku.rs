@@ -1,89 +1,77 @@
-use search::{State, Action, Space};
+use search::{AllDifferent, Csp};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct SudokuCell {
-    value: Option<u8>,
-}
-
-impl SudokuCell {
-    fn full(value: u8) -> Self {
-        Self { value: Some(value) }
-    }
-
-    fn empty() -> Self {
-        Self { value: None }
-    }
-
-    fn is_full(&self) -> bool {
-        self.value.is_some()
-    }
-
-    fn is_empty(&self) -> bool {
-        self.value.is_none()
-    }
-
-    fn value(&self) -> Option<u8> {
-        self.value
-    }
-}
-
-#[derive(Clone, Eq, Hash, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct SudokuBoard {
-    board: [[SudokuCell; 9]; 9],
+    board: [[Option<u8>; 9]; 9],
 }
 
 impl SudokuBoard {
-    fn new() -> Self {
-        Self {
-            board: [[SudokuCell::empty(); 9]; 9],
+    /// Board from rows of digits, `.` for the empty cells
+    fn parse(rows: [&str; 9]) -> Self {
+        let mut board = [[None; 9]; 9];
+        for (row, line) in rows.iter().enumerate() {
+            for (column, cell) in line.chars().enumerate() {
+                board[row][column] = cell.to_digit(10).map(|digit| digit as u8);
+            }
         }
+        Self { board }
     }
 
-    fn set(&mut self, x: usize, y: usize, value: u8) {
-        self.board[y][x] = SudokuCell::full(value);
+    fn get_value(&self, row: usize, column: usize) -> Option<u8> {
+        self.board[row][column]
     }
 
-    fn clear(&mut self, x: usize, y: usize) {
-        self.board[y][x] = SudokuCell::empty();
+    /// Rows, columns and squares, as cell indices
+    fn units() -> Vec<Vec<usize>> {
+        let mut units = Vec::new();
+        for i in 0..9 {
+            units.push((0..9).map(|j| i * 9 + j).collect());
+            units.push((0..9).map(|j| j * 9 + i).collect());
+            units.push(
+                (0..9)
+                    .map(|j| ((i / 3) * 3 + j / 3) * 9 + (i % 3) * 3 + j % 3)
+                    .collect(),
+            );
+        }
+        units
     }
 
-    fn get(&self, x: usize, y: usize) -> SudokuCell {
-        self.board[y][x]
+    fn is_valid(&self) -> bool {
+        Self::units().iter().all(|unit| {
+            let mut seen = [false; 9];
+            unit.iter()
+                .filter_map(|&cell| self.get_value(cell / 9, cell % 9))
+                .all(|value| !std::mem::replace(&mut seen[value as usize - 1], true))
+        })
     }
 
-    fn get_value(&self, x: usize, y: usize) -> Option<u8> {
-        self.board[y][x].value()
+    fn is_full(&self) -> bool {
+        self.board.iter().flatten().all(Option::is_some)
     }
 
-    fn is_valid(&self) -> bool {
-        for i in 0..9 {
-            let mut row = [false; 9];
-            let mut col = [false; 9];
-            let mut square = [false; 9];
-            for j in 0..9 {
-                if let Some(value) = self.get_value(i, j) {
-                    if row[value as usize - 1] {
-                        return false;
-                    }
-                    row[value as usize - 1] = true;
-                }
-                if let Some(value) = self.get_value(j, i) {
-                    if col[value as usize - 1] {
-                        return false;
-                    }
-                    col[value as usize - 1] = true;
-                }
-                let x = (i % 3) * 3 + j % 3;
-                let y = (i / 3) * 3 + j / 3;
-                if let Some(value) = self.get_value(x, y) {
-                    if square[value as usize - 1] {
-                        return false;
-                    }
-                    square[value as usize - 1] = true;
-                }
+    /// One variable per cell, the given digits have a single value
+    fn to_csp(&self) -> Csp<u8> {
+        let mut csp = Csp::new();
+        for row in 0..9 {
+            for column in 0..9 {
+                match self.get_value(row, column) {
+                    Some(value) => csp.add_variable(vec![value]),
+                    None => csp.add_variable((1..=9).collect()),
+                };
             }
         }
-        true
+        for unit in Self::units() {
+            csp.add_constraint(AllDifferent::new(unit));
+        }
+        csp
+    }
+
+    fn from_values(values: &[u8]) -> Self {
+        let mut board = [[None; 9]; 9];
+        for (cell, &value) in values.iter().enumerate() {
+            board[cell / 9][cell % 9] = Some(value);
+        }
+        Self { board }
     }
 }
 
@@ -103,187 +91,317 @@ impl std::fmt::Display for SudokuBoard {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Actions {
-    Set(usize, usize, u8),
+fn medium_sudoku() -> SudokuBoard {
+    SudokuBoard::parse([
+        "53..7....",
+        "6..195...",
+        ".98....6.",
+        "8...6...3",
+        "4..8.3..1",
+        "7...2...6",
+        ".6....28.",
+        "...419..5",
+        "....8..79",
+    ])
 }
 
-impl Actions {
-    fn set(x: usize, y: usize, value: u8) -> Self {
-        Self::Set(x, y, value)
-    }
+fn main() {
+    use search::{BacktrackingConfig, BacktrackingSearch};
+
+    let board = medium_sudoku();
+    let result = board
+        .to_csp()
+        .backtracking_search(BacktrackingConfig::default());
+    println!("Backtracking search results:");
+    println!("  Assignments: {}", result.stats.assignments);
+    println!("  Backtracks: {}", result.stats.backtracks);
+    println!("  Constraint checks: {}", result.stats.checks);
+    let solution = SudokuBoard::from_values(&result.solution.unwrap());
+    println!("{}", solution);
+    assert!(solution.is_valid() && solution.is_full());
 }
 
-impl Action for Actions {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{
+        ArcConsistency, BacktrackingConfig, BacktrackingSearch, BinaryConstraint, CdclConfig,
+        CdclSolver, Cnf, Inference, ToCnf, ValueOrdering, VariableOrdering,
+    };
+    use std::time::Instant;
+
+    /// Unsolvable: the first row can't hold a 9
+    fn unsolvable_sudoku() -> SudokuBoard {
+        SudokuBoard::parse([
+            "12345678.",
+            "........9",
+            ".........",
+            ".........",
+            ".........",
+            ".........",
+            ".........",
+            ".........",
+            ".........",
+        ])
+    }
 
-impl State for SudokuBoard {
-    type Action = Actions;
+    /// Needs a lot of guessing with forward checking
+    fn hard_sudoku() -> SudokuBoard {
+        SudokuBoard::parse([
+            "8........",
+            "..36.....",
+            ".7..9.2..",
+            ".5...7...",
+            "....457..",
+            "...1...3.",
+            "..1....68",
+            "..85...1.",
+            ".9....4..",
+        ])
+    }
 
-    fn get_available_actions(&self) -> Vec<Self::Action> {
-        let mut actions = Vec::new();
-        for i in 0..9 {
-            for j in 0..9 {
-                if self.get(i, j).is_empty() {
-                    for value in 1..10 {
-                        actions.push(Actions::set(i, j, value));
-                    }
+    #[test]
+    fn valid_medium_sudoku() {
+        let board = medium_sudoku();
+        println!("{}", board);
+        assert!(board.is_valid());
+        assert!(!board.is_full());
+    }
+
+    #[test]
+    fn solve_medium_sudoku() {
+        let board = medium_sudoku();
+        let csp = board.to_csp();
+        let started = Instant::now();
+        let result = csp.backtracking_search(BacktrackingConfig::default());
+        println!("Solved in {:?}: {:?}", started.elapsed(), result.stats);
+        let values = result.solution.unwrap();
+        assert!(csp.is_solution(&values));
+        let solution = SudokuBoard::from_values(&values);
+        println!("{}", solution);
+        assert!(solution.is_valid() && solution.is_full());
+        // forward checking alone solves it without backtracking
+        assert_eq!(result.stats.backtracks, 0);
+    }
+
+    #[test]
+    fn every_ordering_solves_it() {
+        let board = medium_sudoku();
+        let csp = board.to_csp();
+        for variable_ordering in [
+            VariableOrdering::InOrder,
+            VariableOrdering::Mrv,
+            VariableOrdering::MrvDegree,
+        ] {
+            for value_ordering in [ValueOrdering::InOrder, ValueOrdering::LeastConstraining] {
+                for inference in [Inference::ForwardChecking, Inference::Mac] {
+                    let config = BacktrackingConfig {
+                        variable_ordering,
+                        value_ordering,
+                        inference,
+                        ..BacktrackingConfig::default()
+                    };
+                    let result = csp.backtracking_search(config);
+                    assert!(csp.is_solution(&result.solution.unwrap()));
                 }
             }
         }
 
-        // prune invalid actions
-        actions.retain(|action| {
-            let board = self.clone();
-            board.apply(action);
-            board.is_valid()
-        });
-
-        actions
+        // without inference only the variable ordering helps
+        let config = BacktrackingConfig {
+            variable_ordering: VariableOrdering::Mrv,
+            value_ordering: ValueOrdering::InOrder,
+            inference: Inference::None,
+            ..BacktrackingConfig::default()
+        };
+        let result = csp.backtracking_search(config);
+        assert!(csp.is_solution(&result.solution.unwrap()));
+        assert!(result.stats.backtracks > 0);
     }
 
-    fn apply(&self, action: &Self::Action) -> Self {
-        let mut board = self.clone();
-        match action {
-            Actions::Set(x, y, value) => board.set(*x, *y, *value),
-        }
-        board
+    #[test]
+    fn unsolvable_sudoku_has_no_solution() {
+        let csp = unsolvable_sudoku().to_csp();
+        let result = csp.backtracking_search(BacktrackingConfig::default());
+        assert!(result.solution.is_none());
+        assert!(result.stats.backtracks > 0);
     }
-}
 
-struct SudokuSolver {
-    board_to_solve: SudokuBoard,
-}
+    #[test]
+    fn mac_backtracks_less_than_forward_checking() {
+        let csp = hard_sudoku().to_csp();
+        let forward_checking = csp.backtracking_search(BacktrackingConfig::default());
+        let started = Instant::now();
+        let mac = csp.backtracking_search(BacktrackingConfig {
+            inference: Inference::Mac,
+            ..BacktrackingConfig::default()
+        });
+        println!("MAC in {:?}: {:?}", started.elapsed(), mac.stats);
+        println!("Forward checking: {:?}", forward_checking.stats);
+        let values = mac.solution.unwrap();
+        assert!(csp.is_solution(&values));
+        assert_eq!(forward_checking.solution, Some(values));
+        assert!(mac.stats.backtracks < forward_checking.stats.backtracks);
+        assert!(mac.stats.propagations > 0);
+        assert_eq!(forward_checking.stats.propagations, 0);
+    }
 
-impl Space for SudokuSolver {
-    type Action = Actions;
-    type State = SudokuBoard;
+    #[test]
+    fn arc_consistency_solves_medium_sudoku() {
+        let board = medium_sudoku();
+        let csp = board.to_csp();
+        let domains = csp.ac3().unwrap();
+        let solution = csp
+            .backtracking_search(BacktrackingConfig::default())
+            .solution
+            .unwrap();
+        for (domain, value) in domains.iter().zip(&solution) {
+            assert_eq!(domain, &vec![*value]);
+        }
 
-    fn initial_state(&self) -> Self::State {
-        self.board_to_solve.clone()
+        assert!(unsolvable_sudoku().to_csp().ac3().is_none());
     }
 
-    fn is_goal(&self, state: &Self::State) -> bool {
-        state.get_available_actions().is_empty() & state.is_valid()
+    #[test]
+    fn matching_finds_pigeonholes() {
+        // three cells of a row that can only hold 1 or 2
+        let mut all_different = Csp::new();
+        let mut pairwise = Csp::new();
+        for _ in 0..3 {
+            all_different.add_variable(vec![1, 2]);
+            pairwise.add_variable(vec![1, 2]);
+        }
+        all_different.add_constraint(AllDifferent::new(vec![0, 1, 2]));
+        for (first, second) in [(0, 1), (0, 2), (1, 2)] {
+            pairwise.add_constraint(BinaryConstraint::new(first, second, |a: &u8, b: &u8| {
+                a != b
+            }));
+        }
+        assert!(all_different.ac3().is_none());
+        // every pair is arc consistent on its own
+        assert_eq!(pairwise.ac3(), Some(vec![vec![1, 2]; 3]));
+        assert!(pairwise
+            .backtracking_search(BacktrackingConfig::default())
+            .solution
+            .is_none());
+
+        // 1 and 2 go to the first two cells, whatever the order
+        let mut csp = Csp::new();
+        csp.add_variable(vec![1, 2]);
+        csp.add_variable(vec![1, 2]);
+        csp.add_variable(vec![1, 2, 3]);
+        csp.add_variable(vec![2, 3, 4]);
+        csp.add_constraint(AllDifferent::new(vec![0, 1, 2, 3]));
+        csp.add_constraint(BinaryConstraint::new(2, 3, |a: &u8, b: &u8| a < b));
+        let domains = csp.ac3().unwrap();
+        assert_eq!(domains[2], vec![3]);
+        assert_eq!(domains[3], vec![4]);
     }
-}
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use search::DepthFirstSearch;
+    #[test]
+    fn backjumping_skips_unrelated_variables() {
+        // six cells free of constraints, then three cells that can't all
+        // differ with two values
+        let mut csp = Csp::new();
+        for _ in 0..6 {
+            csp.add_variable(vec![1, 2, 3]);
+        }
+        for _ in 0..3 {
+            csp.add_variable(vec![1, 2]);
+        }
+        for (first, second) in [(6, 7), (6, 8), (7, 8)] {
+            csp.add_constraint(BinaryConstraint::new(first, second, |a: &u8, b: &u8| {
+                a != b
+            }));
+        }
+        let chronological = BacktrackingConfig {
+            variable_ordering: VariableOrdering::InOrder,
+            ..BacktrackingConfig::default()
+        };
+        let backjumping = BacktrackingConfig {
+            backjumping: true,
+            ..chronological
+        };
+        let without = csp.backtracking_search(chronological);
+        let with = csp.backtracking_search(backjumping);
+        assert!(without.solution.is_none() && with.solution.is_none());
+        println!("Chronological: {:?}", without.stats);
+        println!("Backjumping: {:?}", with.stats);
+        // every combination of the free cells is tried in vain
+        assert!(without.stats.backtracks > 3usize.pow(6));
+        // the failure has nothing to do with them: once both values of the
+        // first constrained cell fail, the search jumps back to the start
+        assert_eq!(with.stats.backjumps, 1);
+        assert_eq!(with.stats.backtracks, 3);
+        assert_eq!(without.stats.backjumps, 0);
+    }
 
-//     fn medium_sudoku() -> SudokuBoard {
-//         let mut board = SudokuBoard::new();
-//         board.set(0, 0, 5);
-//         board.set(0, 1, 3);
-//         board.set(0, 4, 7);
-//         board.set(1, 0, 6);
-//         board.set(1, 3, 1);
-//         board.set(1, 4, 9);
-//         board.set(1, 5, 5);
-//         board.set(2, 1, 9);
-//         board.set(2, 2, 8);
-//         board.set(2, 7, 6);
-//         board.set(3, 0, 8);
-//         board.set(3, 4, 6);
-//         board.set(3, 8, 3);
-//         board.set(4, 0, 4);
-//         board.set(4, 3, 8);
-//         board.set(4, 5, 3);
-//         board.set(4, 8, 1);
-//         board.set(5, 0, 7);
-//         board.set(5, 4, 2);
-//         board.set(5, 8, 6);
-//         board.set(6, 1, 6);
-//         board.set(6, 6, 2);
-//         board.set(6, 7, 8);
-//         board.set(7, 3, 4);
-//         board.set(7, 4, 1);
-//         board.set(7, 5, 9);
-//         board.set(7, 8, 5);
-//         board.set(8, 4, 8);
-//         board.set(8, 7, 7);
-//         board.set(8, 8, 9);
-//         board
-//     }
+    #[test]
+    fn backjumping_and_nogoods_solve_hard_sudoku() {
+        let csp = hard_sudoku().to_csp();
+        let plain = csp.backtracking_search(BacktrackingConfig::default());
+        for inference in [Inference::ForwardChecking, Inference::Mac] {
+            let config = BacktrackingConfig {
+                inference,
+                backjumping: true,
+                learn_nogoods: true,
+                ..BacktrackingConfig::default()
+            };
+            let started = Instant::now();
+            let result = csp.backtracking_search(config);
+            println!(
+                "{:?} in {:?}: {:?}",
+                inference,
+                started.elapsed(),
+                result.stats
+            );
+            assert_eq!(result.solution, plain.solution);
+            assert!(result.stats.nogoods > 0);
+        }
 
-//     #[test]
-//     fn valid_medium_sudoku() {
-//         let board = medium_sudoku();
-//         println!("{}", board);
-//         assert!(board.is_valid());
-//     }
+        let config = BacktrackingConfig {
+            backjumping: true,
+            ..BacktrackingConfig::default()
+        };
+        let result = csp.backtracking_search(config);
+        assert_eq!(result.solution, plain.solution);
+        assert!(result.stats.backtracks <= plain.stats.backtracks);
+        assert_eq!(result.stats.nogoods, 0);
+
+        let result = unsolvable_sudoku()
+            .to_csp()
+            .backtracking_search(BacktrackingConfig {
+                backjumping: true,
+                learn_nogoods: true,
+                ..BacktrackingConfig::default()
+            });
+        assert!(result.solution.is_none());
+    }
 
-//     #[test]
-//     fn solve_medium_sudoku() {
-//         let board = medium_sudoku();
-//         let solver = SudokuSolver {
-//             board_to_solve: board,
-//         };
-//         let solution = solver.dfs_search();
-//         assert!(solution.is_some());
-//         let solution = solution.unwrap();
-//         let state = solution.end_state;
-//         println!("Breadth first search results:");
-//         println!("  Generated: {}", solution.generated);
-//         println!("  Expanded: {}", solution.expanded);
-//         println!("  Path length: {}", solution.path.len());
-//         println!("  Path: {:?}", solution.path);
-//         println!("{}", &state);
-//         assert!(state.is_valid());
-//     }
-// }
+    #[test]
+    fn sat_solver_agrees_with_backtracking() {
+        for board in [medium_sudoku(), hard_sudoku()] {
+            let csp = board.to_csp();
+            let encoding = csp.to_cnf();
+            assert_eq!(
+                encoding.cnf.variables(),
+                81 * 9 - 8 * board.board.iter().flatten().flatten().count()
+            );
+            let started = Instant::now();
+            let result = encoding.cnf.solve_cdcl(CdclConfig::default());
+            println!("CDCL in {:?}: {:?}", started.elapsed(), result.stats);
+            let model = result.model.unwrap();
+            assert!(encoding.cnf.is_satisfied_by(&model));
+            let values = encoding.decode(&model);
+            let expected = csp.backtracking_search(BacktrackingConfig::default());
+            assert_eq!(Some(values), expected.solution);
+        }
 
-fn main() {
-    use search::BreadthFirstSearch;
-    fn medium_sudoku() -> SudokuBoard {
-        let mut board = SudokuBoard::new();
-        board.set(0, 0, 5);
-        board.set(0, 1, 3);
-        board.set(0, 4, 7);
-        board.set(1, 0, 6);
-        board.set(1, 3, 1);
-        board.set(1, 4, 9);
-        board.set(1, 5, 5);
-        board.set(2, 1, 9);
-        board.set(2, 2, 8);
-        board.set(2, 7, 6);
-        board.set(3, 0, 8);
-        board.set(3, 4, 6);
-        board.set(3, 8, 3);
-        board.set(4, 0, 4);
-        board.set(4, 3, 8);
-        board.set(4, 5, 3);
-        board.set(4, 8, 1);
-        board.set(5, 0, 7);
-        board.set(5, 4, 2);
-        board.set(5, 8, 6);
-        board.set(6, 1, 6);
-        board.set(6, 6, 2);
-        board.set(6, 7, 8);
-        board.set(7, 3, 4);
-        board.set(7, 4, 1);
-        board.set(7, 5, 9);
-        board.set(7, 8, 5);
-        board.set(8, 4, 8);
-        board.set(8, 7, 7);
-        board.set(8, 8, 9);
-        board
+        let encoding = unsolvable_sudoku().to_csp().to_cnf();
+        assert!(encoding.literal(0, &1).is_some());
+        assert!(encoding.literal(0, &2).is_none());
+        // through DIMACS
+        let cnf: Cnf = encoding.cnf.to_string().parse().unwrap();
+        assert!(cnf.solve_cdcl(CdclConfig::default()).model.is_none());
     }
-
-    let board = medium_sudoku();
-    let solver = SudokuSolver {
-        board_to_solve: board,
-    };
-    let solution = solver.bfs_search();
-    assert!(solution.is_some());
-    let solution = solution.unwrap();
-    let state = solution.end_state;
-    println!("Breadth first search results:");
-    println!("  Generated: {}", solution.generated);
-    println!("  Expanded: {}", solution.expanded);
-    println!("  Path length: {}", solution.path.len());
-    println!("  Path: {:?}", solution.path);
-    println!("{}", &state);
-    assert!(state.is_valid());
-}
\ No newline at end of file
+}
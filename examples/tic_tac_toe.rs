@@ -0,0 +1,503 @@
+use search::{Action, Game, State};
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mark {
+    X,
+    O,
+}
+
+impl Mark {
+    pub fn opponent(self) -> Self {
+        match self {
+            Mark::X => Mark::O,
+            Mark::O => Mark::X,
+        }
+    }
+}
+
+/// Put a mark on a cell, numbered row by row from the top left
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Place(usize);
+
+impl Action for Place {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    cells: [Option<Mark>; 9],
+}
+
+impl Board {
+    pub fn empty() -> Self {
+        Self { cells: [None; 9] }
+    }
+
+    /// Board from rows of `X`, `O` and `.`
+    pub fn parse(rows: [&str; 3]) -> Self {
+        let mut cells = [None; 9];
+        for (index, mark) in rows.concat().chars().enumerate() {
+            cells[index] = match mark {
+                'X' => Some(Mark::X),
+                'O' => Some(Mark::O),
+                _ => None,
+            };
+        }
+        Self { cells }
+    }
+
+    /// X always starts
+    pub fn to_move(&self) -> Mark {
+        let marks = self.cells.iter().filter(|cell| cell.is_some()).count();
+        if marks % 2 == 0 {
+            Mark::X
+        } else {
+            Mark::O
+        }
+    }
+
+    pub fn winner(&self) -> Option<Mark> {
+        LINES.iter().find_map(|&[a, b, c]| {
+            let mark = self.cells[a]?;
+            (self.cells[b] == Some(mark) && self.cells[c] == Some(mark)).then_some(mark)
+        })
+    }
+
+    fn empty_cells(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_none()).count()
+    }
+}
+
+impl State for Board {
+    type Action = Place;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        if self.winner().is_some() {
+            return Vec::new();
+        }
+        (0..9)
+            .filter(|&index| self.cells[index].is_none())
+            .map(Place)
+            .collect()
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        let mut cells = self.cells;
+        cells[action.0] = Some(self.to_move());
+        Self { cells }
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(3) {
+            for cell in row {
+                let mark = match cell {
+                    Some(Mark::X) => 'X',
+                    Some(Mark::O) => 'O',
+                    None => '.',
+                };
+                write!(f, "{}", mark)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct TicTacToe;
+
+impl Game for TicTacToe {
+    type State = Board;
+    type Player = Mark;
+
+    fn initial_state(&self) -> Self::State {
+        Board::empty()
+    }
+
+    fn current_player(&self, state: &Self::State) -> Self::Player {
+        state.to_move()
+    }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.winner().is_some() || state.empty_cells() == 0
+    }
+
+    /// Quicker wins are worth more
+    fn utility(&self, state: &Self::State, player: Self::Player) -> f64 {
+        let score = 1.0 + state.empty_cells() as f64;
+        match state.winner() {
+            Some(winner) if winner == player => score,
+            Some(_) => -score,
+            None => 0.0,
+        }
+    }
+}
+
+/// Lines still open for `player` minus the ones still open for the opponent
+fn open_lines(board: &Board, player: Mark) -> f64 {
+    let open = |mark: Mark| {
+        LINES
+            .iter()
+            .filter(|line| line.iter().all(|&cell| board.cells[cell] != Some(mark)))
+            .count() as f64
+    };
+    open(player.opponent()) - open(player)
+}
+
+fn main() {
+    use search::{AlphaBetaConfig, AlphaBetaEngine};
+
+    let game = TicTacToe;
+    let mut engine = game.alpha_beta_engine(&open_lines, AlphaBetaConfig::default());
+    let mut board = game.initial_state();
+    while !game.is_terminal(&board) {
+        let decision = engine.search(&board);
+        board = board.apply(&decision.best_move.unwrap());
+        println!("{}", board);
+    }
+    match board.winner() {
+        Some(winner) => println!("{:?} wins", winner),
+        None => println!("Draw"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use search::{
+        AlphaBetaConfig, AlphaBetaEngine, AlphaBetaSearch, MctsConfig, MinimaxSearch,
+        MonteCarloTreeSearch, SearchLimits, Ucb1,
+    };
+    use std::time::Duration;
+
+    /// Full-depth searches never call the evaluation
+    fn unused(_: &Board, _: Mark) -> f64 {
+        unreachable!()
+    }
+
+    #[test]
+    fn perfect_play_is_a_draw() {
+        let game = TicTacToe;
+        let board = game.initial_state();
+        let minimax = game.minimax(&board, usize::MAX, &unused);
+        let alpha_beta = game.alpha_beta(&board, usize::MAX, &unused);
+        assert_eq!(minimax.value, 0.0);
+        assert_eq!(alpha_beta.value, 0.0);
+        println!(
+            "Minimax: {} expansions, alpha-beta: {}",
+            minimax.expanded, alpha_beta.expanded
+        );
+        assert!(alpha_beta.expanded * 10 < minimax.expanded);
+
+        // both players keep the draw
+        let mut board = board;
+        while !game.is_terminal(&board) {
+            let decision = game.alpha_beta(&board, usize::MAX, &unused);
+            assert_eq!(decision.value, 0.0);
+            board = board.apply(&decision.best_move.unwrap());
+        }
+        assert_eq!(board.winner(), None);
+    }
+
+    #[test]
+    fn wins_are_taken() {
+        let game = TicTacToe;
+        // X can also block O, but winning right away is better
+        let board = Board::parse(["XX.", "OO.", "X.O"]);
+        for decision in [
+            game.minimax(&board, usize::MAX, &unused),
+            game.alpha_beta(&board, usize::MAX, &unused),
+        ] {
+            assert_eq!(decision.best_move, Some(Place(2)));
+            assert_eq!(
+                decision.value,
+                game.utility(&board.apply(&Place(2)), Mark::X)
+            );
+        }
+    }
+
+    #[test]
+    fn threats_are_blocked() {
+        let game = TicTacToe;
+        // O must stop the diagonal
+        let board = Board::parse(["XO.", ".X.", "..."]);
+        assert_eq!(board.to_move(), Mark::O);
+        for decision in [
+            game.minimax(&board, usize::MAX, &unused),
+            game.alpha_beta(&board, usize::MAX, &unused),
+        ] {
+            assert_eq!(decision.best_move, Some(Place(8)));
+        }
+    }
+
+    #[test]
+    fn cut_off_searches_agree() {
+        let game = TicTacToe;
+        let board = Board::parse(["X..", "...", "..."]);
+        for depth in 0..4 {
+            let minimax = game.minimax(&board, depth, &open_lines);
+            let alpha_beta = game.alpha_beta(&board, depth, &open_lines);
+            assert_eq!(minimax.value, alpha_beta.value);
+            assert_eq!(minimax.best_move, alpha_beta.best_move);
+            assert!(alpha_beta.expanded <= minimax.expanded);
+        }
+        let decision = game.alpha_beta(&board, 0, &open_lines);
+        assert_eq!(decision.best_move, None);
+        assert_eq!(decision.value, open_lines(&board, Mark::O));
+        // answering a corner opening anywhere but the centre loses
+        assert_eq!(
+            game.alpha_beta(&board, usize::MAX, &unused).best_move,
+            Some(Place(4))
+        );
+    }
+
+    fn configs() -> Vec<AlphaBetaConfig> {
+        let mut configs = Vec::new();
+        for table_size in [0, 1 << 12] {
+            for killer_moves in [false, true] {
+                for history_heuristic in [false, true] {
+                    configs.push(AlphaBetaConfig {
+                        table_size,
+                        killer_moves,
+                        history_heuristic,
+                        ..AlphaBetaConfig::default()
+                    });
+                }
+            }
+        }
+        configs
+    }
+
+    /// Play the moves of `line` from `board`, checking they are legal
+    fn play(board: &Board, line: &[Place]) -> Board {
+        let mut board = board.clone();
+        for action in line {
+            assert!(board.get_available_actions().contains(action));
+            board = board.apply(action);
+        }
+        board
+    }
+
+    #[test]
+    fn engine_options_keep_the_value() {
+        let game = TicTacToe;
+        let boards = [
+            game.initial_state(),
+            Board::parse(["X..", "...", "..."]),
+            Board::parse(["XO.", ".X.", "..."]),
+            Board::parse(["XX.", "OO.", "X.O"]),
+        ];
+        let plain = game.alpha_beta(&game.initial_state(), usize::MAX, &unused);
+        for config in configs() {
+            for board in &boards {
+                let mut engine = game.alpha_beta_engine(&unused, config);
+                let expected = game.alpha_beta(board, usize::MAX, &unused);
+                let decision = engine.search_depth(board, usize::MAX);
+                assert_eq!(decision.value, expected.value);
+                let line = &decision.principal_variation;
+                assert_eq!(decision.best_move.as_ref(), line.first());
+                play(board, line);
+            }
+            let mut engine = game.alpha_beta_engine(&unused, config);
+            let decision = engine.search_depth(&game.initial_state(), usize::MAX);
+            println!(
+                "table: {}, killers: {}, history: {}: {} expansions",
+                config.table_size, config.killer_moves, config.history_heuristic, decision.expanded
+            );
+            if config.table_size > 0 {
+                assert!(decision.expanded * 2 < plain.expanded);
+            }
+            if config.killer_moves || config.history_heuristic {
+                assert!(decision.expanded < plain.expanded);
+            }
+        }
+
+        // move ordering alone doesn't change the value of cut off searches
+        for config in configs()
+            .into_iter()
+            .filter(|config| config.table_size == 0)
+        {
+            let mut engine = game.alpha_beta_engine(&open_lines, config);
+            for board in &boards {
+                for depth in 1..4 {
+                    let expected = game.alpha_beta(board, depth, &open_lines);
+                    assert_eq!(engine.search_depth(board, depth).value, expected.value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_reports_the_principal_variation() {
+        let game = TicTacToe;
+        let board = game.initial_state();
+        let config = AlphaBetaConfig {
+            table_size: 0,
+            ..AlphaBetaConfig::default()
+        };
+        let mut engine = game.alpha_beta_engine(&open_lines, config);
+        let decision = engine.search(&board);
+        assert_eq!(decision.value, 0.0);
+        // the last iteration reaches the end of every line
+        assert_eq!(decision.depth, 9);
+        assert_eq!(decision.principal_variation.len(), 9);
+        let end = play(&board, &decision.principal_variation);
+        assert!(game.is_terminal(&end));
+        assert_eq!(end.winner(), None);
+
+        let config = AlphaBetaConfig {
+            max_depth: 2,
+            ..AlphaBetaConfig::default()
+        };
+        let mut engine = game.alpha_beta_engine(&open_lines, config);
+        let decision = engine.search(&Board::parse(["XX.", "OO.", "X.O"]));
+        assert_eq!(decision.depth, 2);
+        assert_eq!(decision.best_move, Some(Place(2)));
+        let decision = engine.search(&board);
+        assert_eq!(decision.depth, 2);
+        assert_eq!(decision.principal_variation.len(), 2);
+    }
+
+    #[test]
+    fn limits_interrupt_deeper_iterations() {
+        let game = TicTacToe;
+        let board = game.initial_state();
+        for limits in [
+            SearchLimits::new().with_time_budget(Duration::ZERO),
+            SearchLimits::new().with_max_expanded(1),
+        ] {
+            let config = AlphaBetaConfig {
+                limits,
+                ..AlphaBetaConfig::default()
+            };
+            let mut engine = game.alpha_beta_engine(&open_lines, config);
+            let decision = engine.search(&board);
+            // the first iteration always completes
+            assert_eq!(decision.depth, 1);
+            assert!(decision.best_move.is_some());
+        }
+
+        let config = AlphaBetaConfig {
+            limits: SearchLimits::new().with_max_expanded(200),
+            ..AlphaBetaConfig::default()
+        };
+        let mut engine = game.alpha_beta_engine(&open_lines, config);
+        let decision = engine.search(&board);
+        assert!(decision.depth > 1 && decision.depth < 9);
+        assert_eq!(decision.principal_variation.len(), decision.depth);
+    }
+
+    #[test]
+    fn engine_keeps_its_tables_for_a_whole_game() {
+        let game = TicTacToe;
+        let mut engine = game.alpha_beta_engine(&open_lines, AlphaBetaConfig::default());
+        let mut board = game.initial_state();
+        let mut expansions = Vec::new();
+        while !game.is_terminal(&board) {
+            let decision = engine.search(&board);
+            assert_eq!(decision.value, 0.0);
+            expansions.push(decision.expanded);
+            board = board.apply(&decision.best_move.unwrap());
+        }
+        assert_eq!(board.winner(), None);
+        // later positions were already searched for the first move
+        println!("Expansions per move: {:?}", expansions);
+        assert!(expansions[1..]
+            .iter()
+            .all(|&expanded| expanded < expansions[0]));
+
+        engine.clear();
+        assert_eq!(engine.search(&game.initial_state()).expanded, expansions[0]);
+    }
+
+    /// Utilities go from -10 to 10, explore accordingly
+    const EXPLORATION: Ucb1 = Ucb1 { exploration: 10.0 };
+
+    #[test]
+    fn mcts_takes_wins_and_blocks_threats() {
+        let game = TicTacToe;
+        let config = MctsConfig {
+            iterations: 2_000,
+            seed: 3,
+            ..MctsConfig::default()
+        };
+        for (board, expected) in [
+            (Board::parse(["XX.", "OO.", "X.O"]), Place(2)),
+            (Board::parse(["XO.", ".X.", "..."]), Place(8)),
+        ] {
+            let mut mcts = game.mcts(config).with_selection(EXPLORATION);
+            let decision = mcts.search(&board);
+            assert_eq!(decision.best_move, Some(expected));
+            assert_eq!(decision.principal_variation.first(), Some(&expected));
+            play(&board, &decision.principal_variation);
+        }
+    }
+
+    #[test]
+    fn mcts_holds_the_draw_against_alpha_beta() {
+        let game = TicTacToe;
+        let config = MctsConfig {
+            iterations: 5_000,
+            seed: 1,
+            ..MctsConfig::default()
+        };
+        for mcts_plays in [Mark::X, Mark::O] {
+            let mut mcts = game.mcts(config).with_selection(EXPLORATION);
+            let mut engine = game.alpha_beta_engine(&open_lines, AlphaBetaConfig::default());
+            let mut board = game.initial_state();
+            while !game.is_terminal(&board) {
+                let action = if board.to_move() == mcts_plays {
+                    mcts.search(&board).best_move.unwrap()
+                } else {
+                    engine.search(&board).best_move.unwrap()
+                };
+                mcts.advance(&action);
+                board = board.apply(&action);
+            }
+            assert_eq!(board.winner(), None, "{}", board);
+        }
+    }
+
+    #[test]
+    fn mcts_reuses_its_trees_and_runs_them_in_parallel() {
+        let game = TicTacToe;
+        let config = MctsConfig {
+            iterations: 1_000,
+            trees: 4,
+            seed: 7,
+            ..MctsConfig::default()
+        };
+        let board = Board::parse(["X..", "...", "..."]);
+        let first = game.mcts(config).with_selection(EXPLORATION).search(&board);
+        let second = game.mcts(config).with_selection(EXPLORATION).search(&board);
+        assert_eq!(first.best_move, second.best_move);
+        assert_eq!(first.value, second.value);
+        assert_eq!(first.best_move, Some(Place(4)));
+        // every iteration adds a node to one of the trees
+        assert!(first.expanded <= 4 * 1_000);
+
+        let mut mcts = game.mcts(config).with_selection(EXPLORATION);
+        let decision = mcts.search(&board);
+        assert_eq!(mcts.root_visits(), 4 * 1_000);
+        let action = decision.best_move.unwrap();
+        mcts.advance(&action);
+        let inherited = mcts.root_visits();
+        assert!(inherited > 0);
+        mcts.search(&board.apply(&action));
+        assert_eq!(mcts.root_visits(), inherited + 4 * 1_000);
+
+        // searching elsewhere starts over
+        mcts.search(&board);
+        assert_eq!(mcts.root_visits(), 4 * 1_000);
+    }
+}
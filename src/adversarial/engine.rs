@@ -0,0 +1,346 @@
+use super::{minimax::prepend, Evaluation, Game};
+use crate::{
+    limits::{LimitTracker, SearchLimits},
+    output::GameDecision,
+    State,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+const MAX_HISTORY_DEPTH: usize = 1 << 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AlphaBetaConfig {
+    /// Deepest iteration of iterative deepening
+    pub max_depth: usize,
+    /// Entries of the transposition table, zero to disable it
+    pub table_size: usize,
+    /// Try first the moves that caused a cutoff at the same ply
+    pub killer_moves: bool,
+    /// Try first the moves that caused the most cutoffs so far
+    pub history_heuristic: bool,
+    /// Checked during iterative deepening, whose first iteration always
+    /// completes. An interrupted iteration is discarded.
+    pub limits: SearchLimits,
+}
+
+impl Default for AlphaBetaConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            table_size: 1 << 16,
+            killer_moves: true,
+            history_heuristic: true,
+            limits: SearchLimits::default(),
+        }
+    }
+}
+
+pub trait AlphaBetaEngine<G: Game> {
+    /// Alpha-beta search of a two-player zero-sum game whose tables are kept
+    /// between calls, so that one engine can play a whole game
+    fn alpha_beta_engine<'a, E>(
+        &'a self,
+        evaluation: &'a E,
+        config: AlphaBetaConfig,
+    ) -> AlphaBeta<'a, G, E>
+    where
+        E: Evaluation<G>;
+}
+
+impl<G> AlphaBetaEngine<G> for G
+where
+    G: Game,
+    <G::State as State>::Action: Eq + Hash,
+{
+    fn alpha_beta_engine<'a, E>(
+        &'a self,
+        evaluation: &'a E,
+        config: AlphaBetaConfig,
+    ) -> AlphaBeta<'a, G, E>
+    where
+        E: Evaluation<G>,
+    {
+        AlphaBeta {
+            game: self,
+            evaluation,
+            config,
+            table: (0..config.table_size).map(|_| None).collect(),
+            killers: Vec::new(),
+            history: HashMap::new(),
+            tracker: config.limits.start(),
+            interruptible: false,
+            cut_off: false,
+            expanded: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The value is at least the stored one
+    Lower,
+    /// The value is at most the stored one
+    Upper,
+}
+
+struct Entry<A> {
+    key: u64,
+    depth: usize,
+    value: f64,
+    bound: Bound,
+    best_move: Option<A>,
+    /// Whether the value was found without evaluating any state
+    resolved: bool,
+}
+
+type Line<A> = (f64, Vec<A>);
+
+/// Negamax alpha-beta with a transposition table, killer moves, the history
+/// heuristic and iterative deepening
+pub struct AlphaBeta<'a, G: Game, E> {
+    game: &'a G,
+    evaluation: &'a E,
+    config: AlphaBetaConfig,
+    /// Indexed by the hash of the states, which is also stored to detect
+    /// collisions
+    table: Vec<Option<Entry<<G::State as State>::Action>>>,
+    killers: Vec<[Option<<G::State as State>::Action>; 2]>,
+    history: HashMap<<G::State as State>::Action, usize>,
+    tracker: LimitTracker,
+    /// Whether the limits may interrupt the current search
+    interruptible: bool,
+    /// Whether the current search evaluated a state
+    cut_off: bool,
+    expanded: usize,
+}
+
+impl<'a, G, E> AlphaBeta<'a, G, E>
+where
+    G: Game,
+    <G::State as State>::Action: Eq + Hash,
+    E: Evaluation<G>,
+{
+    /// Best move for the player to move in `state`, searching deeper and
+    /// deeper until `max_depth`, the limits, or the end of the game in every
+    /// line
+    pub fn search(&mut self, state: &G::State) -> GameDecision<G::State> {
+        self.tracker = self.config.limits.start();
+        self.interruptible = false;
+        self.expanded = 0;
+        let mut decision = None;
+        for depth in 1..=self.config.max_depth.max(1) {
+            self.cut_off = false;
+            let Some((value, line)) =
+                self.negamax(state, depth, 0, f64::NEG_INFINITY, f64::INFINITY)
+            else {
+                break;
+            };
+            decision = Some(self.decision(value, line, depth));
+            if !self.cut_off || self.tracker.exceeded(self.expanded) {
+                break;
+            }
+            self.interruptible = true;
+        }
+        self.interruptible = false;
+        decision.expect("the first iteration is never interrupted")
+    }
+
+    /// Best move for the player to move in `state`, looking `depth` moves
+    /// ahead. The limits are ignored.
+    pub fn search_depth(&mut self, state: &G::State, depth: usize) -> GameDecision<G::State> {
+        self.interruptible = false;
+        self.expanded = 0;
+        let (value, line) = self
+            .negamax(state, depth, 0, f64::NEG_INFINITY, f64::INFINITY)
+            .expect("searches without limits are never interrupted");
+        self.decision(value, line, depth)
+    }
+
+    /// Forget the transposition table and the move ordering statistics
+    pub fn clear(&mut self) {
+        self.table.iter_mut().for_each(|entry| *entry = None);
+        self.killers.clear();
+        self.history.clear();
+    }
+
+    fn decision(
+        &self,
+        value: f64,
+        line: Vec<<G::State as State>::Action>,
+        depth: usize,
+    ) -> GameDecision<G::State> {
+        GameDecision {
+            best_move: line.first().cloned(),
+            value,
+            principal_variation: line,
+            depth,
+            expanded: self.expanded,
+        }
+    }
+
+    /// Value of `state` for the player to move, `None` if the limits were
+    /// reached
+    fn negamax(
+        &mut self,
+        state: &G::State,
+        depth: usize,
+        ply: usize,
+        mut alpha: f64,
+        mut beta: f64,
+    ) -> Option<Line<<G::State as State>::Action>> {
+        let player = self.game.current_player(state);
+        if self.game.is_terminal(state) {
+            return Some((self.game.utility(state, player), Vec::new()));
+        }
+        if depth == 0 {
+            self.cut_off = true;
+            return Some((self.evaluation.evaluate(state, player), Vec::new()));
+        }
+        if self.interruptible && self.tracker.exceeded(self.expanded) {
+            return None;
+        }
+
+        let key = hash(state);
+        let mut table_move = None;
+        if let Some(entry) = self.probe(key) {
+            table_move = entry.best_move.clone();
+            if entry.depth >= depth {
+                let value = entry.value;
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => value >= beta,
+                    Bound::Upper => value <= alpha,
+                };
+                if usable {
+                    self.cut_off |= !entry.resolved;
+                    return Some((value, table_move.into_iter().collect()));
+                }
+                match entry.bound {
+                    Bound::Lower => alpha = alpha.max(value),
+                    Bound::Upper => beta = beta.min(value),
+                    Bound::Exact => {}
+                }
+            }
+        }
+
+        self.expanded += 1;
+        let window_alpha = alpha;
+        let outer_cut_off = std::mem::replace(&mut self.cut_off, false);
+        let mut actions = state.get_available_actions();
+        self.order(&mut actions, table_move.as_ref(), ply);
+        let mut best: Line<<G::State as State>::Action> = (f64::NEG_INFINITY, Vec::new());
+        for action in actions {
+            let child = state.apply(&action);
+            let (value, line) = if self.game.current_player(&child) == player {
+                self.negamax(&child, depth - 1, ply + 1, alpha, beta)?
+            } else {
+                let (value, line) = self.negamax(&child, depth - 1, ply + 1, -beta, -alpha)?;
+                (-value, line)
+            };
+            if best.1.is_empty() || value > best.0 {
+                best = (value, prepend(action.clone(), line));
+            }
+            if value >= beta {
+                self.record_cutoff(action, depth, ply);
+                break;
+            }
+            alpha = alpha.max(value);
+        }
+
+        let bound = if best.0 >= beta {
+            Bound::Lower
+        } else if best.0 <= window_alpha {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+        self.store(Entry {
+            key,
+            depth,
+            value: best.0,
+            bound,
+            best_move: best.1.first().cloned(),
+            resolved: !self.cut_off,
+        });
+        self.cut_off |= outer_cut_off;
+        Some(best)
+    }
+
+    fn probe(&self, key: u64) -> Option<&Entry<<G::State as State>::Action>> {
+        if self.table.is_empty() {
+            return None;
+        }
+        let slot = &self.table[key as usize % self.table.len()];
+        slot.as_ref().filter(|entry| entry.key == key)
+    }
+
+    /// Keep the deepest entry of a state, replace the ones of other states
+    fn store(&mut self, entry: Entry<<G::State as State>::Action>) {
+        if self.table.is_empty() {
+            return;
+        }
+        let index = entry.key as usize % self.table.len();
+        let slot = &mut self.table[index];
+        if slot
+            .as_ref()
+            .is_none_or(|old| old.key != entry.key || old.depth <= entry.depth)
+        {
+            *slot = Some(entry);
+        }
+    }
+
+    /// Move from the table first, then the killer moves, then the moves with
+    /// the best history
+    fn order(
+        &self,
+        actions: &mut [<G::State as State>::Action],
+        table_move: Option<&<G::State as State>::Action>,
+        ply: usize,
+    ) {
+        let killers = self.killers.get(ply);
+        actions.sort_by_cached_key(|action| {
+            let rank = if Some(action) == table_move {
+                2
+            } else if self.config.killer_moves
+                && killers.is_some_and(|killers| killers.contains(&Some(action.clone())))
+            {
+                1
+            } else {
+                0
+            };
+            let history = if self.config.history_heuristic {
+                self.history.get(action).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            std::cmp::Reverse((rank, history))
+        });
+    }
+
+    fn record_cutoff(&mut self, action: <G::State as State>::Action, depth: usize, ply: usize) {
+        if self.config.history_heuristic {
+            // deeper cutoffs weigh more, even in searches without a depth limit
+            let weight = depth.min(MAX_HISTORY_DEPTH);
+            let score = self.history.entry(action.clone()).or_insert(0);
+            *score = score.saturating_add(weight * weight);
+        }
+        if self.config.killer_moves {
+            if self.killers.len() <= ply {
+                self.killers.resize(ply + 1, [None, None]);
+            }
+            let killers = &mut self.killers[ply];
+            if killers[0].as_ref() != Some(&action) {
+                killers[1] = killers[0].replace(action);
+            }
+        }
+    }
+}
+
+fn hash<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,235 @@
+use super::{
+    minimax::{improves, prepend, worst, Minimax, Scored},
+    Evaluation, StochasticGame,
+};
+use crate::{output::GameDecision, State};
+
+/// Pruning of the chance nodes of a *-minimax search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Star {
+    /// Bound each outcome with what the others may still be worth
+    Star1,
+    /// Also probe the first move of every outcome beforehand, to bound it
+    /// before searching it fully. Pays off when the first moves are good.
+    Star2,
+}
+
+pub trait ExpectimaxSearch<G: StochasticGame> {
+    /// Best move for the player to move in `state`, weighting the outcomes of
+    /// chance nodes by their probabilities. Chance nodes count as a ply.
+    fn expectimax<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>;
+}
+
+impl<G: StochasticGame> ExpectimaxSearch<G> for G {
+    fn expectimax<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>,
+    {
+        let mut search = Minimax {
+            game: self,
+            evaluation,
+            player: self.current_player(state),
+            expanded: 0,
+        };
+        let (value, line) = search.expectimax(state, depth);
+        search.decision(value, line, depth)
+    }
+}
+
+pub trait StarMinimaxSearch<G: StochasticGame> {
+    /// Same decision as `expectimax`, pruning with alpha-beta at the players'
+    /// nodes and with the utility bounds at chance nodes. The evaluation must
+    /// stay within the utility bounds.
+    fn star_minimax<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+        pruning: Star,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>;
+}
+
+impl<G: StochasticGame> StarMinimaxSearch<G> for G {
+    fn star_minimax<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+        pruning: Star,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>,
+    {
+        let mut search = Minimax {
+            game: self,
+            evaluation,
+            player: self.current_player(state),
+            expanded: 0,
+        };
+        let (low, high) = self.utility_bounds();
+        let (value, line) = search.star(state, depth, low, high, pruning);
+        search.decision(value, line, depth)
+    }
+}
+
+impl<'a, G: StochasticGame, E: Evaluation<G>> Minimax<'a, G, E> {
+    fn expectimax(&mut self, state: &G::State, depth: usize) -> Scored<G::State> {
+        if let Some(value) = self.leaf(state, depth) {
+            return (value, Vec::new());
+        }
+        self.expanded += 1;
+        if self.game.is_chance(state) {
+            let value = self
+                .game
+                .outcomes(state)
+                .into_iter()
+                .map(|(outcome, probability)| {
+                    probability * self.expectimax(&state.apply(&outcome), depth - 1).0
+                })
+                .sum();
+            return (value, Vec::new());
+        }
+        let maximising = self.game.current_player(state) == self.player;
+        let mut best: Scored<G::State> = (worst(maximising), Vec::new());
+        for action in state.get_available_actions() {
+            let (value, line) = self.expectimax(&state.apply(&action), depth - 1);
+            if best.1.is_empty() || improves(maximising, value, best.0) {
+                best = (value, prepend(action, line));
+            }
+        }
+        best
+    }
+
+    fn star(
+        &mut self,
+        state: &G::State,
+        depth: usize,
+        mut alpha: f64,
+        mut beta: f64,
+        pruning: Star,
+    ) -> Scored<G::State> {
+        if let Some(value) = self.leaf(state, depth) {
+            return (value, Vec::new());
+        }
+        self.expanded += 1;
+        if self.game.is_chance(state) {
+            return (self.chance(state, depth, alpha, beta, pruning), Vec::new());
+        }
+        let maximising = self.game.current_player(state) == self.player;
+        let mut best: Scored<G::State> = (worst(maximising), Vec::new());
+        for action in state.get_available_actions() {
+            let (value, line) = self.star(&state.apply(&action), depth - 1, alpha, beta, pruning);
+            if best.1.is_empty() || improves(maximising, value, best.0) {
+                best = (value, prepend(action, line));
+            }
+            if maximising {
+                alpha = alpha.max(value);
+            } else {
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Expected value of a chance node, or a bound on it outside of the
+    /// window
+    fn chance(
+        &mut self,
+        state: &G::State,
+        depth: usize,
+        alpha: f64,
+        beta: f64,
+        pruning: Star,
+    ) -> f64 {
+        let (low, high) = self.game.utility_bounds();
+        let outcomes: Vec<_> = self
+            .game
+            .outcomes(state)
+            .into_iter()
+            .map(|(outcome, probability)| (state.apply(&outcome), probability))
+            .collect();
+        let mut lower = vec![low; outcomes.len()];
+        let mut upper = vec![high; outcomes.len()];
+        // expectation over the outcomes from `from` on
+        let expected = |bounds: &[f64], from: usize| -> f64 {
+            outcomes[from..]
+                .iter()
+                .zip(&bounds[from..])
+                .map(|((_, probability), bound)| probability * bound)
+                .sum()
+        };
+
+        if pruning == Star::Star2 && depth > 1 {
+            // the first move of a player bounds the value of their node
+            for (i, (child, probability)) in outcomes.iter().enumerate() {
+                if self.game.is_terminal(child) || self.game.is_chance(child) {
+                    continue;
+                }
+                let Some(first) = child.get_available_actions().into_iter().next() else {
+                    continue;
+                };
+                let probe = child.apply(&first);
+                if self.game.current_player(child) == self.player {
+                    let others = expected(&lower, 0) - probability * lower[i];
+                    let target = (beta - others) / probability;
+                    let (value, _) = self.star(&probe, depth - 2, lower[i], target, pruning);
+                    lower[i] = lower[i].max(value);
+                    let bound = expected(&lower, 0);
+                    if bound >= beta {
+                        return bound;
+                    }
+                } else {
+                    let others = expected(&upper, 0) - probability * upper[i];
+                    let target = (alpha - others) / probability;
+                    let (value, _) = self.star(&probe, depth - 2, target, upper[i], pruning);
+                    upper[i] = upper[i].min(value);
+                    let bound = expected(&upper, 0);
+                    if bound <= alpha {
+                        return bound;
+                    }
+                }
+            }
+        }
+
+        // value of the outcomes searched so far, bounds of the other ones
+        let mut searched = 0.0;
+        for (i, (child, probability)) in outcomes.iter().enumerate() {
+            let rest_lower = expected(&lower, i + 1);
+            let rest_upper = expected(&upper, i + 1);
+            let child_alpha = (alpha - searched - rest_upper) / probability;
+            let child_beta = (beta - searched - rest_lower) / probability;
+            let (value, _) = self.star(
+                child,
+                depth - 1,
+                child_alpha.max(lower[i]),
+                child_beta.min(upper[i]),
+                pruning,
+            );
+            if value <= child_alpha {
+                return searched + probability * value + rest_upper;
+            }
+            if value >= child_beta {
+                return searched + probability * value + rest_lower;
+            }
+            searched += probability * value;
+        }
+        searched
+    }
+}
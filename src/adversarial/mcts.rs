@@ -0,0 +1,528 @@
+use super::Game;
+use crate::{output::GameDecision, rng::Rng, State};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Visits of a node and its value for the player who moved into it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NodeStats {
+    pub visits: usize,
+    pub value: f64,
+}
+
+/// How to descend the tree: the child with the highest score is visited
+pub trait SelectionPolicy {
+    fn score(&self, parent_visits: usize, child: &NodeStats) -> f64;
+}
+
+impl<F> SelectionPolicy for F
+where
+    F: Fn(usize, &NodeStats) -> f64,
+{
+    fn score(&self, parent_visits: usize, child: &NodeStats) -> f64 {
+        self(parent_visits, child)
+    }
+}
+
+/// Upper confidence bound of UCT, for values between 0 and 1
+#[derive(Debug, Clone, Copy)]
+pub struct Ucb1 {
+    pub exploration: f64,
+}
+
+impl Default for Ucb1 {
+    fn default() -> Self {
+        Self {
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl SelectionPolicy for Ucb1 {
+    fn score(&self, parent_visits: usize, child: &NodeStats) -> f64 {
+        let parent_visits = parent_visits as f64;
+        let visits = child.visits as f64;
+        child.value + self.exploration * (parent_visits.ln() / visits).sqrt()
+    }
+}
+
+/// Which untried move of a node is added to the tree next
+pub trait ExpansionPolicy<S: State> {
+    /// Index of the move to try among `untried`, which is never empty
+    fn choose(&self, state: &S, untried: &[S::Action], rng: &mut Rng) -> usize;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomExpansion;
+
+impl<S: State> ExpansionPolicy<S> for RandomExpansion {
+    fn choose(&self, _: &S, untried: &[S::Action], rng: &mut Rng) -> usize {
+        rng.below(untried.len())
+    }
+}
+
+/// How a game is played out from a new node
+pub trait RolloutPolicy<G: Game> {
+    /// A terminal state reached from `state`
+    fn rollout(&self, game: &G, state: &G::State, rng: &mut Rng) -> G::State;
+}
+
+/// Every player moves at random
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomRollout;
+
+impl<G: Game> RolloutPolicy<G> for RandomRollout {
+    fn rollout(&self, game: &G, state: &G::State, rng: &mut Rng) -> G::State {
+        let mut state = state.clone();
+        while !game.is_terminal(&state) {
+            let actions = state.get_available_actions();
+            let Some(action) = rng.choose(&actions) else {
+                break;
+            };
+            state = state.apply(action);
+        }
+        state
+    }
+}
+
+/// How the outcome of a rollout updates the nodes it went through
+pub trait BackpropagationPolicy {
+    /// `stats.visits` already counts this rollout
+    fn update(&self, stats: &mut NodeStats, reward: f64);
+}
+
+/// Value is the average reward
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeanBackup;
+
+impl BackpropagationPolicy for MeanBackup {
+    fn update(&self, stats: &mut NodeStats, reward: f64) {
+        stats.value += (reward - stats.value) / stats.visits as f64;
+    }
+}
+
+/// Value is the best reward, for single-player games
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxBackup;
+
+impl BackpropagationPolicy for MaxBackup {
+    fn update(&self, stats: &mut NodeStats, reward: f64) {
+        stats.value = if stats.visits == 1 {
+            reward
+        } else {
+            stats.value.max(reward)
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MctsConfig {
+    /// Iterations of every tree per search
+    pub iterations: usize,
+    pub time_budget: Option<Duration>,
+    /// Independent trees searched in parallel, whose root statistics are
+    /// merged to decide
+    pub trees: usize,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 10_000,
+            time_budget: None,
+            trees: 1,
+            seed: 0,
+        }
+    }
+}
+
+pub trait MonteCarloTreeSearch<G: Game> {
+    /// UCT with random expansion and rollouts and mean backups; each policy
+    /// can be replaced. Rewards are the utilities of the terminal states, so
+    /// should be between 0 and 1 for `Ucb1`.
+    fn mcts(
+        &self,
+        config: MctsConfig,
+    ) -> Mcts<'_, G, Ucb1, RandomExpansion, RandomRollout, MeanBackup>;
+}
+
+impl<G: Game> MonteCarloTreeSearch<G> for G {
+    fn mcts(
+        &self,
+        config: MctsConfig,
+    ) -> Mcts<'_, G, Ucb1, RandomExpansion, RandomRollout, MeanBackup> {
+        let trees = (0..config.trees.max(1))
+            .map(|i| Tree {
+                nodes: Vec::new(),
+                rng: Rng::seed_from_u64(config.seed.wrapping_add(i as u64)),
+            })
+            .collect();
+        Mcts {
+            game: self,
+            config,
+            selection: Ucb1::default(),
+            expansion: RandomExpansion,
+            rollout: RandomRollout,
+            backpropagation: MeanBackup,
+            trees,
+        }
+    }
+}
+
+struct TreeNode<S: State> {
+    state: S,
+    /// Move leading to this node
+    action: Option<S::Action>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<S::Action>,
+    terminal: bool,
+    depth: usize,
+    stats: NodeStats,
+}
+
+struct Tree<S: State> {
+    /// The root comes first
+    nodes: Vec<TreeNode<S>>,
+    rng: Rng,
+}
+
+/// Monte Carlo tree search keeping its trees between moves
+pub struct Mcts<'a, G: Game, Sel, Exp, Rol, Bac> {
+    game: &'a G,
+    config: MctsConfig,
+    selection: Sel,
+    expansion: Exp,
+    rollout: Rol,
+    backpropagation: Bac,
+    trees: Vec<Tree<G::State>>,
+}
+
+impl<'a, G: Game, Sel, Exp, Rol, Bac> Mcts<'a, G, Sel, Exp, Rol, Bac> {
+    pub fn with_selection<T>(self, selection: T) -> Mcts<'a, G, T, Exp, Rol, Bac> {
+        Mcts {
+            game: self.game,
+            config: self.config,
+            selection,
+            expansion: self.expansion,
+            rollout: self.rollout,
+            backpropagation: self.backpropagation,
+            trees: self.trees,
+        }
+    }
+
+    pub fn with_expansion<T>(self, expansion: T) -> Mcts<'a, G, Sel, T, Rol, Bac> {
+        Mcts {
+            game: self.game,
+            config: self.config,
+            selection: self.selection,
+            expansion,
+            rollout: self.rollout,
+            backpropagation: self.backpropagation,
+            trees: self.trees,
+        }
+    }
+
+    pub fn with_rollout<T>(self, rollout: T) -> Mcts<'a, G, Sel, Exp, T, Bac> {
+        Mcts {
+            game: self.game,
+            config: self.config,
+            selection: self.selection,
+            expansion: self.expansion,
+            rollout,
+            backpropagation: self.backpropagation,
+            trees: self.trees,
+        }
+    }
+
+    pub fn with_backpropagation<T>(self, backpropagation: T) -> Mcts<'a, G, Sel, Exp, Rol, T> {
+        Mcts {
+            game: self.game,
+            config: self.config,
+            selection: self.selection,
+            expansion: self.expansion,
+            rollout: self.rollout,
+            backpropagation,
+            trees: self.trees,
+        }
+    }
+}
+
+impl<'a, G, Sel, Exp, Rol, Bac> Mcts<'a, G, Sel, Exp, Rol, Bac>
+where
+    G: Game + Sync,
+    G::State: Send + Sync,
+    <G::State as State>::Action: Eq + Hash + Send + Sync,
+    Sel: SelectionPolicy + Sync,
+    Exp: ExpansionPolicy<G::State> + Sync,
+    Rol: RolloutPolicy<G> + Sync,
+    Bac: BackpropagationPolicy + Sync,
+{
+    /// Grow the trees from `state`, reusing them if they are rooted there,
+    /// and pick the move of the root that was visited the most
+    pub fn search(&mut self, state: &G::State) -> GameDecision<G::State> {
+        let started = Instant::now();
+        let game = self.game;
+        for tree in &mut self.trees {
+            if tree.nodes.first().is_none_or(|root| root.state != *state) {
+                tree.reset(game, state.clone());
+            }
+        }
+        let sizes: Vec<usize> = self.trees.iter().map(|tree| tree.nodes.len()).collect();
+        let config = self.config;
+        let policies = (
+            &self.selection,
+            &self.expansion,
+            &self.rollout,
+            &self.backpropagation,
+        );
+        self.trees.par_iter_mut().for_each(|tree| {
+            for _ in 0..config.iterations {
+                if config
+                    .time_budget
+                    .is_some_and(|budget| started.elapsed() >= budget)
+                {
+                    break;
+                }
+                tree.iterate(game, policies);
+            }
+        });
+        let expanded = self
+            .trees
+            .iter()
+            .zip(sizes)
+            .map(|(tree, size)| tree.nodes.len() - size)
+            .sum();
+        self.decide(expanded)
+    }
+
+    /// Keep only the subtrees following `action`, to search the next state
+    /// without starting from scratch
+    pub fn advance(&mut self, action: &<G::State as State>::Action) {
+        let game = self.game;
+        for tree in &mut self.trees {
+            tree.advance(game, action);
+        }
+    }
+
+    /// Visits of the roots of the trees, including the ones inherited from
+    /// previous searches
+    pub fn root_visits(&self) -> usize {
+        self.trees
+            .iter()
+            .filter_map(|tree| tree.nodes.first())
+            .map(|root| root.stats.visits)
+            .sum()
+    }
+
+    /// Total visits and value of every move of the root, over all trees
+    fn root_moves(&self) -> Vec<(<G::State as State>::Action, NodeStats)> {
+        let mut moves: Vec<(<G::State as State>::Action, NodeStats)> = Vec::new();
+        let mut positions = HashMap::new();
+        for tree in &self.trees {
+            for &child in &tree.nodes[0].children {
+                let node = &tree.nodes[child];
+                let action = node.action.clone().expect("children have a move");
+                let position = *positions.entry(action.clone()).or_insert_with(|| {
+                    moves.push((action, NodeStats::default()));
+                    moves.len() - 1
+                });
+                let stats = &mut moves[position].1;
+                let visits = stats.visits + node.stats.visits;
+                if visits > 0 {
+                    stats.value = (stats.value * stats.visits as f64
+                        + node.stats.value * node.stats.visits as f64)
+                        / visits as f64;
+                }
+                stats.visits = visits;
+            }
+        }
+        moves
+    }
+
+    fn decide(&self, expanded: usize) -> GameDecision<G::State> {
+        let best = self
+            .root_moves()
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.visits.cmp(&b.visits).then(a.value.total_cmp(&b.value)));
+        let depth = self
+            .trees
+            .iter()
+            .flat_map(|tree| tree.nodes.iter().map(|node| node.depth))
+            .max()
+            .unwrap_or(0);
+        let Some((action, stats)) = best else {
+            return GameDecision {
+                best_move: None,
+                value: 0.0,
+                principal_variation: Vec::new(),
+                depth,
+                expanded,
+            };
+        };
+        // the most visited line of the first tree that tried the move
+        let mut principal_variation = vec![action.clone()];
+        let tree = self
+            .trees
+            .iter()
+            .find(|tree| tree.child(0, &action).is_some())
+            .expect("some tree tried the move");
+        let mut node = tree.child(0, &action).expect("the tree tried the move");
+        while let Some(&child) = tree.nodes[node]
+            .children
+            .iter()
+            .max_by_key(|&&child| tree.nodes[child].stats.visits)
+        {
+            principal_variation.push(tree.nodes[child].action.clone().expect("a move"));
+            node = child;
+        }
+        GameDecision {
+            best_move: Some(action),
+            value: stats.value,
+            principal_variation,
+            depth,
+            expanded,
+        }
+    }
+}
+
+impl<S: State> Tree<S> {
+    fn reset<G: Game<State = S>>(&mut self, game: &G, state: S) {
+        self.nodes.clear();
+        self.add(game, state, None, None);
+    }
+
+    fn add<G: Game<State = S>>(
+        &mut self,
+        game: &G,
+        state: S,
+        action: Option<S::Action>,
+        parent: Option<usize>,
+    ) -> usize {
+        let terminal = game.is_terminal(&state);
+        let untried = if terminal {
+            Vec::new()
+        } else {
+            state.get_available_actions()
+        };
+        let depth = parent.map_or(0, |parent| self.nodes[parent].depth + 1);
+        self.nodes.push(TreeNode {
+            state,
+            action,
+            parent,
+            children: Vec::new(),
+            untried,
+            terminal,
+            depth,
+            stats: NodeStats::default(),
+        });
+        let index = self.nodes.len() - 1;
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        index
+    }
+
+    fn child(&self, node: usize, action: &S::Action) -> Option<usize>
+    where
+        S::Action: PartialEq,
+    {
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].action.as_ref() == Some(action))
+    }
+
+    /// Selection, expansion, rollout and backpropagation
+    fn iterate<G, Sel, Exp, Rol, Bac>(
+        &mut self,
+        game: &G,
+        (selection, expansion, rollout, backpropagation): (&Sel, &Exp, &Rol, &Bac),
+    ) where
+        G: Game<State = S>,
+        Sel: SelectionPolicy,
+        Exp: ExpansionPolicy<S>,
+        Rol: RolloutPolicy<G>,
+        Bac: BackpropagationPolicy,
+    {
+        let mut node = 0;
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            let parent_visits = self.nodes[node].stats.visits;
+            node = *self.nodes[node]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let a = selection.score(parent_visits, &self.nodes[a].stats);
+                    let b = selection.score(parent_visits, &self.nodes[b].stats);
+                    a.total_cmp(&b)
+                })
+                .expect("the node has children");
+        }
+        if !self.nodes[node].untried.is_empty() {
+            let current = &mut self.nodes[node];
+            let index = expansion.choose(&current.state, &current.untried, &mut self.rng);
+            let action = current.untried.swap_remove(index);
+            let state = current.state.apply(&action);
+            node = self.add(game, state, Some(action), Some(node));
+        }
+        let end = if self.nodes[node].terminal {
+            self.nodes[node].state.clone()
+        } else {
+            rollout.rollout(game, &self.nodes[node].state, &mut self.rng)
+        };
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let parent = self.nodes[index].parent;
+            let stats = &mut self.nodes[index].stats;
+            stats.visits += 1;
+            if let Some(parent) = parent {
+                // the value is the one of the player who chose the move
+                let mover = game.current_player(&self.nodes[parent].state);
+                let reward = game.utility(&end, mover);
+                backpropagation.update(&mut self.nodes[index].stats, reward);
+            }
+            current = parent;
+        }
+    }
+
+    /// Make the child reached by `action` the root, dropping the rest
+    fn advance<G: Game<State = S>>(&mut self, game: &G, action: &S::Action)
+    where
+        S::Action: PartialEq,
+    {
+        let Some(root) = self.nodes.first() else {
+            return;
+        };
+        let Some(child) = self.child(0, action) else {
+            let state = root.state.apply(action);
+            self.reset(game, state);
+            return;
+        };
+        let offset = self.nodes[child].depth;
+        let mut nodes: Vec<_> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        // parents are copied before their children
+        let mut indices = HashMap::new();
+        let mut stack = vec![child];
+        while let Some(old) = stack.pop() {
+            let mut node = nodes[old].take().expect("nodes are copied once");
+            let index = self.nodes.len();
+            indices.insert(old, index);
+            stack.extend(node.children.iter().rev());
+            node.parent = node.parent.and_then(|parent| indices.get(&parent).copied());
+            node.children.clear();
+            node.depth -= offset;
+            if let Some(parent) = node.parent {
+                self.nodes[parent].children.push(index);
+            }
+            self.nodes.push(node);
+        }
+        self.nodes[0].action = None;
+    }
+}
@@ -0,0 +1,167 @@
+use super::{Evaluation, Game};
+use crate::{output::GameDecision, State};
+
+pub trait MinimaxSearch<G: Game> {
+    /// Best move for the player to move in `state`, looking `depth` moves
+    /// ahead and estimating the states where the search is cut off
+    fn minimax<E>(&self, state: &G::State, depth: usize, evaluation: &E) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>;
+}
+
+impl<G: Game> MinimaxSearch<G> for G {
+    fn minimax<E>(&self, state: &G::State, depth: usize, evaluation: &E) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>,
+    {
+        let mut search = Minimax {
+            game: self,
+            evaluation,
+            player: self.current_player(state),
+            expanded: 0,
+        };
+        let (value, line) = search.value(state, depth);
+        search.decision(value, line, depth)
+    }
+}
+
+pub trait AlphaBetaSearch<G: Game> {
+    /// Same decision as `minimax`, skipping the subtrees that can't change
+    /// it
+    fn alpha_beta<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>;
+}
+
+impl<G: Game> AlphaBetaSearch<G> for G {
+    fn alpha_beta<E>(
+        &self,
+        state: &G::State,
+        depth: usize,
+        evaluation: &E,
+    ) -> GameDecision<G::State>
+    where
+        E: Evaluation<G>,
+    {
+        let mut search = Minimax {
+            game: self,
+            evaluation,
+            player: self.current_player(state),
+            expanded: 0,
+        };
+        let (value, line) = search.alpha_beta(state, depth, f64::NEG_INFINITY, f64::INFINITY);
+        search.decision(value, line, depth)
+    }
+}
+
+/// Backed-up value and the principal variation leading to it
+pub(super) type Scored<S> = (f64, Vec<<S as State>::Action>);
+
+/// Search from the point of view of `player`, who maximises while the
+/// opponents minimise
+pub(super) struct Minimax<'a, G: Game, E> {
+    pub game: &'a G,
+    pub evaluation: &'a E,
+    pub player: G::Player,
+    pub expanded: usize,
+}
+
+impl<'a, G: Game, E: Evaluation<G>> Minimax<'a, G, E> {
+    pub fn decision(
+        &self,
+        value: f64,
+        line: Vec<<G::State as State>::Action>,
+        depth: usize,
+    ) -> GameDecision<G::State> {
+        GameDecision {
+            best_move: line.first().cloned(),
+            value,
+            principal_variation: line,
+            depth,
+            expanded: self.expanded,
+        }
+    }
+
+    /// Value of a state where the search stops, if it does
+    pub fn leaf(&self, state: &G::State, depth: usize) -> Option<f64> {
+        if self.game.is_terminal(state) {
+            Some(self.game.utility(state, self.player))
+        } else if depth == 0 {
+            Some(self.evaluation.evaluate(state, self.player))
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self, state: &G::State, depth: usize) -> Scored<G::State> {
+        if let Some(value) = self.leaf(state, depth) {
+            return (value, Vec::new());
+        }
+        self.expanded += 1;
+        let maximising = self.game.current_player(state) == self.player;
+        let mut best: Scored<G::State> = (worst(maximising), Vec::new());
+        for action in state.get_available_actions() {
+            let (value, line) = self.value(&state.apply(&action), depth - 1);
+            if best.1.is_empty() || improves(maximising, value, best.0) {
+                best = (value, prepend(action, line));
+            }
+        }
+        best
+    }
+
+    fn alpha_beta(
+        &mut self,
+        state: &G::State,
+        depth: usize,
+        mut alpha: f64,
+        mut beta: f64,
+    ) -> Scored<G::State> {
+        if let Some(value) = self.leaf(state, depth) {
+            return (value, Vec::new());
+        }
+        self.expanded += 1;
+        let maximising = self.game.current_player(state) == self.player;
+        let mut best: Scored<G::State> = (worst(maximising), Vec::new());
+        for action in state.get_available_actions() {
+            let (value, line) = self.alpha_beta(&state.apply(&action), depth - 1, alpha, beta);
+            if best.1.is_empty() || improves(maximising, value, best.0) {
+                best = (value, prepend(action, line));
+            }
+            if maximising {
+                alpha = alpha.max(value);
+            } else {
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+}
+
+pub(super) fn worst(maximising: bool) -> f64 {
+    if maximising {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    }
+}
+
+pub(super) fn improves(maximising: bool, value: f64, best: f64) -> bool {
+    if maximising {
+        value > best
+    } else {
+        value < best
+    }
+}
+
+pub(super) fn prepend<A>(action: A, mut line: Vec<A>) -> Vec<A> {
+    line.insert(0, action);
+    line
+}
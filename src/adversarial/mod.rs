@@ -0,0 +1,63 @@
+//! Adversarial search for turn-based games:
+//! - minimax
+//! - alpha-beta pruning, optionally with a transposition table, killer moves,
+//!   the history heuristic and iterative deepening
+//! - expectimax and *-minimax (Star1, Star2) for games with chance nodes
+//! - Monte Carlo tree search
+
+mod engine;
+mod expectimax;
+mod mcts;
+mod minimax;
+
+pub use engine::{AlphaBeta, AlphaBetaConfig, AlphaBetaEngine};
+pub use expectimax::{ExpectimaxSearch, Star, StarMinimaxSearch};
+pub use mcts::{
+    BackpropagationPolicy, ExpansionPolicy, MaxBackup, Mcts, MctsConfig, MeanBackup,
+    MonteCarloTreeSearch, NodeStats, RandomExpansion, RandomRollout, RolloutPolicy,
+    SelectionPolicy, Ucb1,
+};
+pub use minimax::{AlphaBetaSearch, MinimaxSearch};
+
+use crate::State;
+
+/// Turn-based game where the moves of a player are the available actions of
+/// the state
+pub trait Game {
+    type State: State;
+    type Player: Copy + Eq;
+    fn initial_state(&self) -> Self::State;
+    /// Player who moves in `state`
+    fn current_player(&self, state: &Self::State) -> Self::Player;
+    fn is_terminal(&self, state: &Self::State) -> bool;
+    /// Value of a terminal state for `player`. The game must be zero-sum: what
+    /// a player wins the other loses.
+    fn utility(&self, state: &Self::State, player: Self::Player) -> f64;
+}
+
+/// Game where chance decides what happens in some states, e.g. by rolling
+/// dice or drawing cards
+pub trait StochasticGame: Game {
+    /// Whether `state` is a chance node rather than a player's turn
+    fn is_chance(&self, state: &Self::State) -> bool;
+    /// Possible outcomes of a chance node, applied like actions, with their
+    /// probabilities summing to one
+    fn outcomes(&self, state: &Self::State) -> Vec<(<Self::State as State>::Action, f64)>;
+    /// Lowest and highest utility a state may have, for any player
+    fn utility_bounds(&self) -> (f64, f64);
+}
+
+/// Estimate of the utility of a non-terminal state for a player, used where
+/// the search is cut off
+pub trait Evaluation<G: Game> {
+    fn evaluate(&self, state: &G::State, player: G::Player) -> f64;
+}
+
+impl<G: Game, F> Evaluation<G> for F
+where
+    F: Fn(&G::State, G::Player) -> f64,
+{
+    fn evaluate(&self, state: &G::State, player: G::Player) -> f64 {
+        self(state, player)
+    }
+}
@@ -26,7 +26,7 @@ where
         let mut visited = StateCacheSet::new();
         while let Some(node) = frontier.pop() {
             let state = node.state();
-            if self.is_goal(&state) {
+            if self.is_goal(state) {
                 return Some(SearchResult::new(node, generated, visited.len()));
             }
             if visited.contains(state) {
@@ -58,7 +58,7 @@ where
         let mut generated: usize = 0;
         while let Some(node) = queue.pop() {
             let state = node.state();
-            if self.is_goal(&state) {
+            if self.is_goal(state) {
                 return Some(SearchResult::new(node, generated, visited.len()));
             }
             if visited.contains(state) {
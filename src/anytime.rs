@@ -0,0 +1,357 @@
+//! Anytime search algorithms:
+//! - ARA* (anytime repairing A*)
+
+use crate::{
+    limits::{LimitTracker, SearchLimits},
+    output::{AnytimeSolution, SearchResult},
+    CostAction, Heuristic, Space, State,
+};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Parameters of an anytime search
+#[derive(Debug, Clone, Copy)]
+pub struct AnytimeConfig {
+    /// Heuristic weight of the first, greedier, iteration
+    pub initial_weight: f64,
+    /// Amount the weight is lowered by after every iteration
+    pub weight_step: f64,
+    /// Bounds on the total effort, across all iterations
+    pub limits: SearchLimits,
+}
+
+impl Default for AnytimeConfig {
+    fn default() -> Self {
+        Self {
+            initial_weight: 3.0,
+            weight_step: 0.5,
+            limits: SearchLimits::default(),
+        }
+    }
+}
+
+pub trait AnytimeSearch<S: Space> {
+    /// Iterate over solutions of decreasing cost. Every iteration reuses the
+    /// work of the previous one and the stream ends once an optimal solution
+    /// has been found, the space has no solution or the limits are reached.
+    fn anytime_search<'a, H>(
+        &'a self,
+        heuristic: &'a H,
+        config: AnytimeConfig,
+    ) -> AnytimeAStar<'a, S, H>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> AnytimeSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn anytime_search<'a, H>(
+        &'a self,
+        heuristic: &'a H,
+        config: AnytimeConfig,
+    ) -> AnytimeAStar<'a, S, H>
+    where
+        H: Heuristic<S::State>,
+    {
+        AnytimeAStar::new(self, heuristic, config)
+    }
+}
+
+struct Record<S: State> {
+    state: S,
+    g: usize,
+    h: usize,
+    parent: Option<(usize, S::Action)>,
+    open: bool,
+    closed: bool,
+    inconsistent: bool,
+}
+
+struct OpenEntry {
+    key: f64,
+    h: usize,
+    index: usize,
+}
+
+impl PartialEq for OpenEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenEntry {}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenEntry {
+    // reversed so that the max-heap pops the lowest key first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .total_cmp(&self.key)
+            .then_with(|| other.h.cmp(&self.h))
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Iterator over the improving solutions found by ARA*
+pub struct AnytimeAStar<'a, S: Space, H> {
+    space: &'a S,
+    heuristic: &'a H,
+    config: AnytimeConfig,
+    tracker: LimitTracker,
+    records: Vec<Record<S::State>>,
+    indices: HashMap<S::State, usize>,
+    open: BinaryHeap<OpenEntry>,
+    incumbent: Option<usize>,
+    reported_cost: Option<usize>,
+    weight: f64,
+    started: bool,
+    finished: bool,
+    generated: usize,
+    expanded: usize,
+}
+
+enum Improvement {
+    Done,
+    Interrupted,
+}
+
+impl<'a, S, H> AnytimeAStar<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    fn new(space: &'a S, heuristic: &'a H, config: AnytimeConfig) -> Self {
+        Self {
+            space,
+            heuristic,
+            config,
+            tracker: config.limits.start(),
+            records: Vec::new(),
+            indices: HashMap::new(),
+            open: BinaryHeap::new(),
+            incumbent: None,
+            reported_cost: None,
+            weight: config.initial_weight.max(1.0),
+            started: false,
+            finished: false,
+            generated: 0,
+            expanded: 0,
+        }
+    }
+
+    /// Heuristic weight used by the latest iteration
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn index_of(&mut self, state: S::State) -> usize {
+        if let Some(&index) = self.indices.get(&state) {
+            return index;
+        }
+        let index = self.records.len();
+        let h = self.heuristic.estimate(&state);
+        self.indices.insert(state.clone(), index);
+        self.records.push(Record {
+            state,
+            g: usize::MAX,
+            h,
+            parent: None,
+            open: false,
+            closed: false,
+            inconsistent: false,
+        });
+        index
+    }
+
+    fn key(&self, index: usize) -> f64 {
+        let record = &self.records[index];
+        record.g as f64 + self.weight * record.h as f64
+    }
+
+    fn push_open(&mut self, index: usize) {
+        self.records[index].open = true;
+        self.open.push(OpenEntry {
+            key: self.key(index),
+            h: self.records[index].h,
+            index,
+        });
+    }
+
+    fn incumbent_cost(&self) -> Option<usize> {
+        self.incumbent.map(|index| self.records[index].g)
+    }
+
+    /// Drop stale heap entries and return the lowest key still open
+    fn min_open_key(&mut self) -> Option<f64> {
+        while let Some(entry) = self.open.peek() {
+            let record = &self.records[entry.index];
+            if record.open && entry.key == self.key(entry.index) {
+                return Some(entry.key);
+            }
+            self.open.pop();
+        }
+        None
+    }
+
+    fn improve_path(&mut self) -> Improvement {
+        while let Some(key) = self.min_open_key() {
+            if self.incumbent_cost().is_some_and(|cost| cost as f64 <= key) {
+                break;
+            }
+            if self.tracker.exceeded(self.expanded) {
+                return Improvement::Interrupted;
+            }
+            let index = self.open.pop().expect("peeked above").index;
+            self.records[index].open = false;
+            self.records[index].closed = true;
+            if self.space.is_goal(&self.records[index].state) {
+                continue;
+            }
+            self.expanded += 1;
+            let state = self.records[index].state.clone();
+            let g = self.records[index].g;
+            for action in state.get_available_actions() {
+                let child = self.index_of(state.apply(&action));
+                self.generated += 1;
+                let child_g = g + action.cost();
+                if child_g >= self.records[child].g {
+                    continue;
+                }
+                self.records[child].g = child_g;
+                self.records[child].parent = Some((index, action));
+                if self.space.is_goal(&self.records[child].state)
+                    && self.incumbent_cost().is_none_or(|cost| child_g < cost)
+                {
+                    self.incumbent = Some(child);
+                }
+                if !self.records[child].closed {
+                    self.push_open(child);
+                } else {
+                    self.records[child].inconsistent = true;
+                }
+            }
+        }
+        Improvement::Done
+    }
+
+    /// Move the inconsistent states back to the open list and rebuild its keys
+    /// with the current weight
+    fn reopen(&mut self) {
+        self.open.clear();
+        for index in 0..self.records.len() {
+            let record = &mut self.records[index];
+            record.closed = false;
+            if record.inconsistent {
+                record.inconsistent = false;
+                record.open = true;
+            }
+            if record.open {
+                self.push_open(index);
+            }
+        }
+    }
+
+    /// Lowest f-value, with an unweighted heuristic, among the states that
+    /// could still lead to a cheaper solution
+    fn lower_bound(&self) -> Option<usize> {
+        self.records
+            .iter()
+            .filter(|record| record.open || record.inconsistent)
+            .map(|record| record.g.saturating_add(record.h))
+            .min()
+    }
+
+    fn solution(&self, index: usize, weight: f64) -> AnytimeSolution<S::State> {
+        let cost = self.records[index].g;
+        let mut path = Vec::new();
+        let mut current = index;
+        while let Some((parent, action)) = &self.records[current].parent {
+            path.push(action.clone());
+            current = *parent;
+        }
+        path.reverse();
+        let bound = match self.lower_bound() {
+            Some(lower) if lower < cost && lower > 0 => weight.min(cost as f64 / lower as f64),
+            Some(lower) if lower < cost => weight,
+            _ => 1.0,
+        };
+        AnytimeSolution {
+            result: SearchResult::from_path(
+                self.records[index].state.clone(),
+                path,
+                self.generated,
+                self.expanded,
+            ),
+            cost,
+            bound,
+        }
+    }
+}
+
+impl<'a, S, H> Iterator for AnytimeAStar<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    type Item = AnytimeSolution<S::State>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.finished {
+                return None;
+            }
+            if !self.started {
+                self.started = true;
+                let initial = self.index_of(self.space.initial_state());
+                self.records[initial].g = 0;
+                if self.space.is_goal(&self.records[initial].state) {
+                    self.incumbent = Some(initial);
+                }
+                self.push_open(initial);
+            } else {
+                self.weight = (self.weight - self.config.weight_step.max(f64::EPSILON)).max(1.0);
+                self.reopen();
+            }
+            let interrupted = matches!(self.improve_path(), Improvement::Interrupted);
+            let optimal = self.weight <= 1.0 && !interrupted;
+            if interrupted || optimal {
+                self.finished = true;
+            }
+            let Some(incumbent) = self.incumbent else {
+                // the weight doesn't prune, so lowering it won't find a solution
+                self.finished = true;
+                return None;
+            };
+            let cost = self.records[incumbent].g;
+            let improved = self.reported_cost.is_none_or(|reported| cost < reported);
+            if improved || optimal {
+                self.reported_cost = Some(cost);
+                // the weight only bounds the solution of a completed iteration
+                let weight = match (optimal, interrupted) {
+                    (true, _) => 1.0,
+                    (false, true) => f64::INFINITY,
+                    (false, false) => self.weight,
+                };
+                let solution = self.solution(incumbent, weight);
+                if solution.bound <= 1.0 {
+                    self.finished = true;
+                }
+                return Some(solution);
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use super::{propagation, Assignment, Csp, Domains, Var};
+use crate::output::{CspResult, SolverStats};
+
+/// Which variable to assign next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableOrdering {
+    /// In the order they were added
+    InOrder,
+    /// Fewest values left first (minimum remaining values)
+    Mrv,
+    /// Fewest values left first, then most constraints on other unassigned
+    /// variables
+    MrvDegree,
+}
+
+/// Which value of the variable to try first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueOrdering {
+    /// In the order of the domain
+    InOrder,
+    /// The one ruling out the fewest values of the other variables first
+    LeastConstraining,
+}
+
+/// What is deduced after every assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inference {
+    None,
+    /// Remove the values of the unassigned variables that conflict with the
+    /// assignment
+    ForwardChecking,
+    /// Make every constraint arc consistent again (MAC), starting from the
+    /// constraints on the assigned variable. The problem is made arc
+    /// consistent before the search.
+    Mac,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BacktrackingConfig {
+    pub variable_ordering: VariableOrdering,
+    pub value_ordering: ValueOrdering,
+    pub inference: Inference,
+    /// When every value of a variable fails, go back to the last variable
+    /// involved in the failures rather than to the previous one
+    /// (conflict-directed backjumping). Values removed by maintaining arc
+    /// consistency are blamed on every assigned variable.
+    pub backjumping: bool,
+    /// Remember the assignments that caused the failures, so that no other
+    /// branch tries them again
+    pub learn_nogoods: bool,
+}
+
+impl Default for BacktrackingConfig {
+    fn default() -> Self {
+        Self {
+            variable_ordering: VariableOrdering::MrvDegree,
+            value_ordering: ValueOrdering::LeastConstraining,
+            inference: Inference::ForwardChecking,
+            backjumping: false,
+            learn_nogoods: false,
+        }
+    }
+}
+
+pub trait BacktrackingSearch<V> {
+    /// Assign the variables one at a time, undoing the last assignment when
+    /// a constraint can no longer be satisfied
+    fn backtracking_search(&self, config: BacktrackingConfig) -> CspResult<V>;
+}
+
+impl<V: Clone + PartialEq> BacktrackingSearch<V> for Csp<V> {
+    fn backtracking_search(&self, config: BacktrackingConfig) -> CspResult<V> {
+        let mut solver = Solver {
+            csp: self,
+            config,
+            assignment: Assignment::new(self.variables()),
+            domains: Domains::new(self.domain_values()),
+            explanations: Vec::new(),
+            path: Vec::new(),
+            nogoods: Vec::new(),
+            watched: vec![Vec::new(); self.variables()],
+            stats: SolverStats::default(),
+        };
+        let consistent = config.inference != Inference::Mac || {
+            let queue = (0..self.constraints.len()).collect();
+            propagation::ac3(
+                self,
+                &mut solver.domains,
+                queue,
+                &mut solver.stats.propagations,
+            )
+        };
+        // values removed before the search are not due to any assignment
+        solver.explain(&[]);
+        let solution = if consistent && solver.solve().is_ok() {
+            solver.assignment.into_values()
+        } else {
+            None
+        };
+        CspResult {
+            solution,
+            stats: solver.stats,
+        }
+    }
+}
+
+struct Solver<'a, V> {
+    csp: &'a Csp<V>,
+    config: BacktrackingConfig,
+    assignment: Assignment<V>,
+    /// Values still allowed for every variable
+    domains: Domains<V>,
+    /// Assigned variables blamed for every value removed from `domains`
+    explanations: Vec<Vec<Var>>,
+    /// Assigned variables, in order
+    path: Vec<Var>,
+    /// Assignments that can't be part of a solution
+    nogoods: Vec<Vec<(Var, V)>>,
+    /// Nogoods of every variable
+    watched: Vec<Vec<usize>>,
+    stats: SolverStats,
+}
+
+/// Assigned variables responsible for a failure
+type Conflict = Vec<Var>;
+
+/// Add the variables of `from` other than `except` to `conflict`
+fn blame(conflict: &mut Conflict, from: impl IntoIterator<Item = Var>, except: Var) {
+    for var in from {
+        if var != except && !conflict.contains(&var) {
+            conflict.push(var);
+        }
+    }
+}
+
+impl<'a, V: Clone + PartialEq> Solver<'a, V> {
+    /// Solve the problem from the current assignment, or find out which
+    /// assigned variables prevent it
+    fn solve(&mut self) -> Result<(), Conflict> {
+        let Some(var) = self.select_variable() else {
+            return Ok(());
+        };
+        // values removed by the earlier assignments
+        let mut conflict = self.explanation(var);
+        for value in self.order_values(var) {
+            self.stats.assignments += 1;
+            self.assignment.assign(var, value);
+            // failures of the value itself, or of the variables after it
+            let (failure, later) = match self.is_consistent(var) {
+                Err(failure) => (failure, false),
+                Ok(()) => {
+                    let mark = self.domains.mark();
+                    self.path.push(var);
+                    let result = match self.infer(var) {
+                        Err(failure) => (failure, false),
+                        Ok(()) => match self.solve() {
+                            Ok(()) => return Ok(()),
+                            Err(failure) => (failure, true),
+                        },
+                    };
+                    self.path.pop();
+                    self.domains.restore(mark);
+                    self.explanations.truncate(mark);
+                    result
+                }
+            };
+            self.assignment.unassign(var);
+            if later && self.config.backjumping && !failure.contains(&var) {
+                // no other value can help, jump further back
+                return Err(failure);
+            }
+            blame(&mut conflict, failure, var);
+        }
+        self.stats.backtracks += 1;
+        if self.config.backjumping
+            && self
+                .path
+                .last()
+                .is_some_and(|last| !conflict.contains(last))
+        {
+            self.stats.backjumps += 1;
+        }
+        if self.config.learn_nogoods && !conflict.is_empty() {
+            self.learn(&conflict);
+        }
+        Err(conflict)
+    }
+
+    fn select_variable(&self) -> Option<Var> {
+        let unassigned = (0..self.csp.variables()).filter(|&var| !self.assignment.is_assigned(var));
+        match self.config.variable_ordering {
+            VariableOrdering::InOrder => unassigned.min(),
+            VariableOrdering::Mrv => unassigned.min_by_key(|&var| self.domains.get(var).len()),
+            VariableOrdering::MrvDegree => unassigned.min_by_key(|&var| {
+                (
+                    self.domains.get(var).len(),
+                    std::cmp::Reverse(self.degree(var)),
+                )
+            }),
+        }
+    }
+
+    /// Constraints between `var` and other unassigned variables
+    fn degree(&self, var: Var) -> usize {
+        self.csp
+            .constraints_on(var)
+            .filter(|constraint| {
+                constraint
+                    .scope()
+                    .iter()
+                    .any(|&other| other != var && !self.assignment.is_assigned(other))
+            })
+            .count()
+    }
+
+    fn order_values(&mut self, var: Var) -> Vec<V> {
+        let values = self.domains.get(var).to_vec();
+        match self.config.value_ordering {
+            ValueOrdering::InOrder => values,
+            ValueOrdering::LeastConstraining => {
+                let mut ruled_out: Vec<(usize, V)> = values
+                    .into_iter()
+                    .map(|value| {
+                        self.assignment.assign(var, value.clone());
+                        let count = self.conflicts(var);
+                        self.assignment.unassign(var);
+                        (count, value)
+                    })
+                    .collect();
+                ruled_out.sort_by_key(|(count, _)| *count);
+                ruled_out.into_iter().map(|(_, value)| value).collect()
+            }
+        }
+    }
+
+    /// Values of the other unassigned variables conflicting with the value
+    /// of `var`
+    fn conflicts(&mut self, var: Var) -> usize {
+        let mut count = 0;
+        for constraint in self.csp.constraints_on(var) {
+            for &other in constraint.scope() {
+                if self.assignment.is_assigned(other) {
+                    continue;
+                }
+                for value in self.domains.get(other) {
+                    self.assignment.assign(other, value.clone());
+                    self.stats.checks += 1;
+                    if !constraint.is_consistent(&self.assignment) {
+                        count += 1;
+                    }
+                }
+                self.assignment.unassign(other);
+            }
+        }
+        count
+    }
+
+    /// Whether the constraints and nogoods on `var` may still hold
+    fn is_consistent(&mut self, var: Var) -> Result<(), Conflict> {
+        for constraint in self.csp.constraints_on(var) {
+            self.stats.checks += 1;
+            if !constraint.is_consistent(&self.assignment) {
+                let mut conflict = Vec::new();
+                let assigned = constraint.scope().iter().copied();
+                blame(
+                    &mut conflict,
+                    assigned.filter(|&other| self.assignment.is_assigned(other)),
+                    var,
+                );
+                return Err(conflict);
+            }
+        }
+        for &index in &self.watched[var] {
+            let nogood = &self.nogoods[index];
+            if nogood
+                .iter()
+                .all(|(other, value)| self.assignment.get(*other) == Some(value))
+            {
+                let mut conflict = Vec::new();
+                blame(&mut conflict, nogood.iter().map(|(other, _)| *other), var);
+                return Err(conflict);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deduce what follows from the assignment of `var`, or find out which
+    /// assigned variables leave some variable without values
+    fn infer(&mut self, var: Var) -> Result<(), Conflict> {
+        match self.config.inference {
+            Inference::None => Ok(()),
+            Inference::ForwardChecking => self.forward_check(var),
+            Inference::Mac => {
+                let value = self.assignment.get(var).cloned();
+                self.domains
+                    .retain(var, |other| Some(other) == value.as_ref());
+                let queue = self.csp.involving[var].clone();
+                let consistent = propagation::ac3(
+                    self.csp,
+                    &mut self.domains,
+                    queue,
+                    &mut self.stats.propagations,
+                );
+                let path = self.path.clone();
+                self.explain(&path);
+                if consistent {
+                    Ok(())
+                } else {
+                    let mut conflict = Vec::new();
+                    blame(&mut conflict, path, var);
+                    Err(conflict)
+                }
+            }
+        }
+    }
+
+    fn forward_check(&mut self, var: Var) -> Result<(), Conflict> {
+        for constraint in self.csp.constraints_on(var) {
+            let assigned: Vec<Var> = constraint
+                .scope()
+                .iter()
+                .copied()
+                .filter(|&other| self.assignment.is_assigned(other))
+                .collect();
+            for &other in constraint.scope() {
+                if self.assignment.is_assigned(other) {
+                    continue;
+                }
+                let assignment = &mut self.assignment;
+                let checks = &mut self.stats.checks;
+                self.domains.retain(other, |value| {
+                    assignment.assign(other, value.clone());
+                    *checks += 1;
+                    constraint.is_consistent(assignment)
+                });
+                self.assignment.unassign(other);
+                self.explain(&assigned);
+                if self.domains.get(other).is_empty() {
+                    return Err(self.explanation(other));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Blame `assigned` for the values removed since the last explanation
+    fn explain(&mut self, assigned: &[Var]) {
+        let removed = self.domains.mark();
+        self.explanations.resize(removed, assigned.to_vec());
+    }
+
+    /// Assigned variables blamed for the values removed from `var`
+    fn explanation(&self, var: Var) -> Conflict {
+        let mut conflict = Vec::new();
+        for ((removed, _), assigned) in self.domains.removed_since(0).iter().zip(&self.explanations)
+        {
+            if *removed == var {
+                blame(&mut conflict, assigned.iter().copied(), var);
+            }
+        }
+        conflict
+    }
+
+    /// Record that the current values of `conflict` can't all hold
+    fn learn(&mut self, conflict: &Conflict) {
+        let nogood: Vec<(Var, V)> = conflict
+            .iter()
+            .map(|&var| {
+                (
+                    var,
+                    self.assignment
+                        .get(var)
+                        .expect("blamed variables are assigned")
+                        .clone(),
+                )
+            })
+            .collect();
+        let index = self.nogoods.len();
+        for &(var, _) in &nogood {
+            self.watched[var].push(index);
+        }
+        self.nogoods.push(nogood);
+        self.stats.nogoods += 1;
+    }
+}
@@ -0,0 +1,349 @@
+use super::{propagation, Assignment, ConflictCounter, Constraint, Domains, Var};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// The variables take pairwise different values. Propagation removes every
+/// value that no matching of the variables to different values uses.
+pub struct AllDifferent {
+    scope: Vec<Var>,
+}
+
+impl AllDifferent {
+    pub fn new(scope: Vec<Var>) -> Self {
+        Self { scope }
+    }
+}
+
+impl<V: PartialEq> Constraint<V> for AllDifferent {
+    fn scope(&self) -> &[Var] {
+        &self.scope
+    }
+
+    fn is_consistent(&self, assignment: &Assignment<V>) -> bool {
+        let values: Vec<&V> = self
+            .scope
+            .iter()
+            .filter_map(|&var| assignment.get(var))
+            .collect();
+        values
+            .iter()
+            .enumerate()
+            .all(|(i, value)| !values[i + 1..].contains(value))
+    }
+
+    /// Pairs of variables with the same value
+    fn violations(&self, assignment: &Assignment<V>) -> usize {
+        let values: Vec<&V> = self
+            .scope
+            .iter()
+            .filter_map(|&var| assignment.get(var))
+            .collect();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                values[i + 1..]
+                    .iter()
+                    .filter(|other| *other == value)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Other variables with the same value
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize {
+        let Some(value) = assignment.get(var) else {
+            return 0;
+        };
+        self.scope
+            .iter()
+            .filter(|&&other| other != var && assignment.get(other) == Some(value))
+            .count()
+    }
+
+    /// Variables grouped by value
+    fn conflict_counter<'a>(&'a self) -> Box<dyn ConflictCounter<V> + 'a>
+    where
+        V: Clone + Eq + Hash + 'a,
+    {
+        Box::new(KeyCounter::new(|_, value: &V| value.clone()))
+    }
+
+    fn propagate(&self, domains: &mut Domains<V>) -> bool
+    where
+        V: Clone,
+    {
+        propagation::all_different(&self.scope, domains)
+    }
+
+    /// Pairs of variables with the same value
+    fn nogoods(&self, domains: &[Vec<V>]) -> Vec<Vec<(Var, V)>>
+    where
+        V: Clone,
+    {
+        let mut nogoods = Vec::new();
+        for (i, &first) in self.scope.iter().enumerate() {
+            for &second in &self.scope[i + 1..] {
+                for value in &domains[first] {
+                    if domains[second].contains(value) {
+                        nogoods.push(vec![(first, value.clone()), (second, value.clone())]);
+                    }
+                }
+            }
+        }
+        nogoods
+    }
+}
+
+/// The variables have pairwise different keys, the key of a variable being
+/// computed from the variable and its value, such as the diagonal of a queen
+/// from its column and row
+pub struct AllDifferentBy<F, K> {
+    scope: Vec<Var>,
+    key: F,
+    keys: PhantomData<fn() -> K>,
+}
+
+impl<F, K> AllDifferentBy<F, K> {
+    pub fn new(scope: Vec<Var>, key: F) -> Self {
+        Self {
+            scope,
+            key,
+            keys: PhantomData,
+        }
+    }
+}
+
+impl<V, K, F> Constraint<V> for AllDifferentBy<F, K>
+where
+    F: Fn(Var, &V) -> K,
+    K: Eq + Hash,
+{
+    fn scope(&self) -> &[Var] {
+        &self.scope
+    }
+
+    fn is_consistent(&self, assignment: &Assignment<V>) -> bool {
+        let mut keys = HashSet::new();
+        self.scope.iter().all(|&var| match assignment.get(var) {
+            Some(value) => keys.insert((self.key)(var, value)),
+            None => true,
+        })
+    }
+
+    /// Pairs of variables with the same key
+    fn violations(&self, assignment: &Assignment<V>) -> usize {
+        let mut holders = HashMap::new();
+        for &var in &self.scope {
+            if let Some(value) = assignment.get(var) {
+                *holders.entry((self.key)(var, value)).or_insert(0) += 1;
+            }
+        }
+        holders
+            .values()
+            .map(|&count: &usize| count * (count - 1) / 2)
+            .sum()
+    }
+
+    /// Other variables with the same key
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize {
+        let Some(value) = assignment.get(var) else {
+            return 0;
+        };
+        let key = (self.key)(var, value);
+        self.scope
+            .iter()
+            .filter(|&&other| {
+                other != var
+                    && assignment
+                        .get(other)
+                        .is_some_and(|value| (self.key)(other, value) == key)
+            })
+            .count()
+    }
+
+    /// Variables grouped by key
+    fn conflict_counter<'a>(&'a self) -> Box<dyn ConflictCounter<V> + 'a>
+    where
+        V: Clone + Eq + Hash + 'a,
+    {
+        Box::new(KeyCounter::new(&self.key))
+    }
+
+    /// Pairs of variables with the same key
+    fn nogoods(&self, domains: &[Vec<V>]) -> Vec<Vec<(Var, V)>>
+    where
+        V: Clone,
+    {
+        let mut nogoods = Vec::new();
+        for (i, &first) in self.scope.iter().enumerate() {
+            for &second in &self.scope[i + 1..] {
+                for value in &domains[first] {
+                    let key = (self.key)(first, value);
+                    for other in &domains[second] {
+                        if (self.key)(second, other) == key {
+                            nogoods.push(vec![(first, value.clone()), (second, other.clone())]);
+                        }
+                    }
+                }
+            }
+        }
+        nogoods
+    }
+
+    /// Remove the values keyed like the only value of another variable, until
+    /// nothing changes: weaker than arc consistency, which would also match
+    /// the variables to different keys.
+    fn propagate(&self, domains: &mut Domains<V>) -> bool
+    where
+        V: Clone + PartialEq,
+    {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &var in &self.scope {
+                let [value] = domains.get(var) else {
+                    continue;
+                };
+                let key = (self.key)(var, value);
+                for &other in &self.scope {
+                    if other != var
+                        && domains.retain(other, |value| (self.key)(other, value) != key)
+                    {
+                        if domains.get(other).is_empty() {
+                            return false;
+                        }
+                        changed = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Conflict counter of the constraints whose variables must have different
+/// keys, keeping the variables that have every key
+struct KeyCounter<K, F> {
+    key: F,
+    holders: HashMap<K, Vec<Var>>,
+}
+
+impl<K, F> KeyCounter<K, F> {
+    fn new(key: F) -> Self {
+        Self {
+            key,
+            holders: HashMap::new(),
+        }
+    }
+}
+
+impl<V, K, F> ConflictCounter<V> for KeyCounter<K, F>
+where
+    F: Fn(Var, &V) -> K,
+    K: Eq + Hash,
+{
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize {
+        let Some(value) = assignment.get(var) else {
+            return 0;
+        };
+        self.holders
+            .get(&(self.key)(var, value))
+            .map_or(0, |holders| {
+                holders.iter().filter(|&&other| other != var).count()
+            })
+    }
+
+    fn assigned(&mut self, var: Var, previous: Option<&V>, assignment: &Assignment<V>) -> Vec<Var> {
+        let mut changed = Vec::new();
+        if let Some(previous) = previous {
+            let key = (self.key)(var, previous);
+            if let Some(holders) = self.holders.get_mut(&key) {
+                holders.retain(|&other| other != var);
+                changed.extend_from_slice(holders);
+                if holders.is_empty() {
+                    self.holders.remove(&key);
+                }
+            }
+        }
+        if let Some(value) = assignment.get(var) {
+            let holders = self.holders.entry((self.key)(var, value)).or_default();
+            changed.extend_from_slice(holders);
+            holders.push(var);
+        }
+        changed
+    }
+}
+
+/// Relation between the values of two variables
+pub struct BinaryConstraint<F> {
+    scope: [Var; 2],
+    relation: F,
+}
+
+impl<F> BinaryConstraint<F> {
+    /// `relation` tells whether the values of `first` and `second` are
+    /// allowed together
+    pub fn new(first: Var, second: Var, relation: F) -> Self {
+        Self {
+            scope: [first, second],
+            relation,
+        }
+    }
+}
+
+impl<V, F> Constraint<V> for BinaryConstraint<F>
+where
+    F: Fn(&V, &V) -> bool,
+{
+    fn scope(&self) -> &[Var] {
+        &self.scope
+    }
+
+    fn is_consistent(&self, assignment: &Assignment<V>) -> bool {
+        match (assignment.get(self.scope[0]), assignment.get(self.scope[1])) {
+            (Some(first), Some(second)) => (self.relation)(first, second),
+            _ => true,
+        }
+    }
+}
+
+/// Assignments of the scope violating `constraint`, as soon as the variables
+/// assigned so far do, trying the values of the scope in order
+pub(super) fn forbidden_assignments<V, C>(constraint: &C, domains: &[Vec<V>]) -> Vec<Vec<(Var, V)>>
+where
+    V: Clone,
+    C: Constraint<V> + ?Sized,
+{
+    fn extend<V: Clone, C: Constraint<V> + ?Sized>(
+        constraint: &C,
+        domains: &[Vec<V>],
+        assignment: &mut Assignment<V>,
+        assigned: usize,
+        nogoods: &mut Vec<Vec<(Var, V)>>,
+    ) {
+        let scope = constraint.scope();
+        if assigned > 0 && !constraint.is_consistent(assignment) {
+            let nogood = scope[..assigned]
+                .iter()
+                .map(|&var| (var, assignment.get(var).expect("assigned").clone()))
+                .collect();
+            nogoods.push(nogood);
+            return;
+        }
+        let Some(&var) = scope.get(assigned) else {
+            return;
+        };
+        for value in &domains[var] {
+            assignment.assign(var, value.clone());
+            extend(constraint, domains, assignment, assigned + 1, nogoods);
+        }
+        assignment.unassign(var);
+    }
+
+    let mut nogoods = Vec::new();
+    let mut assignment = Assignment::new(domains.len());
+    extend(constraint, domains, &mut assignment, 0, &mut nogoods);
+    nogoods
+}
@@ -0,0 +1,214 @@
+use super::{Assignment, Constraint, Csp, Var};
+use crate::{
+    output::{CspResult, SolverStats},
+    rng::Rng,
+};
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MinConflictsConfig {
+    /// Reassignments before giving up
+    pub max_steps: usize,
+    pub seed: u64,
+}
+
+impl Default for MinConflictsConfig {
+    fn default() -> Self {
+        Self {
+            max_steps: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Conflicts of the variables of the scope of a constraint, updated by
+/// min-conflicts after every change of value
+pub trait ConflictCounter<V> {
+    /// Conflicts of `var` with its value in `assignment`, the other
+    /// variables of the scope having the values they were last `assigned`.
+    /// Zero if `var` is unassigned.
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize;
+    /// `var` now has its value in `assignment` instead of `previous`.
+    /// Returns the other variables of the scope whose conflicts may have
+    /// changed.
+    fn assigned(&mut self, var: Var, previous: Option<&V>, assignment: &Assignment<V>) -> Vec<Var>;
+}
+
+/// Counter asking the constraint again every time
+pub(super) struct Recount<'a, C: ?Sized>(pub(super) &'a C);
+
+impl<V, C> ConflictCounter<V> for Recount<'_, C>
+where
+    C: Constraint<V> + ?Sized,
+{
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize {
+        if assignment.is_assigned(var) {
+            self.0.conflicts(var, assignment)
+        } else {
+            0
+        }
+    }
+
+    fn assigned(&mut self, _: Var, _: Option<&V>, _: &Assignment<V>) -> Vec<Var> {
+        self.0.scope().to_vec()
+    }
+}
+
+pub trait MinConflicts<V> {
+    /// Start from a greedy complete assignment, then repeatedly give a random
+    /// conflicted variable the value with the fewest conflicts, ties broken
+    /// at random. Every variable needs a non-empty domain.
+    ///
+    /// Each constraint keeps the conflicts of its scope up to date through
+    /// its `conflict_counter`. With counters that don't go over the scope, a
+    /// step costs a number of lookups proportional to the values tried, and
+    /// values without conflicts are drawn at random before the whole domain
+    /// is tried, so large problems such as n-queens with all-different
+    /// constraints are solved in about linear time.
+    fn min_conflicts(&self, config: MinConflictsConfig) -> CspResult<V>;
+}
+
+impl<V: Clone + Eq + Hash> MinConflicts<V> for Csp<V> {
+    fn min_conflicts(&self, config: MinConflictsConfig) -> CspResult<V> {
+        let mut solver = Solver {
+            csp: self,
+            rng: Rng::seed_from_u64(config.seed),
+            assignment: Assignment::new(self.variables()),
+            counters: self
+                .constraints
+                .iter()
+                .map(|constraint| constraint.conflict_counter())
+                .collect(),
+            conflicts: vec![0; self.variables()],
+            conflicted: Vec::new(),
+            position: vec![None; self.variables()],
+            stats: SolverStats::default(),
+        };
+        for var in 0..self.variables() {
+            let value = solver.least_conflicting(var);
+            solver.reassign(var, value);
+        }
+        let mut steps = 0;
+        while steps < config.max_steps && !solver.conflicted.is_empty() {
+            let var = solver.conflicted[solver.rng.below(solver.conflicted.len())];
+            let value = solver.least_conflicting(var);
+            solver.reassign(var, value);
+            steps += 1;
+        }
+        let solution = if solver.conflicted.is_empty() {
+            solver.assignment.into_values()
+        } else {
+            None
+        };
+        CspResult {
+            solution,
+            stats: solver.stats,
+        }
+    }
+}
+
+struct Solver<'a, V> {
+    csp: &'a Csp<V>,
+    rng: Rng,
+    assignment: Assignment<V>,
+    /// Of every constraint
+    counters: Vec<Box<dyn ConflictCounter<V> + 'a>>,
+    /// Conflicts of every variable, over its constraints
+    conflicts: Vec<usize>,
+    /// Variables with conflicts, in no particular order
+    conflicted: Vec<Var>,
+    /// Index of every variable in `conflicted`
+    position: Vec<Option<usize>>,
+    stats: SolverStats,
+}
+
+impl<V: Clone + Eq + Hash> Solver<'_, V> {
+    /// Value of `var` with the fewest conflicts, the others keeping their
+    /// values. Values are first drawn at random, as many times as there are
+    /// values, for one without conflicts, then all of them are tried.
+    fn least_conflicting(&mut self, var: Var) -> V {
+        let csp = self.csp;
+        let domain = csp.domain(var);
+        let previous = self.assignment.get(var).cloned();
+        let mut best = None;
+        for _ in 0..domain.len() {
+            let value = &domain[self.rng.below(domain.len())];
+            if self.conflicts_with(var, value) == 0 {
+                best = Some(value.clone());
+                break;
+            }
+        }
+        if best.is_none() {
+            let mut fewest = usize::MAX;
+            let mut ties = 0;
+            for value in domain {
+                let count = self.conflicts_with(var, value);
+                if count < fewest {
+                    fewest = count;
+                    ties = 1;
+                    best = Some(value.clone());
+                } else if count == fewest {
+                    // keep each of the tied values with the same probability
+                    ties += 1;
+                    if self.rng.below(ties) == 0 {
+                        best = Some(value.clone());
+                    }
+                }
+            }
+        }
+        match previous {
+            Some(value) => self.assignment.assign(var, value),
+            None => self.assignment.unassign(var),
+        }
+        best.expect("every domain has a value")
+    }
+
+    /// Conflicts of `var` if it took `value`
+    fn conflicts_with(&mut self, var: Var, value: &V) -> usize {
+        self.assignment.assign(var, value.clone());
+        self.count(var)
+    }
+
+    /// Conflicts of `var` with its value in the assignment
+    fn count(&mut self, var: Var) -> usize {
+        let mut count = 0;
+        for &index in &self.csp.involving[var] {
+            self.stats.checks += 1;
+            count += self.counters[index].conflicts(var, &self.assignment);
+        }
+        count
+    }
+
+    fn reassign(&mut self, var: Var, value: V) {
+        self.stats.assignments += 1;
+        let previous = self.assignment.get(var).cloned();
+        self.assignment.assign(var, value);
+        let mut changed = vec![var];
+        for &index in &self.csp.involving[var] {
+            changed.extend(self.counters[index].assigned(var, previous.as_ref(), &self.assignment));
+        }
+        changed.sort_unstable();
+        changed.dedup();
+        for other in changed {
+            self.conflicts[other] = self.count(other);
+            self.update_conflicted(other);
+        }
+    }
+
+    fn update_conflicted(&mut self, var: Var) {
+        match (self.conflicts[var] > 0, self.position[var]) {
+            (true, None) => {
+                self.position[var] = Some(self.conflicted.len());
+                self.conflicted.push(var);
+            }
+            (false, Some(position)) => {
+                self.conflicted.swap_remove(position);
+                if let Some(&moved) = self.conflicted.get(position) {
+                    self.position[moved] = Some(position);
+                }
+                self.position[var] = None;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Constraint satisfaction problems over finite domains:
+//! - backtracking search with MRV/degree and least-constraining-value
+//!   ordering, forward checking or maintained arc consistency,
+//!   conflict-directed backjumping and nogood learning
+//! - AC-3 arc consistency, generalised to constraints of any arity
+//! - min-conflicts local search, with conflict counts kept up to date by the
+//!   constraints
+
+mod backtracking;
+mod constraints;
+mod min_conflicts;
+mod propagation;
+
+pub use backtracking::{
+    BacktrackingConfig, BacktrackingSearch, Inference, ValueOrdering, VariableOrdering,
+};
+pub use constraints::{AllDifferent, AllDifferentBy, BinaryConstraint};
+pub use min_conflicts::{ConflictCounter, MinConflicts, MinConflictsConfig};
+pub use propagation::ArcConsistency;
+
+use std::hash::Hash;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// Index of a variable, in the order the variables were added
+pub type Var = usize;
+
+/// Values given to some of the variables of a problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment<V> {
+    values: Vec<Option<V>>,
+}
+
+impl<V> Assignment<V> {
+    /// No variable assigned yet
+    pub fn new(variables: usize) -> Self {
+        Self {
+            values: (0..variables).map(|_| None).collect(),
+        }
+    }
+
+    pub fn get(&self, var: Var) -> Option<&V> {
+        self.values[var].as_ref()
+    }
+
+    pub fn is_assigned(&self, var: Var) -> bool {
+        self.values[var].is_some()
+    }
+
+    pub fn assign(&mut self, var: Var, value: V) {
+        self.values[var] = Some(value);
+    }
+
+    pub fn unassign(&mut self, var: Var) {
+        self.values[var] = None;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.values.iter().all(Option::is_some)
+    }
+
+    /// Value of every variable, if they are all assigned
+    pub fn into_values(self) -> Option<Vec<V>> {
+        self.values.into_iter().collect()
+    }
+}
+
+/// Values still allowed for every variable, remembering the ones removed so
+/// that they can be put back
+#[derive(Debug, Clone)]
+pub struct Domains<V> {
+    values: Vec<Vec<V>>,
+    removed: Vec<(Var, V)>,
+}
+
+impl<V: Clone + PartialEq> Domains<V> {
+    pub fn new(values: Vec<Vec<V>>) -> Self {
+        Self {
+            values,
+            removed: Vec::new(),
+        }
+    }
+
+    pub fn variables(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn get(&self, var: Var) -> &[V] {
+        &self.values[var]
+    }
+
+    /// Keep the values of `var` for which `keep` holds, returning whether
+    /// some were removed
+    pub fn retain<F>(&mut self, var: Var, mut keep: F) -> bool
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let before = self.removed.len();
+        let mut kept = Vec::with_capacity(self.values[var].len());
+        for value in std::mem::take(&mut self.values[var]) {
+            if keep(&value) {
+                kept.push(value);
+            } else {
+                self.removed.push((var, value));
+            }
+        }
+        self.values[var] = kept;
+        self.removed.len() > before
+    }
+
+    pub fn remove(&mut self, var: Var, value: &V) -> bool {
+        self.retain(var, |other| other != value)
+    }
+
+    /// Point to come back to with `restore`
+    pub fn mark(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Variables and values removed since `mark`, oldest first
+    pub fn removed_since(&self, mark: usize) -> &[(Var, V)] {
+        &self.removed[mark..]
+    }
+
+    /// Put back the values removed since `mark`
+    pub fn restore(&mut self, mark: usize) {
+        for (var, value) in self.removed.drain(mark..).rev() {
+            self.values[var].push(value);
+        }
+    }
+
+    pub fn into_values(self) -> Vec<Vec<V>> {
+        self.values
+    }
+}
+
+pub trait Constraint<V> {
+    /// Variables the constraint is about
+    fn scope(&self) -> &[Var];
+    /// Whether the values of the assigned variables of the scope may still
+    /// satisfy the constraint. The others can take any value.
+    fn is_consistent(&self, assignment: &Assignment<V>) -> bool;
+
+    /// How badly the assigned variables of the scope violate the
+    /// constraint, zero if and only if they are consistent
+    fn violations(&self, assignment: &Assignment<V>) -> usize {
+        usize::from(!self.is_consistent(assignment))
+    }
+
+    /// Violations of the constraint that `var`, assigned, takes part in:
+    /// the ones a new value of `var` alone could repair. Min-conflicts
+    /// reassigns the variables with conflicts, to the values with the
+    /// fewest.
+    ///
+    /// By default every violation of the constraint, which is exact for
+    /// binary constraints.
+    fn conflicts(&self, var: Var, assignment: &Assignment<V>) -> usize {
+        let _ = var;
+        self.violations(assignment)
+    }
+
+    /// Conflicts of the variables of the scope, kept up to date by
+    /// min-conflicts as their values change.
+    ///
+    /// By default `conflicts` is asked every time and every variable of the
+    /// scope may have changed, which costs a pass over the scope, so
+    /// constraints with a large scope should provide their own.
+    fn conflict_counter<'a>(&'a self) -> Box<dyn ConflictCounter<V> + 'a>
+    where
+        V: Clone + Eq + Hash + 'a,
+    {
+        Box::new(min_conflicts::Recount(self))
+    }
+
+    /// Assignments of some of the variables of the scope that violate the
+    /// constraint whatever the values of the others, covering together every
+    /// violating assignment of the scope. `domains` has the values of every
+    /// variable. Used to encode the constraint in CNF.
+    ///
+    /// By default every combination of the values of the scope may be tried.
+    fn nogoods(&self, domains: &[Vec<V>]) -> Vec<Vec<(Var, V)>>
+    where
+        V: Clone,
+    {
+        constraints::forbidden_assignments(self, domains)
+    }
+
+    /// Remove the values of the scope that can't be part of any assignment of
+    /// the scope satisfying the constraint (generalised arc consistency).
+    /// Returns `false` if a domain becomes empty.
+    ///
+    /// By default every combination of the values of the scope may be tried,
+    /// so constraints with a large scope should provide their own.
+    fn propagate(&self, domains: &mut Domains<V>) -> bool
+    where
+        V: Clone + PartialEq,
+    {
+        propagation::generalised_arc_consistency(self, domains)
+    }
+}
+
+/// Variables with finite domains, and constraints on their values
+pub struct Csp<V> {
+    /// Shared by the variables added together
+    domains: Vec<Rc<[V]>>,
+    constraints: Vec<Box<dyn Constraint<V>>>,
+    /// Constraints of every variable
+    involving: Vec<Vec<usize>>,
+}
+
+impl<V> Default for Csp<V> {
+    fn default() -> Self {
+        Self {
+            domains: Vec::new(),
+            constraints: Vec::new(),
+            involving: Vec::new(),
+        }
+    }
+}
+
+impl<V: Clone + PartialEq> Csp<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_variable(&mut self, domain: Vec<V>) -> Var {
+        self.domains.push(domain.into());
+        self.involving.push(Vec::new());
+        self.domains.len() - 1
+    }
+
+    /// `count` variables with the same domain, stored once
+    pub fn add_variables(&mut self, count: usize, domain: Vec<V>) -> Range<Var> {
+        let first = self.domains.len();
+        let domain: Rc<[V]> = domain.into();
+        self.domains.resize(first + count, domain);
+        self.involving.resize_with(first + count, Vec::new);
+        first..first + count
+    }
+
+    /// Every variable of the scope must have been added already
+    pub fn add_constraint<C>(&mut self, constraint: C)
+    where
+        C: Constraint<V> + 'static,
+    {
+        let index = self.constraints.len();
+        for &var in constraint.scope() {
+            self.involving[var].push(index);
+        }
+        self.constraints.push(Box::new(constraint));
+    }
+
+    pub fn variables(&self) -> usize {
+        self.domains.len()
+    }
+
+    pub fn domain(&self, var: Var) -> &[V] {
+        &self.domains[var]
+    }
+
+    /// Values of the domain of every variable, to be pruned by a search
+    fn domain_values(&self) -> Vec<Vec<V>> {
+        self.domains.iter().map(|domain| domain.to_vec()).collect()
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint<V>> {
+        self.constraints
+            .iter()
+            .map(|constraint| constraint.as_ref())
+    }
+
+    /// Constraints whose scope contains `var`
+    pub fn constraints_on(&self, var: Var) -> impl Iterator<Item = &dyn Constraint<V>> {
+        self.involving[var]
+            .iter()
+            .map(|&index| self.constraints[index].as_ref())
+    }
+
+    /// Whether `values` gives every variable a value of its domain and
+    /// satisfies every constraint
+    pub fn is_solution(&self, values: &[V]) -> bool {
+        if values.len() != self.variables() {
+            return false;
+        }
+        let mut assignment = Assignment::new(values.len());
+        for (var, value) in values.iter().enumerate() {
+            if !self.domains[var].contains(value) {
+                return false;
+            }
+            assignment.assign(var, value.clone());
+        }
+        self.constraints()
+            .all(|constraint| constraint.is_consistent(&assignment))
+    }
+}
@@ -0,0 +1,275 @@
+use super::{Assignment, Constraint, Csp, Domains, Var};
+use std::collections::VecDeque;
+
+pub trait ArcConsistency<V> {
+    /// Domains reduced by AC-3 until every constraint is arc consistent,
+    /// `None` if a variable has no value left
+    fn ac3(&self) -> Option<Vec<Vec<V>>>;
+}
+
+impl<V: Clone + PartialEq> ArcConsistency<V> for Csp<V> {
+    fn ac3(&self) -> Option<Vec<Vec<V>>> {
+        let mut domains = Domains::new(self.domain_values());
+        let queue = (0..self.constraints.len()).collect();
+        ac3(self, &mut domains, queue, &mut 0).then(|| domains.into_values())
+    }
+}
+
+/// Propagate the constraints of `queue`, and again the constraints of every
+/// variable whose domain shrinks, until nothing changes. Returns `false` if a
+/// domain becomes empty.
+pub(super) fn ac3<V: Clone + PartialEq>(
+    csp: &Csp<V>,
+    domains: &mut Domains<V>,
+    queue: Vec<usize>,
+    propagations: &mut usize,
+) -> bool {
+    let mut queued = vec![false; csp.constraints.len()];
+    let mut queue: VecDeque<usize> = queue
+        .into_iter()
+        .filter(|&index| !std::mem::replace(&mut queued[index], true))
+        .collect();
+    while let Some(index) = queue.pop_front() {
+        queued[index] = false;
+        let constraint = &csp.constraints[index];
+        let sizes: Vec<usize> = constraint
+            .scope()
+            .iter()
+            .map(|&var| domains.get(var).len())
+            .collect();
+        *propagations += 1;
+        if !constraint.propagate(domains) {
+            return false;
+        }
+        for (&var, size) in constraint.scope().iter().zip(sizes) {
+            if domains.get(var).len() == size {
+                continue;
+            }
+            for &other in &csp.involving[var] {
+                if !std::mem::replace(&mut queued[other], true) {
+                    queue.push_back(other);
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Keep the values of the scope that have a support: values of the other
+/// variables of the scope satisfying the constraint with them
+pub(super) fn generalised_arc_consistency<V, C>(constraint: &C, domains: &mut Domains<V>) -> bool
+where
+    V: Clone + PartialEq,
+    C: Constraint<V> + ?Sized,
+{
+    let scope = constraint.scope();
+    let mut assignment = Assignment::new(domains.variables());
+    for &var in scope {
+        let values = domains.get(var).to_vec();
+        for value in values {
+            assignment.assign(var, value.clone());
+            if !supported(constraint, scope, domains, &mut assignment, var) {
+                domains.remove(var, &value);
+            }
+            assignment.unassign(var);
+        }
+        if domains.get(var).is_empty() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether the unassigned variables of `scope` other than `var` can be
+/// assigned consistently
+fn supported<V, C>(
+    constraint: &C,
+    scope: &[Var],
+    domains: &Domains<V>,
+    assignment: &mut Assignment<V>,
+    var: Var,
+) -> bool
+where
+    V: Clone + PartialEq,
+    C: Constraint<V> + ?Sized,
+{
+    if !constraint.is_consistent(assignment) {
+        return false;
+    }
+    let Some(&next) = scope
+        .iter()
+        .find(|&&other| other != var && !assignment.is_assigned(other))
+    else {
+        return true;
+    };
+    for value in domains.get(next) {
+        assignment.assign(next, value.clone());
+        let found = supported(constraint, scope, domains, assignment, var);
+        assignment.unassign(next);
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+/// Generalised arc consistency of an all-different constraint, from a
+/// maximum matching between its variables and their values (Régin). A value
+/// is kept if some maximum matching gives it to the variable.
+pub(super) fn all_different<V: Clone + PartialEq>(scope: &[Var], domains: &mut Domains<V>) -> bool {
+    let mut values: Vec<V> = Vec::new();
+    let edges: Vec<Vec<usize>> = scope
+        .iter()
+        .map(|&var| {
+            domains
+                .get(var)
+                .iter()
+                .map(
+                    |value| match values.iter().position(|other| other == value) {
+                        Some(index) => index,
+                        None => {
+                            values.push(value.clone());
+                            values.len() - 1
+                        }
+                    },
+                )
+                .collect()
+        })
+        .collect();
+
+    let mut matched_to: Vec<Option<usize>> = vec![None; values.len()];
+    for var in 0..scope.len() {
+        let mut visited = vec![false; values.len()];
+        if !augment(var, &edges, &mut matched_to, &mut visited) {
+            return false;
+        }
+    }
+    let mut matching = vec![0; scope.len()];
+    for (value, var) in matched_to.iter().enumerate() {
+        if let Some(var) = var {
+            matching[*var] = value;
+        }
+    }
+
+    // variables then values; matched edges go from the variable to the
+    // value, the others from the value to the variable
+    let n = scope.len();
+    let mut graph = vec![Vec::new(); n + values.len()];
+    for (var, var_edges) in edges.iter().enumerate() {
+        for &value in var_edges {
+            if matching[var] == value {
+                graph[var].push(n + value);
+            } else {
+                graph[n + value].push(var);
+            }
+        }
+    }
+    // values at the end of an alternating path from a free value
+    let mut reachable = vec![false; graph.len()];
+    let mut stack: Vec<usize> = (0..values.len())
+        .filter(|&value| matched_to[value].is_none())
+        .map(|value| n + value)
+        .collect();
+    while let Some(node) = stack.pop() {
+        if !std::mem::replace(&mut reachable[node], true) {
+            stack.extend(graph[node].iter().copied());
+        }
+    }
+    let component = strongly_connected_components(&graph);
+
+    for (var, var_edges) in edges.iter().enumerate() {
+        let kept: Vec<&V> = var_edges
+            .iter()
+            .filter(|&&value| {
+                matching[var] == value
+                    || reachable[n + value]
+                    || component[var] == component[n + value]
+            })
+            .map(|&value| &values[value])
+            .collect();
+        domains.retain(scope[var], |value| kept.contains(&value));
+    }
+    true
+}
+
+/// Kuhn's augmenting path from `var`
+fn augment(
+    var: usize,
+    edges: &[Vec<usize>],
+    matched_to: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &value in &edges[var] {
+        if std::mem::replace(&mut visited[value], true) {
+            continue;
+        }
+        if matched_to[value].is_none_or(|other| augment(other, edges, matched_to, visited)) {
+            matched_to[value] = Some(var);
+            return true;
+        }
+    }
+    false
+}
+
+/// Component of every node, with Tarjan's algorithm
+fn strongly_connected_components(graph: &[Vec<usize>]) -> Vec<usize> {
+    struct Tarjan<'a> {
+        graph: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        component: Vec<usize>,
+        visited: usize,
+        components: usize,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.visited);
+            self.low[node] = self.visited;
+            self.visited += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for &next in &self.graph[node] {
+                match self.index[next] {
+                    None => {
+                        self.visit(next);
+                        self.low[node] = self.low[node].min(self.low[next]);
+                    }
+                    Some(index) if self.on_stack[next] => {
+                        self.low[node] = self.low[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+            if Some(self.low[node]) == self.index[node] {
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    self.component[member] = self.components;
+                    if member == node {
+                        break;
+                    }
+                }
+                self.components += 1;
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        index: vec![None; graph.len()],
+        low: vec![0; graph.len()],
+        stack: Vec::new(),
+        on_stack: vec![false; graph.len()],
+        component: vec![0; graph.len()],
+        visited: 0,
+        components: 0,
+    };
+    for node in 0..graph.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.component
+}
@@ -1,5 +1,5 @@
 use crate::State;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 pub struct StateCacheSet<S: State> {
     seen: HashSet<S>,
@@ -24,3 +24,49 @@ impl<S: State> StateCacheSet<S> {
         self.seen.len()
     }
 }
+
+/// Keeps, for every state, the cost vectors of the non-dominated paths reaching it
+pub struct ParetoCacheSet<S: State> {
+    labels: HashMap<S, Vec<Vec<usize>>>,
+}
+
+impl<S: State> ParetoCacheSet<S> {
+    pub fn new() -> Self {
+        Self {
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Store `cost` as a label of `state`, dropping the labels it dominates.
+    /// Returns false, leaving the cache untouched, when an equal or dominating
+    /// label is already known.
+    pub fn insert(&mut self, state: &S, cost: &[usize]) -> bool {
+        let labels = self.labels.entry(state.clone()).or_default();
+        if labels.iter().any(|label| weakly_dominates(label, cost)) {
+            return false;
+        }
+        labels.retain(|label| !weakly_dominates(cost, label));
+        labels.push(cost.to_vec());
+        true
+    }
+
+    /// Whether `cost` is still one of the non-dominated labels of `state`
+    pub fn contains(&self, state: &S, cost: &[usize]) -> bool {
+        self.labels
+            .get(state)
+            .is_some_and(|labels| labels.iter().any(|label| label.as_slice() == cost))
+    }
+}
+
+/// `a` is no worse than `b` in every objective. Missing trailing objectives
+/// count as zero.
+pub fn weakly_dominates(a: &[usize], b: &[usize]) -> bool {
+    (0..a.len().max(b.len()))
+        .all(|i| a.get(i).copied().unwrap_or(0) <= b.get(i).copied().unwrap_or(0))
+}
+
+/// Pareto dominance: `a` is no worse than `b` in every objective and strictly
+/// better in at least one
+pub fn dominates(a: &[usize], b: &[usize]) -> bool {
+    weakly_dominates(a, b) && !weakly_dominates(b, a)
+}
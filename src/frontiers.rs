@@ -1,5 +1,6 @@
 use crate::{State, Node};
-use std::collections::{VecDeque, BinaryHeap};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
 
 pub trait Frontier: Iterator {
     type State: State;
@@ -57,8 +58,7 @@ impl<S: State> Frontier for StackFrontier<S> {
     type State = S;
 
     fn new(initial_state: Self::State) -> Self {
-        let mut stack = Vec::new();
-        stack.push(Node::new(initial_state));
+        let stack = vec![Node::new(initial_state)];
         Self { stack }
     }
 
@@ -71,16 +71,69 @@ impl<S: State> Frontier for StackFrontier<S> {
     }
 }
 
-// pub struct PriorityFrontier<S> where 
-//     S: State + PartialEq + Eq + PartialOrd + Ord,
-// {
-//     heap: BinaryHeap<S>,
-// }
-//
-// impl<S: State + PartialEq + Eq + PartialOrd + Ord> Iterator for PriorityFrontier<S> {
-//     type Item = S;
-//
-//     fn next(&mut self) -> Option<Self::Item> {
-//         self.heap.pop()
-//     }
-// }
+/// Frontier popping the node with the lowest priority first, ties are popped in
+/// insertion order
+pub struct PriorityFrontier<S: State, P: Ord> {
+    heap: BinaryHeap<Prioritized<S, P>>,
+    pushed: usize,
+}
+
+struct Prioritized<S: State, P: Ord> {
+    priority: P,
+    sequence: usize,
+    node: Node<S>,
+}
+
+impl<S: State, P: Ord> PartialEq for Prioritized<S, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: State, P: Ord> Eq for Prioritized<S, P> {}
+
+impl<S: State, P: Ord> PartialOrd for Prioritized<S, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: State, P: Ord> Ord for Prioritized<S, P> {
+    // reversed so that the max-heap pops the lowest priority first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl<S: State, P: Ord> PriorityFrontier<S, P> {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            pushed: 0,
+        }
+    }
+
+    pub fn push(&mut self, node: Node<S>, priority: P) {
+        self.heap.push(Prioritized {
+            priority,
+            sequence: self.pushed,
+            node,
+        });
+        self.pushed += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<(Node<S>, P)> {
+        self.heap.pop().map(|entry| (entry.node, entry.priority))
+    }
+}
+
+impl<S: State, P: Ord> Iterator for PriorityFrontier<S, P> {
+    type Item = Node<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pop().map(|(node, _)| node)
+    }
+}
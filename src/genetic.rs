@@ -0,0 +1,290 @@
+//! Population-based optimisation:
+//! - genetic algorithm with tournament or roulette selection and elitism
+
+use crate::{local::Objective, output::LocalSearchResult, rng::Rng, Space, State};
+use rayon::prelude::*;
+
+/// Individual of a genetic algorithm
+pub trait Genome: Clone + Send + Sync {
+    /// The higher the better
+    fn fitness(&self) -> f64;
+    fn crossover(&self, other: &Self, rng: &mut Rng) -> Self;
+    fn mutate(&mut self, rng: &mut Rng);
+}
+
+/// Recombination of two states into a child
+pub type Crossover<S> = fn(&S, &S, &mut Rng) -> S;
+
+/// Genome wrapping a state: its fitness is the opposite of the objective and
+/// a mutation applies one of its available actions at random. Without a
+/// crossover function the child is a copy of one of the parents.
+pub struct StateGenome<'a, S: State, O> {
+    pub state: S,
+    objective: &'a O,
+    crossover: Option<Crossover<S>>,
+}
+
+impl<'a, S: State, O> StateGenome<'a, S, O> {
+    pub fn new(state: S, objective: &'a O) -> Self {
+        Self {
+            state,
+            objective,
+            crossover: None,
+        }
+    }
+
+    pub fn with_crossover(mut self, crossover: Crossover<S>) -> Self {
+        self.crossover = Some(crossover);
+        self
+    }
+}
+
+impl<'a, S: State, O> Clone for StateGenome<'a, S, O> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            objective: self.objective,
+            crossover: self.crossover,
+        }
+    }
+}
+
+impl<'a, S, O> Genome for StateGenome<'a, S, O>
+where
+    S: State + Send + Sync,
+    O: Objective<S> + Sync,
+{
+    fn fitness(&self) -> f64 {
+        -self.objective.value(&self.state)
+    }
+
+    fn crossover(&self, other: &Self, rng: &mut Rng) -> Self {
+        let state = match self.crossover {
+            Some(crossover) => crossover(&self.state, &other.state, rng),
+            None if rng.chance(0.5) => self.state.clone(),
+            None => other.state.clone(),
+        };
+        Self {
+            state,
+            objective: self.objective,
+            crossover: self.crossover,
+        }
+    }
+
+    fn mutate(&mut self, rng: &mut Rng) {
+        let actions = self.state.get_available_actions();
+        if let Some(action) = rng.choose(&actions) {
+            self.state = self.state.apply(action);
+        }
+    }
+}
+
+/// How parents are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// Fittest of this many individuals drawn at random
+    Tournament(usize),
+    /// Drawn with probability proportional to their fitness, shifted so the
+    /// least fit individual has (almost) no chance
+    Roulette,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeneticConfig {
+    pub population_size: usize,
+    pub generations: usize,
+    pub selection: Selection,
+    /// Fittest individuals copied unchanged into the next generation
+    pub elitism: usize,
+    pub crossover_rate: f64,
+    pub mutation_rate: f64,
+    /// Stop as soon as an individual at least this fit is found
+    pub target_fitness: Option<f64>,
+    pub seed: u64,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 100,
+            generations: 1_000,
+            selection: Selection::Tournament(3),
+            elitism: 2,
+            crossover_rate: 0.9,
+            mutation_rate: 0.2,
+            target_fitness: None,
+            seed: 0,
+        }
+    }
+}
+
+/// Outcome of a genetic algorithm
+#[derive(Debug, Clone)]
+pub struct Evolution<G> {
+    pub best: G,
+    pub fitness: f64,
+    pub generations: usize,
+    pub evaluations: usize,
+    /// Best fitness so far, for the initial population and every generation
+    pub trajectory: Vec<f64>,
+    /// Best fitness of the initial population and of every generation, which
+    /// may drop without elitism
+    pub generation_best: Vec<f64>,
+}
+
+/// Evolve `population`, evaluating and breeding every generation in
+/// parallel. Results only depend on the seed, not on the thread count.
+pub fn evolve<G: Genome>(population: Vec<G>, config: &GeneticConfig) -> Evolution<G> {
+    assert!(!population.is_empty(), "empty initial population");
+    let mut rng = Rng::seed_from_u64(config.seed);
+    let size = config.population_size.max(1);
+    let mut scored: Vec<(G, f64)> = population
+        .into_par_iter()
+        .map(|genome| {
+            let fitness = genome.fitness();
+            (genome, fitness)
+        })
+        .collect();
+    let mut evaluations = scored.len();
+    sort_by_fitness(&mut scored);
+    let mut best = scored[0].clone();
+    let mut trajectory = vec![best.1];
+    let mut generation_best = vec![best.1];
+    let mut generations = 0;
+    while generations < config.generations
+        && !config.target_fitness.is_some_and(|target| best.1 >= target)
+    {
+        let elites = config.elitism.min(scored.len()).min(size);
+        let seeds: Vec<u64> = (elites..size).map(|_| rng.next_u64()).collect();
+        let offspring: Vec<(G, f64)> = seeds
+            .into_par_iter()
+            .map(|seed| {
+                let mut rng = Rng::seed_from_u64(seed);
+                let parent = select(&scored, config.selection, &mut rng);
+                let mut child = if rng.chance(config.crossover_rate) {
+                    let other = select(&scored, config.selection, &mut rng);
+                    parent.crossover(other, &mut rng)
+                } else {
+                    parent.clone()
+                };
+                if rng.chance(config.mutation_rate) {
+                    child.mutate(&mut rng);
+                }
+                let fitness = child.fitness();
+                (child, fitness)
+            })
+            .collect();
+        evaluations += offspring.len();
+        scored.truncate(elites);
+        scored.extend(offspring);
+        sort_by_fitness(&mut scored);
+        if scored[0].1 > best.1 {
+            best = scored[0].clone();
+        }
+        generations += 1;
+        trajectory.push(best.1);
+        generation_best.push(scored[0].1);
+    }
+    Evolution {
+        best: best.0,
+        fitness: best.1,
+        generations,
+        evaluations,
+        trajectory,
+        generation_best,
+    }
+}
+
+fn sort_by_fitness<G>(scored: &mut [(G, f64)]) {
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+}
+
+fn select<'a, G>(scored: &'a [(G, f64)], selection: Selection, rng: &mut Rng) -> &'a G {
+    match selection {
+        Selection::Tournament(size) => {
+            let mut winner = rng.below(scored.len());
+            for _ in 1..size {
+                let contender = rng.below(scored.len());
+                if scored[contender].1 > scored[winner].1 {
+                    winner = contender;
+                }
+            }
+            &scored[winner].0
+        }
+        Selection::Roulette => {
+            let worst = scored
+                .iter()
+                .map(|(_, fitness)| *fitness)
+                .fold(f64::INFINITY, f64::min);
+            let weight = |fitness: f64| fitness - worst + f64::EPSILON;
+            let total: f64 = scored.iter().map(|(_, fitness)| weight(*fitness)).sum();
+            let mut ball = rng.unit() * total;
+            for (genome, fitness) in scored {
+                ball -= weight(*fitness);
+                if ball <= 0.0 {
+                    return genome;
+                }
+            }
+            &scored[scored.len() - 1].0
+        }
+    }
+}
+
+pub trait GeneticSearch<S: Space> {
+    /// Minimise `objective` with a genetic algorithm whose first population is
+    /// made of random walks from the initial state
+    fn genetic_search<O>(
+        &self,
+        objective: &O,
+        crossover: Option<Crossover<S::State>>,
+        config: GeneticConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State> + Sync;
+}
+
+impl<S> GeneticSearch<S> for S
+where
+    S: Space,
+    S::State: State + Send + Sync,
+{
+    fn genetic_search<O>(
+        &self,
+        objective: &O,
+        crossover: Option<Crossover<S::State>>,
+        config: GeneticConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State> + Sync,
+    {
+        let mut rng = Rng::seed_from_u64(config.seed);
+        let mut genome = StateGenome::new(self.initial_state(), objective);
+        genome.crossover = crossover;
+        let population = (0..config.population_size.max(1))
+            .map(|i| {
+                let mut individual = genome.clone();
+                for _ in 0..i {
+                    individual.mutate(&mut rng);
+                }
+                individual
+            })
+            .collect();
+        let config = GeneticConfig {
+            seed: rng.next_u64(),
+            ..config
+        };
+        let evolution = evolve(population, &config);
+        LocalSearchResult {
+            state: evolution.best.state,
+            value: -evolution.fitness,
+            steps: evolution.generations,
+            evaluations: evolution.evaluations,
+            restarts: 0,
+            trajectory: evolution
+                .trajectory
+                .into_iter()
+                .map(|fitness| -fitness)
+                .collect(),
+        }
+    }
+}
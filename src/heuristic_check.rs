@@ -0,0 +1,168 @@
+//! Checks of a heuristic against the true costs in a region of a space:
+//! - admissibility, against the cheapest paths to a goal in the region
+//! - consistency, along every action of the region
+
+use crate::{
+    output::{HeuristicReport, HeuristicViolation},
+    CostAction, Heuristic, Space, State,
+};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeuristicCheckConfig {
+    /// Most states of the region, explored breadth-first from the initial
+    /// state
+    pub max_states: usize,
+}
+
+impl Default for HeuristicCheckConfig {
+    fn default() -> Self {
+        Self { max_states: 10_000 }
+    }
+}
+
+pub trait HeuristicCheck<S: Space> {
+    /// Compare the estimates of `heuristic` in a region around the initial
+    /// state with the cheapest paths from its states to its goals, found
+    /// backwards from the goals, and with the costs of its actions. Paths
+    /// leaving the region are not considered, so some overestimates may be
+    /// missed unless the region is exhaustive, but the ones reported are
+    /// real.
+    fn check_heuristic<H>(
+        &self,
+        heuristic: &H,
+        config: HeuristicCheckConfig,
+    ) -> HeuristicReport<S::State>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> HeuristicCheck<S> for S
+where
+    S: Space,
+    <S::State as State>::Action: CostAction,
+{
+    fn check_heuristic<H>(
+        &self,
+        heuristic: &H,
+        config: HeuristicCheckConfig,
+    ) -> HeuristicReport<S::State>
+    where
+        H: Heuristic<S::State>,
+    {
+        // breadth-first, so the states are expanded in the order of their
+        // numbers
+        let initial_state = self.initial_state();
+        let mut index = HashMap::from([(initial_state.clone(), 0)]);
+        let mut states = vec![initial_state];
+        let mut parents = vec![None];
+        let mut actions: Vec<Vec<(<S::State as State>::Action, usize)>> = Vec::new();
+        // whether the region left out some successors of its states
+        let mut cut = false;
+        while actions.len() < states.len() && states.len() < config.max_states {
+            let from = actions.len();
+            let mut successors = Vec::new();
+            for action in states[from].get_available_actions() {
+                let child = states[from].apply(&action);
+                let to = match index.get(&child) {
+                    Some(&to) => to,
+                    None if states.len() < config.max_states => {
+                        index.insert(child.clone(), states.len());
+                        states.push(child);
+                        parents.push(Some((from, action.clone())));
+                        states.len() - 1
+                    }
+                    None => {
+                        cut = true;
+                        continue;
+                    }
+                };
+                successors.push((action, to));
+            }
+            actions.push(successors);
+        }
+
+        // cheapest paths to the goals of the region, by the index of their
+        // first action
+        let mut predecessors = vec![Vec::new(); states.len()];
+        for (from, successors) in actions.iter().enumerate() {
+            for (k, (_, to)) in successors.iter().enumerate() {
+                predecessors[*to].push((from, k));
+            }
+        }
+        let mut costs = vec![usize::MAX; states.len()];
+        let mut next = vec![None; states.len()];
+        let mut queue = BinaryHeap::new();
+        for (i, state) in states.iter().enumerate() {
+            if self.is_goal(state) {
+                costs[i] = 0;
+                queue.push(Reverse((0, i)));
+            }
+        }
+        while let Some(Reverse((cost, to))) = queue.pop() {
+            if cost > costs[to] {
+                continue;
+            }
+            for &(from, k) in &predecessors[to] {
+                let from_cost = cost + actions[from][k].0.cost();
+                if from_cost < costs[from] {
+                    costs[from] = from_cost;
+                    next[from] = Some(k);
+                    queue.push(Reverse((from_cost, from)));
+                }
+            }
+        }
+
+        let path = |mut i: usize| {
+            let mut path = Vec::new();
+            while let Some((parent, action)) = &parents[i] {
+                path.push(action.clone());
+                i = *parent;
+            }
+            path.reverse();
+            path
+        };
+        let estimates: Vec<usize> = states
+            .iter()
+            .map(|state| heuristic.estimate(state))
+            .collect();
+        let mut violations = Vec::new();
+        for (i, state) in states.iter().enumerate() {
+            if costs[i] < estimates[i] {
+                let mut to_goal = Vec::new();
+                let mut j = i;
+                while let Some(k) = next[j] {
+                    to_goal.push(actions[j][k].0.clone());
+                    j = actions[j][k].1;
+                }
+                violations.push(HeuristicViolation::Overestimate {
+                    path: path(i),
+                    state: state.clone(),
+                    estimate: estimates[i],
+                    to_goal,
+                    cost: costs[i],
+                });
+            }
+            for (action, to) in actions.get(i).into_iter().flatten() {
+                let cost = action.cost();
+                if estimates[i] > cost.saturating_add(estimates[*to]) {
+                    violations.push(HeuristicViolation::Inconsistent {
+                        path: path(i),
+                        state: state.clone(),
+                        estimate: estimates[i],
+                        action: action.clone(),
+                        cost,
+                        next: states[*to].clone(),
+                        next_estimate: estimates[*to],
+                    });
+                }
+            }
+        }
+        HeuristicReport {
+            states: states.len(),
+            exhaustive: !cut && actions.len() == states.len(),
+            violations,
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Incremental search algorithms:
+//! - LPA* (lifelong planning A*)
+
+use crate::{output::SearchResult, CostAction, Heuristic, Space, State};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+const INFINITY: usize = usize::MAX;
+
+type Key = (usize, usize);
+
+pub trait IncrementalSearch<S: Space> {
+    /// Start a planner that keeps its search state between calls to
+    /// `compute_path`, repairing it after transitions change cost. The
+    /// heuristic must be consistent.
+    fn incremental_search<'a, H>(&'a self, heuristic: &'a H) -> LpaStar<'a, S, H>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> IncrementalSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn incremental_search<'a, H>(&'a self, heuristic: &'a H) -> LpaStar<'a, S, H>
+    where
+        H: Heuristic<S::State>,
+    {
+        LpaStar::new(self, heuristic)
+    }
+}
+
+struct Record<S: State> {
+    state: S,
+    g: usize,
+    rhs: usize,
+    h: usize,
+    predecessors: Vec<(usize, S::Action)>,
+    successors: Option<Vec<usize>>,
+    queued: Option<Key>,
+}
+
+/// Lifelong planning A* over a space whose transition costs may change
+/// between searches.
+///
+/// Costs come from the actions unless overridden with `set_transition_cost`,
+/// `block_transition` or `block_state`. Only the states affected by a change
+/// are expanded again by the next `compute_path`.
+pub struct LpaStar<'a, S: Space, H> {
+    space: &'a S,
+    heuristic: &'a H,
+    records: Vec<Record<S::State>>,
+    indices: HashMap<S::State, usize>,
+    queue: BinaryHeap<Reverse<(Key, usize)>>,
+    overrides: HashMap<(usize, usize), usize>,
+    blocked: HashSet<usize>,
+    goals: Vec<usize>,
+    goal_g: usize,
+    start: usize,
+    generated: usize,
+    expanded: usize,
+}
+
+impl<'a, S, H> LpaStar<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    fn new(space: &'a S, heuristic: &'a H) -> Self {
+        let mut search = Self {
+            space,
+            heuristic,
+            records: Vec::new(),
+            indices: HashMap::new(),
+            queue: BinaryHeap::new(),
+            overrides: HashMap::new(),
+            blocked: HashSet::new(),
+            goals: Vec::new(),
+            goal_g: INFINITY,
+            start: 0,
+            generated: 0,
+            expanded: 0,
+        };
+        search.start = search.index_of(space.initial_state());
+        search.records[search.start].rhs = 0;
+        search.enqueue(search.start);
+        search
+    }
+
+    /// Find a cheapest path under the current transition costs, reusing the
+    /// work of the previous calls. `expanded` and `generated` only count the
+    /// work done by this call.
+    pub fn compute_path(&mut self) -> Option<SearchResult<S::State>> {
+        self.generated = 0;
+        self.expanded = 0;
+        loop {
+            // every goal has a free transition to a virtual sink whose cost is
+            // the one of the best path
+            let sink_rhs = self.goal_rhs();
+            let sink_key = (self.goal_g.min(sink_rhs), self.goal_g.min(sink_rhs));
+            let top_key = self.top_key();
+            // on ties the queued state goes first, it may be an outdated goal
+            if top_key == (INFINITY, INFINITY) || sink_key < top_key {
+                if self.goal_g == sink_rhs {
+                    break;
+                }
+                self.goal_g = sink_rhs;
+                continue;
+            }
+            let Reverse((_, index)) = self.queue.pop().expect("top key is finite");
+            self.records[index].queued = None;
+            self.expanded += 1;
+            let successors = self.successors(index);
+            let record = &mut self.records[index];
+            if record.g > record.rhs {
+                record.g = record.rhs;
+            } else {
+                record.g = INFINITY;
+                self.update(index);
+            }
+            for successor in successors {
+                self.update(successor);
+            }
+        }
+        if self.goal_g == INFINITY {
+            return None;
+        }
+        Some(self.extract_path())
+    }
+
+    /// Override the cost of every action leading from `from` to `to`
+    pub fn set_transition_cost(&mut self, from: &S::State, to: &S::State, cost: usize) {
+        let (from, to) = (self.index_of(from.clone()), self.index_of(to.clone()));
+        self.overrides.insert((from, to), cost);
+        self.update(to);
+    }
+
+    /// Forbid every action leading from `from` to `to`
+    pub fn block_transition(&mut self, from: &S::State, to: &S::State) {
+        self.set_transition_cost(from, to, INFINITY);
+    }
+
+    /// Go back to the costs of the actions leading from `from` to `to`
+    pub fn restore_transition(&mut self, from: &S::State, to: &S::State) {
+        let (from, to) = (self.index_of(from.clone()), self.index_of(to.clone()));
+        if self.overrides.remove(&(from, to)).is_some() {
+            self.update(to);
+        }
+    }
+
+    /// Forbid every transition entering `state`
+    pub fn block_state(&mut self, state: &S::State) {
+        let index = self.index_of(state.clone());
+        if self.blocked.insert(index) {
+            self.update(index);
+        }
+    }
+
+    /// Allow again the transitions entering `state`
+    pub fn unblock_state(&mut self, state: &S::State) {
+        let index = self.index_of(state.clone());
+        if self.blocked.remove(&index) {
+            self.update(index);
+        }
+    }
+
+    fn index_of(&mut self, state: S::State) -> usize {
+        if let Some(&index) = self.indices.get(&state) {
+            return index;
+        }
+        let index = self.records.len();
+        let h = self.heuristic.estimate(&state);
+        if self.space.is_goal(&state) {
+            self.goals.push(index);
+        }
+        self.indices.insert(state.clone(), index);
+        self.records.push(Record {
+            state,
+            g: INFINITY,
+            rhs: INFINITY,
+            h,
+            predecessors: Vec::new(),
+            successors: None,
+            queued: None,
+        });
+        index
+    }
+
+    /// Successors of a state, generated the first time it is expanded. Goals
+    /// are never expanded through.
+    fn successors(&mut self, index: usize) -> Vec<usize> {
+        if let Some(successors) = &self.records[index].successors {
+            return successors.clone();
+        }
+        let mut successors = Vec::new();
+        if !self.space.is_goal(&self.records[index].state) {
+            let state = self.records[index].state.clone();
+            for action in state.get_available_actions() {
+                let successor = self.index_of(state.apply(&action));
+                self.generated += 1;
+                self.records[successor].predecessors.push((index, action));
+                successors.push(successor);
+            }
+        }
+        self.records[index].successors = Some(successors.clone());
+        successors
+    }
+
+    fn cost(&self, from: usize, to: usize, action: &<S::State as State>::Action) -> usize {
+        if self.blocked.contains(&to) {
+            return INFINITY;
+        }
+        match self.overrides.get(&(from, to)) {
+            Some(&cost) => cost,
+            None => action.cost(),
+        }
+    }
+
+    fn update(&mut self, index: usize) {
+        if index != self.start {
+            let record = &self.records[index];
+            let rhs = record
+                .predecessors
+                .iter()
+                .map(|(predecessor, action)| {
+                    let cost = self.cost(*predecessor, index, action);
+                    self.records[*predecessor].g.saturating_add(cost)
+                })
+                .min()
+                .unwrap_or(INFINITY);
+            self.records[index].rhs = rhs;
+        }
+        let record = &self.records[index];
+        if record.g != record.rhs {
+            self.enqueue(index);
+        } else {
+            self.records[index].queued = None;
+        }
+    }
+
+    fn key(&self, index: usize) -> Key {
+        let record = &self.records[index];
+        let best = record.g.min(record.rhs);
+        (best.saturating_add(record.h), best)
+    }
+
+    fn enqueue(&mut self, index: usize) {
+        let key = self.key(index);
+        if self.records[index].queued != Some(key) {
+            self.records[index].queued = Some(key);
+            self.queue.push(Reverse((key, index)));
+        }
+    }
+
+    /// Drop outdated queue entries and return the lowest key still queued
+    fn top_key(&mut self) -> Key {
+        while let Some(Reverse((key, index))) = self.queue.peek() {
+            if self.records[*index].queued == Some(*key) {
+                return *key;
+            }
+            self.queue.pop();
+        }
+        (INFINITY, INFINITY)
+    }
+
+    fn goal_rhs(&self) -> usize {
+        self.goals
+            .iter()
+            .map(|&goal| self.records[goal].g)
+            .min()
+            .unwrap_or(INFINITY)
+    }
+
+    fn extract_path(&self) -> SearchResult<S::State> {
+        let goal = *self
+            .goals
+            .iter()
+            .min_by_key(|&&goal| self.records[goal].g)
+            .expect("a goal has been reached");
+        let mut path = Vec::new();
+        let mut current = goal;
+        while current != self.start && path.len() < self.records.len() {
+            let (predecessor, action) = self.records[current]
+                .predecessors
+                .iter()
+                .min_by_key(|(predecessor, action)| {
+                    let cost = self.cost(*predecessor, current, action);
+                    self.records[*predecessor].g.saturating_add(cost)
+                })
+                .expect("states with a finite cost have a predecessor");
+            path.push(action.clone());
+            current = *predecessor;
+        }
+        path.reverse();
+        SearchResult::from_path(
+            self.records[goal].state.clone(),
+            path,
+            self.generated,
+            self.expanded,
+        )
+    }
+}
@@ -0,0 +1,61 @@
+//! Informed search algorithms:
+//! - A*
+
+use crate::{
+    frontiers::PriorityFrontier, output::SearchResult, CostAction, Heuristic, Node, Space, State,
+};
+use std::collections::HashMap;
+
+pub trait AStarSearch<S: Space> {
+    /// Optimal as long as `heuristic` never overestimates the remaining cost
+    fn astar_search<H>(&self, heuristic: &H) -> Option<SearchResult<S::State>>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> AStarSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn astar_search<H>(&self, heuristic: &H) -> Option<SearchResult<S::State>>
+    where
+        H: Heuristic<S::State>,
+    {
+        let mut generated: usize = 0;
+        let mut expanded: usize = 0;
+        let mut best_g = HashMap::new();
+        let mut frontier = PriorityFrontier::new();
+        let initial_state = self.initial_state();
+        let h = heuristic.estimate(&initial_state);
+        best_g.insert(initial_state.clone(), 0);
+        frontier.push(Node::new(initial_state), (h, h, 0));
+        while let Some((node, (_, _, g))) = frontier.pop() {
+            let state = node.state();
+            if best_g.get(state).is_some_and(|&best| best < g) {
+                continue;
+            }
+            if self.is_goal(state) {
+                return Some(SearchResult::new(node, generated, expanded));
+            }
+            expanded += 1;
+            for action in state.get_available_actions() {
+                let child = node.apply(&action);
+                let child_g = g + action.cost();
+                generated += 1;
+                if best_g
+                    .get(child.state())
+                    .is_some_and(|&best| best <= child_g)
+                {
+                    continue;
+                }
+                best_g.insert(child.state().clone(), child_g);
+                let h = heuristic.estimate(child.state());
+                // ties on f are broken towards the node closer to a goal
+                frontier.push(child, (child_g.saturating_add(h), h, child_g));
+            }
+        }
+        None
+    }
+}
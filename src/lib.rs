@@ -1,11 +1,76 @@
+mod adversarial;
 mod algos;
+mod anytime;
+mod csp;
 mod dup_protection;
 mod frontiers;
+mod genetic;
+mod heuristic_check;
+mod incremental;
+mod informed;
+mod limits;
+mod local;
+mod mdp;
+mod memory_bounded;
+mod multi_objective;
+mod nondeterministic;
 mod output;
+mod pattern_database;
+mod planning;
+mod plans;
+mod rng;
+mod sat;
 
 use std::hash::Hash;
 
+pub use adversarial::{
+    AlphaBeta, AlphaBetaConfig, AlphaBetaEngine, AlphaBetaSearch, BackpropagationPolicy,
+    Evaluation, ExpansionPolicy, ExpectimaxSearch, Game, MaxBackup, Mcts, MctsConfig, MeanBackup,
+    MinimaxSearch, MonteCarloTreeSearch, NodeStats, RandomExpansion, RandomRollout,
+    RolloutPolicy, SelectionPolicy, Star, StarMinimaxSearch, StochasticGame, Ucb1,
+};
 pub use algos::{BreadthFirstSearch, DepthFirstSearch};
+pub use anytime::{AnytimeAStar, AnytimeConfig, AnytimeSearch};
+pub use csp::{
+    AllDifferent, AllDifferentBy, ArcConsistency, Assignment, BacktrackingConfig,
+    BacktrackingSearch, BinaryConstraint, ConflictCounter, Constraint, Csp, Domains, Inference,
+    MinConflicts, MinConflictsConfig, ValueOrdering, Var, VariableOrdering,
+};
+pub use dup_protection::dominates;
+pub use genetic::{
+    evolve, Crossover, Evolution, GeneticConfig, GeneticSearch, Genome, Selection, StateGenome,
+};
+pub use heuristic_check::{HeuristicCheck, HeuristicCheckConfig};
+pub use incremental::{IncrementalSearch, LpaStar};
+pub use informed::AStarSearch;
+pub use limits::SearchLimits;
+pub use local::{
+    ActionTabu, AnnealingConfig, Aspiration, ClimbingStrategy, CoolingSchedule,
+    ExponentialCooling, HillClimbing, HillClimbingConfig, LateAcceptanceConfig,
+    LateAcceptanceHillClimbing, LinearCooling, LogarithmicCooling, Objective,
+    SimulatedAnnealing, StateTabu, TabuAttribute, TabuConfig, TabuSearch,
+};
+pub use mdp::{
+    LabelledRtdp, LaoStar, Mdp, MdpConfig, PolicyIteration, RtdpConfig, ValueIteration,
+};
+pub use memory_bounded::{
+    IterativeDeepeningAStar, MemoryBoundedAStar, RecursiveBestFirstSearch,
+};
+pub use multi_objective::ParetoSearch;
+pub use nondeterministic::{AndOrSearch, NondeterministicSpace};
+pub use output::{
+    AnytimeSolution, ConditionalPlan, CspResult, GameDecision, HeuristicReport, HeuristicViolation,
+    LocalSearchResult, MdpSolution, MemoryBoundedSolution, ParetoSolution, PlanEdit, SatResult,
+    SatStats, SearchResult, ShortenedPlan, SolverStats,
+};
+pub use pattern_database::{AdditivePdb, PatternDatabase};
+pub use planning::{
+    EnforcedHillClimbing, Fact, FfHeuristic, GroundAction, HAdd, HMax, LandmarkCount, Operator,
+    PddlError, StripsState, StripsTask,
+};
+pub use plans::{replay, validate_plan, PlanError, PlanShortening, Replay, ShortcutConfig};
+pub use rng::Rng;
+pub use sat::{BoolVar, CdclConfig, CdclSolver, Cnf, CspEncoding, DimacsError, Lit, ToCnf};
 
 // ================================================================================
 // Traits to be implemented by the user to define the search problem
@@ -18,6 +83,12 @@ pub trait CostAction: Action {
     fn cost(&self) -> usize;
 }
 
+/// Action with several costs that can't be folded into one (e.g. time and
+/// energy). Every action of a space must report the same number of costs.
+pub trait MultiCostAction: Action {
+    fn costs(&self) -> Vec<usize>;
+}
+
 pub trait State: Clone + Eq + Hash {
     type Action: Action;
     fn get_available_actions(&self) -> Vec<Self::Action>;
@@ -32,6 +103,20 @@ pub trait Space {
     fn is_goal(&self, state: &Self::State) -> bool;
 }
 
+/// Estimate of the cost still needed to reach a goal from a state
+pub trait Heuristic<S: State> {
+    fn estimate(&self, state: &S) -> usize;
+}
+
+impl<S: State, F> Heuristic<S> for F
+where
+    F: Fn(&S) -> usize,
+{
+    fn estimate(&self, state: &S) -> usize {
+        self(state)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Node<S: State> {
     state: S,
@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+/// Bounds on the effort a search may spend before giving up
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchLimits {
+    pub max_expanded: Option<usize>,
+    pub time_budget: Option<Duration>,
+}
+
+impl SearchLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_expanded(mut self, max_expanded: usize) -> Self {
+        self.max_expanded = Some(max_expanded);
+        self
+    }
+
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Start measuring the time budget from now
+    pub(crate) fn start(&self) -> LimitTracker {
+        LimitTracker {
+            limits: *self,
+            started: Instant::now(),
+        }
+    }
+}
+
+pub(crate) struct LimitTracker {
+    limits: SearchLimits,
+    started: Instant,
+}
+
+impl LimitTracker {
+    pub fn exceeded(&self, expanded: usize) -> bool {
+        self.limits.max_expanded.is_some_and(|max| expanded >= max)
+            || self
+                .limits
+                .time_budget
+                .is_some_and(|budget| self.started.elapsed() >= budget)
+    }
+}
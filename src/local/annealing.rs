@@ -0,0 +1,143 @@
+use super::{reached, Objective};
+use crate::{output::LocalSearchResult, rng::Rng, Space, State};
+
+/// Temperature of simulated annealing at every step; the search stops once it
+/// reaches zero
+pub trait CoolingSchedule {
+    fn temperature(&self, step: usize) -> f64;
+}
+
+impl<F> CoolingSchedule for F
+where
+    F: Fn(usize) -> f64,
+{
+    fn temperature(&self, step: usize) -> f64 {
+        self(step)
+    }
+}
+
+/// `initial * factor^step`
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialCooling {
+    pub initial: f64,
+    pub factor: f64,
+}
+
+impl CoolingSchedule for ExponentialCooling {
+    fn temperature(&self, step: usize) -> f64 {
+        self.initial * self.factor.powf(step as f64)
+    }
+}
+
+/// Falls from `initial` to zero in `steps` steps
+#[derive(Debug, Clone, Copy)]
+pub struct LinearCooling {
+    pub initial: f64,
+    pub steps: usize,
+}
+
+impl CoolingSchedule for LinearCooling {
+    fn temperature(&self, step: usize) -> f64 {
+        self.initial * (1.0 - step as f64 / self.steps.max(1) as f64).max(0.0)
+    }
+}
+
+/// `initial / ln(step + e)`, slow but with convergence guarantees
+#[derive(Debug, Clone, Copy)]
+pub struct LogarithmicCooling {
+    pub initial: f64,
+}
+
+impl CoolingSchedule for LogarithmicCooling {
+    fn temperature(&self, step: usize) -> f64 {
+        self.initial / (step as f64 + std::f64::consts::E).ln()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnnealingConfig {
+    pub max_steps: usize,
+    /// Stop as soon as a state at least this good is found
+    pub target: Option<f64>,
+    pub seed: u64,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        Self {
+            max_steps: 100_000,
+            target: None,
+            seed: 0,
+        }
+    }
+}
+
+pub trait SimulatedAnnealing<S: Space> {
+    /// Move to random neighbours, accepting worse ones with probability
+    /// `exp(-delta / temperature)`. Returns the best state visited.
+    fn simulated_annealing<O, C>(
+        &self,
+        objective: &O,
+        schedule: &C,
+        config: AnnealingConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        C: CoolingSchedule;
+}
+
+impl<S> SimulatedAnnealing<S> for S
+where
+    S: Space,
+    S::State: State,
+{
+    fn simulated_annealing<O, C>(
+        &self,
+        objective: &O,
+        schedule: &C,
+        config: AnnealingConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        C: CoolingSchedule,
+    {
+        let mut rng = Rng::seed_from_u64(config.seed);
+        let mut current = self.initial_state();
+        let mut value = objective.value(&current);
+        let mut best = (current.clone(), value);
+        let mut evaluations = 1;
+        let mut steps = 0;
+        let mut trajectory = vec![value];
+        while steps < config.max_steps && !reached(config.target, best.1) {
+            let temperature = schedule.temperature(steps);
+            if temperature <= 0.0 {
+                break;
+            }
+            let actions = current.get_available_actions();
+            let Some(action) = rng.choose(&actions) else {
+                break;
+            };
+            let neighbour = current.apply(action);
+            let neighbour_value = objective.value(&neighbour);
+            evaluations += 1;
+            steps += 1;
+            let delta = neighbour_value - value;
+            if delta <= 0.0 || rng.chance((-delta / temperature).exp()) {
+                current = neighbour;
+                value = neighbour_value;
+                if value < best.1 {
+                    best = (current.clone(), value);
+                }
+            }
+            trajectory.push(best.1);
+        }
+        LocalSearchResult {
+            state: best.0,
+            value: best.1,
+            steps,
+            evaluations,
+            restarts: 0,
+            trajectory,
+        }
+    }
+}
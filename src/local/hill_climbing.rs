@@ -0,0 +1,189 @@
+use super::{reached, Objective};
+use crate::{output::LocalSearchResult, rng::Rng, Space, State};
+
+/// How the next neighbour is picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClimbingStrategy {
+    /// Move to the best neighbour
+    SteepestAscent,
+    /// Move to the first improving neighbour, in random order
+    FirstChoice,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HillClimbingConfig {
+    pub strategy: ClimbingStrategy,
+    /// Moves allowed per climb
+    pub max_steps: usize,
+    /// Consecutive moves to equally good neighbours allowed on plateaus
+    pub sideways_moves: usize,
+    /// Stop as soon as a state at least this good is found
+    pub target: Option<f64>,
+    pub seed: u64,
+}
+
+impl Default for HillClimbingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ClimbingStrategy::SteepestAscent,
+            max_steps: 10_000,
+            sideways_moves: 0,
+            target: None,
+            seed: 0,
+        }
+    }
+}
+
+pub trait HillClimbing<S: Space> {
+    /// Climb from the initial state until no neighbour is better
+    fn hill_climbing<O>(
+        &self,
+        objective: &O,
+        config: HillClimbingConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>;
+
+    /// Climb from the initial state, then from up to `restarts` random states
+    /// drawn with `random_state`, keeping the best local optimum
+    fn random_restart_hill_climbing<O, R>(
+        &self,
+        objective: &O,
+        config: HillClimbingConfig,
+        restarts: usize,
+        random_state: R,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        R: FnMut(&mut Rng) -> S::State;
+}
+
+impl<S> HillClimbing<S> for S
+where
+    S: Space,
+    S::State: State,
+{
+    fn hill_climbing<O>(
+        &self,
+        objective: &O,
+        config: HillClimbingConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+    {
+        let mut rng = Rng::seed_from_u64(config.seed);
+        climb(self.initial_state(), objective, &config, &mut rng)
+    }
+
+    fn random_restart_hill_climbing<O, R>(
+        &self,
+        objective: &O,
+        config: HillClimbingConfig,
+        restarts: usize,
+        mut random_state: R,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        R: FnMut(&mut Rng) -> S::State,
+    {
+        let mut rng = Rng::seed_from_u64(config.seed);
+        let mut best = climb(self.initial_state(), objective, &config, &mut rng);
+        for restart in 1..=restarts {
+            if reached(config.target, best.value) {
+                break;
+            }
+            let start = random_state(&mut rng);
+            let mut result = climb(start, objective, &config, &mut rng);
+            let steps = best.steps + result.steps;
+            let evaluations = best.evaluations + result.evaluations;
+            let mut trajectory = std::mem::take(&mut best.trajectory);
+            trajectory.extend(result.trajectory.iter().map(|value| value.min(best.value)));
+            if result.value < best.value {
+                std::mem::swap(&mut best, &mut result);
+            }
+            best.steps = steps;
+            best.evaluations = evaluations;
+            best.restarts = restart;
+            best.trajectory = trajectory;
+        }
+        best
+    }
+}
+
+fn climb<S, O>(
+    start: S,
+    objective: &O,
+    config: &HillClimbingConfig,
+    rng: &mut Rng,
+) -> LocalSearchResult<S>
+where
+    S: State,
+    O: Objective<S>,
+{
+    let mut value = objective.value(&start);
+    let mut current = start;
+    let mut evaluations = 1;
+    let mut steps = 0;
+    let mut sideways = 0;
+    let mut trajectory = vec![value];
+    while steps < config.max_steps && !reached(config.target, value) {
+        let mut actions = current.get_available_actions();
+        let mut next = None;
+        match config.strategy {
+            ClimbingStrategy::SteepestAscent => {
+                for action in &actions {
+                    let neighbour = current.apply(action);
+                    let neighbour_value = objective.value(&neighbour);
+                    evaluations += 1;
+                    if next
+                        .as_ref()
+                        .is_none_or(|(_, best_value)| neighbour_value < *best_value)
+                    {
+                        next = Some((neighbour, neighbour_value));
+                    }
+                }
+            }
+            ClimbingStrategy::FirstChoice => {
+                rng.shuffle(&mut actions);
+                let mut plateau = None;
+                for action in &actions {
+                    let neighbour = current.apply(action);
+                    let neighbour_value = objective.value(&neighbour);
+                    evaluations += 1;
+                    if neighbour_value < value {
+                        next = Some((neighbour, neighbour_value));
+                        break;
+                    }
+                    if neighbour_value == value && plateau.is_none() {
+                        plateau = Some((neighbour, neighbour_value));
+                    }
+                }
+                next = next.or(plateau);
+            }
+        }
+        match next {
+            Some((neighbour, neighbour_value)) if neighbour_value < value => {
+                sideways = 0;
+                current = neighbour;
+                value = neighbour_value;
+            }
+            Some((neighbour, neighbour_value))
+                if neighbour_value == value && sideways < config.sideways_moves =>
+            {
+                sideways += 1;
+                current = neighbour;
+            }
+            _ => break,
+        }
+        steps += 1;
+        trajectory.push(value);
+    }
+    LocalSearchResult {
+        state: current,
+        value,
+        steps,
+        evaluations,
+        restarts: 0,
+        trajectory,
+    }
+}
@@ -0,0 +1,87 @@
+use super::{reached, Objective};
+use crate::{output::LocalSearchResult, rng::Rng, Space, State};
+
+#[derive(Debug, Clone, Copy)]
+pub struct LateAcceptanceConfig {
+    /// Number of past values a candidate is compared against
+    pub history_length: usize,
+    pub max_steps: usize,
+    /// Stop as soon as a state at least this good is found
+    pub target: Option<f64>,
+    pub seed: u64,
+}
+
+impl Default for LateAcceptanceConfig {
+    fn default() -> Self {
+        Self {
+            history_length: 50,
+            max_steps: 100_000,
+            target: None,
+            seed: 0,
+        }
+    }
+}
+
+pub trait LateAcceptanceHillClimbing<S: Space> {
+    /// Move to random neighbours no worse than the current state or than the
+    /// state of `history_length` steps ago. Returns the best state visited.
+    fn late_acceptance_hill_climbing<O>(
+        &self,
+        objective: &O,
+        config: LateAcceptanceConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>;
+}
+
+impl<S> LateAcceptanceHillClimbing<S> for S
+where
+    S: Space,
+    S::State: State,
+{
+    fn late_acceptance_hill_climbing<O>(
+        &self,
+        objective: &O,
+        config: LateAcceptanceConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+    {
+        let mut rng = Rng::seed_from_u64(config.seed);
+        let mut current = self.initial_state();
+        let mut value = objective.value(&current);
+        let mut best = (current.clone(), value);
+        let mut history = vec![value; config.history_length.max(1)];
+        let mut evaluations = 1;
+        let mut steps = 0;
+        let mut trajectory = vec![value];
+        while steps < config.max_steps && !reached(config.target, best.1) {
+            let actions = current.get_available_actions();
+            let Some(action) = rng.choose(&actions) else {
+                break;
+            };
+            let neighbour = current.apply(action);
+            let neighbour_value = objective.value(&neighbour);
+            evaluations += 1;
+            let slot = steps % history.len();
+            if neighbour_value <= history[slot] || neighbour_value <= value {
+                current = neighbour;
+                value = neighbour_value;
+                if value < best.1 {
+                    best = (current.clone(), value);
+                }
+            }
+            history[slot] = value;
+            steps += 1;
+            trajectory.push(best.1);
+        }
+        LocalSearchResult {
+            state: best.0,
+            value: best.1,
+            steps,
+            evaluations,
+            restarts: 0,
+            trajectory,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Local search over complete states, where only the final state matters:
+//! - hill climbing (steepest ascent, first choice, random restarts)
+//! - simulated annealing
+//! - tabu search
+//! - late acceptance hill climbing
+
+mod annealing;
+mod hill_climbing;
+mod late_acceptance;
+mod tabu;
+
+pub use annealing::{
+    AnnealingConfig, CoolingSchedule, ExponentialCooling, LinearCooling, LogarithmicCooling,
+    SimulatedAnnealing,
+};
+pub use hill_climbing::{ClimbingStrategy, HillClimbing, HillClimbingConfig};
+pub use late_acceptance::{LateAcceptanceConfig, LateAcceptanceHillClimbing};
+pub use tabu::{ActionTabu, Aspiration, StateTabu, TabuAttribute, TabuConfig, TabuSearch};
+
+use crate::State;
+
+/// Value of a state, the lower the better
+pub trait Objective<S: State> {
+    fn value(&self, state: &S) -> f64;
+}
+
+impl<S: State, F> Objective<S> for F
+where
+    F: Fn(&S) -> f64,
+{
+    fn value(&self, state: &S) -> f64 {
+        self(state)
+    }
+}
+
+/// Whether `value` is good enough to stop searching
+fn reached(target: Option<f64>, value: f64) -> bool {
+    target.is_some_and(|target| value <= target)
+}
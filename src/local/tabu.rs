@@ -0,0 +1,168 @@
+use super::{reached, Objective};
+use crate::{output::LocalSearchResult, Space, State};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// What a move makes tabu. A candidate move is tabu while its key matches the
+/// key of one of the moves made during the last `tenure` steps.
+pub trait TabuAttribute<S: State> {
+    type Key: Eq + Hash;
+    fn key(&self, from: &S, action: &S::Action, to: &S) -> Self::Key;
+}
+
+impl<S: State, K, F> TabuAttribute<S> for F
+where
+    K: Eq + Hash,
+    F: Fn(&S, &S::Action, &S) -> K,
+{
+    type Key = K;
+
+    fn key(&self, from: &S, action: &S::Action, to: &S) -> K {
+        self(from, action, to)
+    }
+}
+
+/// Actions recently applied are tabu
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActionTabu;
+
+impl<S> TabuAttribute<S> for ActionTabu
+where
+    S: State,
+    S::Action: Eq + Hash,
+{
+    type Key = S::Action;
+
+    fn key(&self, _: &S, action: &S::Action, _: &S) -> S::Action {
+        action.clone()
+    }
+}
+
+/// Moves leading to states sharing an attribute with recently reached states
+/// are tabu
+#[derive(Debug, Clone, Copy)]
+pub struct StateTabu<F>(pub F);
+
+impl<S, K, F> TabuAttribute<S> for StateTabu<F>
+where
+    S: State,
+    K: Eq + Hash,
+    F: Fn(&S) -> K,
+{
+    type Key = K;
+
+    fn key(&self, _: &S, _: &S::Action, to: &S) -> K {
+        (self.0)(to)
+    }
+}
+
+/// When a tabu move may be made anyway
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aspiration {
+    /// Tabu moves are never made
+    Never,
+    /// Tabu moves leading to a state better than any seen so far are allowed
+    ImprovesBest,
+    /// Tabu moves leading to a state at least this good are allowed
+    Below(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TabuConfig {
+    /// Steps a move stays tabu for
+    pub tenure: usize,
+    pub max_steps: usize,
+    pub aspiration: Aspiration,
+    /// Stop as soon as a state at least this good is found
+    pub target: Option<f64>,
+}
+
+impl Default for TabuConfig {
+    fn default() -> Self {
+        Self {
+            tenure: 7,
+            max_steps: 10_000,
+            aspiration: Aspiration::ImprovesBest,
+            target: None,
+        }
+    }
+}
+
+pub trait TabuSearch<S: Space> {
+    /// Move to the best admissible neighbour at every step, even when it is
+    /// worse than the current state. Returns the best state visited.
+    fn tabu_search<O, T>(
+        &self,
+        objective: &O,
+        attribute: &T,
+        config: TabuConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        T: TabuAttribute<S::State>;
+}
+
+impl<S> TabuSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+{
+    fn tabu_search<O, T>(
+        &self,
+        objective: &O,
+        attribute: &T,
+        config: TabuConfig,
+    ) -> LocalSearchResult<S::State>
+    where
+        O: Objective<S::State>,
+        T: TabuAttribute<S::State>,
+    {
+        let mut current = self.initial_state();
+        let mut best = (current.clone(), objective.value(&current));
+        let mut tabu_until: HashMap<T::Key, usize> = HashMap::new();
+        let mut evaluations = 1;
+        let mut steps = 0;
+        let mut trajectory = vec![best.1];
+        while steps < config.max_steps && !reached(config.target, best.1) {
+            let mut next = None;
+            for action in current.get_available_actions() {
+                let neighbour = current.apply(&action);
+                let value = objective.value(&neighbour);
+                evaluations += 1;
+                let key = attribute.key(&current, &action, &neighbour);
+                let is_tabu = tabu_until.get(&key).is_some_and(|&until| until > steps);
+                let aspired = match config.aspiration {
+                    Aspiration::Never => false,
+                    Aspiration::ImprovesBest => value < best.1,
+                    Aspiration::Below(threshold) => value <= threshold,
+                };
+                if (!is_tabu || aspired)
+                    && next
+                        .as_ref()
+                        .is_none_or(|(_, _, next_value)| value < *next_value)
+                {
+                    next = Some((neighbour, key, value));
+                }
+            }
+            let Some((neighbour, key, value)) = next else {
+                // every move is tabu
+                break;
+            };
+            steps += 1;
+            tabu_until.insert(key, steps + config.tenure);
+            current = neighbour;
+            if value < best.1 {
+                best = (current.clone(), value);
+            }
+            trajectory.push(best.1);
+        }
+        LocalSearchResult {
+            state: best.0,
+            value: best.1,
+            steps,
+            evaluations,
+            restarts: 0,
+            trajectory,
+        }
+    }
+}
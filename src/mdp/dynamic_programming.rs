@@ -0,0 +1,206 @@
+use super::{Mdp, Model};
+use crate::output::MdpSolution;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MdpConfig {
+    /// Values are settled once a sweep over the states changes none of them
+    /// by more than this
+    pub epsilon: f64,
+    /// Sweeps of value iteration, or improvement steps and evaluation sweeps
+    /// of each step for policy iteration, before giving up
+    pub max_iterations: usize,
+}
+
+impl Default for MdpConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: 1e-6,
+            max_iterations: 10_000,
+        }
+    }
+}
+
+pub trait ValueIteration<M: Mdp> {
+    /// Give every state reachable from the initial state the value of its
+    /// best action, over and over until the values settle
+    fn value_iteration(&self, config: MdpConfig) -> MdpSolution<M::State>;
+}
+
+impl<M: Mdp> ValueIteration<M> for M {
+    fn value_iteration(&self, config: MdpConfig) -> MdpSolution<M::State> {
+        let model = Model::explore(self);
+        let mut values = vec![0.0; model.states.len()];
+        let mut solution = Progress::default();
+        while solution.iterations < config.max_iterations {
+            solution.iterations += 1;
+            let mut residual: f64 = 0.0;
+            let mut next = vec![0.0; values.len()];
+            for (state, value) in next.iter_mut().enumerate() {
+                if let Some((_, best)) = model.greedy(state, &values) {
+                    solution.backups += 1;
+                    residual = residual.max((best - values[state]).abs());
+                    *value = best;
+                }
+            }
+            values = next;
+            if residual <= config.epsilon {
+                solution.converged = true;
+                break;
+            }
+        }
+        let policy = greedy_policy(&model, &values);
+        solution.finish(&model, policy, values)
+    }
+}
+
+pub trait PolicyIteration<M: Mdp> {
+    /// Evaluate the policy until its values settle, then switch every state
+    /// to a better action if there is one, until no state switches. Starts
+    /// with the first action of every state.
+    fn policy_iteration(&self, config: MdpConfig) -> MdpSolution<M::State>;
+
+    /// Policy iteration evaluating each policy with only `sweeps` sweeps
+    /// over the states, until the greedy policy changes no value by more than
+    /// the threshold. Without sweeps this is value iteration.
+    fn modified_policy_iteration(&self, sweeps: usize, config: MdpConfig) -> MdpSolution<M::State>;
+}
+
+impl<M: Mdp> PolicyIteration<M> for M {
+    fn policy_iteration(&self, config: MdpConfig) -> MdpSolution<M::State> {
+        let model = Model::explore(self);
+        let mut values = vec![0.0; model.states.len()];
+        let mut policy: Vec<Option<usize>> = model
+            .actions
+            .iter()
+            .map(|actions| (!actions.is_empty()).then_some(0))
+            .collect();
+        let mut solution = Progress::default();
+        while solution.iterations < config.max_iterations {
+            solution.iterations += 1;
+            let mut settled = false;
+            for _ in 0..config.max_iterations {
+                let residual = evaluate(&model, &policy, &mut values, &mut solution.backups);
+                if residual <= config.epsilon {
+                    settled = true;
+                    break;
+                }
+            }
+
+            let mut stable = true;
+            for (state, action) in policy.iter_mut().enumerate() {
+                let Some(current) = *action else {
+                    continue;
+                };
+                let (best, value) = model
+                    .greedy(state, &values)
+                    .expect("non-terminal states have actions");
+                // switching between equally good actions could go on forever
+                if value > model.q_value(&model.actions[state][current].1, &values) + config.epsilon
+                {
+                    *action = Some(best);
+                    stable = false;
+                }
+            }
+            if stable {
+                solution.converged = settled;
+                break;
+            }
+        }
+        solution.finish(&model, policy, values)
+    }
+
+    fn modified_policy_iteration(&self, sweeps: usize, config: MdpConfig) -> MdpSolution<M::State> {
+        let model = Model::explore(self);
+        let mut values = vec![0.0; model.states.len()];
+        let mut solution = Progress::default();
+        let mut policy = vec![None; model.states.len()];
+        while solution.iterations < config.max_iterations {
+            solution.iterations += 1;
+            // improvement, whose values are those of a first sweep
+            let mut residual: f64 = 0.0;
+            let mut next = vec![0.0; values.len()];
+            for (state, value) in next.iter_mut().enumerate() {
+                if let Some((action, best)) = model.greedy(state, &values) {
+                    solution.backups += 1;
+                    policy[state] = Some(action);
+                    residual = residual.max((best - values[state]).abs());
+                    *value = best;
+                }
+            }
+            values = next;
+            if residual <= config.epsilon {
+                solution.converged = true;
+                break;
+            }
+            for _ in 0..sweeps {
+                evaluate(&model, &policy, &mut values, &mut solution.backups);
+            }
+        }
+        let policy = greedy_policy(&model, &values);
+        solution.finish(&model, policy, values)
+    }
+}
+
+/// Counters of a run
+#[derive(Default)]
+struct Progress {
+    iterations: usize,
+    backups: usize,
+    converged: bool,
+}
+
+impl Progress {
+    fn finish<M: Mdp>(
+        self,
+        model: &Model<M>,
+        policy: Vec<Option<usize>>,
+        values: Vec<f64>,
+    ) -> MdpSolution<M::State> {
+        MdpSolution {
+            policy: policy
+                .into_iter()
+                .enumerate()
+                .filter_map(|(state, action)| {
+                    let action = model.actions[state][action?].0.clone();
+                    Some((model.states[state].clone(), action))
+                })
+                .collect(),
+            values: model.states.iter().cloned().zip(values).collect(),
+            iterations: self.iterations,
+            backups: self.backups,
+            converged: self.converged,
+        }
+    }
+}
+
+/// Update the values of the states with the actions of `policy`, returning
+/// the largest change
+fn evaluate<M: Mdp>(
+    model: &Model<M>,
+    policy: &[Option<usize>],
+    values: &mut Vec<f64>,
+    backups: &mut usize,
+) -> f64 {
+    let mut residual: f64 = 0.0;
+    let next: Vec<f64> = policy
+        .iter()
+        .enumerate()
+        .map(|(state, action)| match action {
+            Some(action) => {
+                *backups += 1;
+                let value = model.q_value(&model.actions[state][*action].1, values);
+                residual = residual.max((value - values[state]).abs());
+                value
+            }
+            None => 0.0,
+        })
+        .collect();
+    *values = next;
+    residual
+}
+
+fn greedy_policy<M: Mdp>(model: &Model<M>, values: &[f64]) -> Vec<Option<usize>> {
+    (0..model.states.len())
+        .map(|state| model.greedy(state, values).map(|(action, _)| action))
+        .collect()
+}
@@ -0,0 +1,352 @@
+use super::{Choice, Mdp, MdpConfig};
+use crate::{output::MdpSolution, rng::Rng, Heuristic, State};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RtdpConfig {
+    /// A state is solved once the values of the states reachable with the
+    /// greedy policy would change by at most this
+    pub epsilon: f64,
+    /// Trials from the initial state before giving up
+    pub max_trials: usize,
+    /// States of a trial before it is cut short and its states checked, as
+    /// if it had met a solved state
+    pub max_trial_length: usize,
+    pub seed: u64,
+}
+
+impl Default for RtdpConfig {
+    fn default() -> Self {
+        Self {
+            epsilon: 1e-6,
+            max_trials: 100_000,
+            max_trial_length: 10_000,
+            seed: 0,
+        }
+    }
+}
+
+pub trait LaoStar<M: Mdp> {
+    /// Grow the greedy policy from the initial state, expanding the states it
+    /// reaches for the first time and backing up its states after each
+    /// depth-first pass over them (improved LAO*). `max_iterations` limits
+    /// the passes.
+    ///
+    /// For stochastic shortest-path problems: the rewards are negated costs
+    /// and the terminal states the goals. The heuristic estimates the cost
+    /// to reach a goal; the policy is optimal if it never overestimates.
+    /// States that aren't terminal but have no actions are dead ends, worth
+    /// nothing more as in value iteration.
+    fn lao_star<H>(&self, heuristic: &H, config: MdpConfig) -> MdpSolution<M::State>
+    where
+        H: Heuristic<M::State>;
+}
+
+pub trait LabelledRtdp<M: Mdp> {
+    /// Run trials from the initial state, following the greedy policy and
+    /// drawing the outcomes of the actions, and backing up the states met.
+    /// States whose greedy policy has converged are labelled solved and end
+    /// the trials, until the initial state is solved.
+    ///
+    /// Same conditions on the problem and the heuristic as `lao_star`.
+    fn labelled_rtdp<H>(&self, heuristic: &H, config: RtdpConfig) -> MdpSolution<M::State>
+    where
+        H: Heuristic<M::State>;
+}
+
+impl<M: Mdp> LaoStar<M> for M {
+    fn lao_star<H>(&self, heuristic: &H, config: MdpConfig) -> MdpSolution<M::State>
+    where
+        H: Heuristic<M::State>,
+    {
+        let mut graph = Graph::new(self, heuristic);
+        let root = graph.add(self.initial_state());
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < config.max_iterations && !converged {
+            iterations += 1;
+            let mut expanded = false;
+            let mut residual: f64 = 0.0;
+            let mut visited = HashSet::from([root]);
+            // states of the greedy policy, with their successors left to visit
+            let mut stack: Vec<(usize, Vec<usize>)> = Vec::new();
+            stack.extend(graph.visit(root, &mut expanded, &mut residual));
+            while let Some((state, successors)) = stack.last_mut() {
+                let state = *state;
+                match successors.pop() {
+                    Some(next) => {
+                        if visited.insert(next) {
+                            stack.extend(graph.visit(next, &mut expanded, &mut residual));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        residual = residual.max(graph.backup(state));
+                    }
+                }
+            }
+            converged = !expanded && residual <= config.epsilon;
+        }
+        graph.solution(root, iterations, converged)
+    }
+}
+
+impl<M: Mdp> LabelledRtdp<M> for M {
+    fn labelled_rtdp<H>(&self, heuristic: &H, config: RtdpConfig) -> MdpSolution<M::State>
+    where
+        H: Heuristic<M::State>,
+    {
+        let mut graph = Graph::new(self, heuristic);
+        let root = graph.add(self.initial_state());
+        let mut rng = Rng::seed_from_u64(config.seed);
+        let mut trials = 0;
+        while !graph.solved[root] && trials < config.max_trials {
+            trials += 1;
+            let mut trial = Vec::new();
+            let mut state = root;
+            while !graph.solved[state] && trial.len() < config.max_trial_length {
+                trial.push(state);
+                graph.expand(state);
+                if graph.is_terminal(state) {
+                    break;
+                }
+                graph.backup(state);
+                let (action, _) = graph.greedy(state);
+                state = graph.sample(state, action, &mut rng);
+            }
+            while let Some(state) = trial.pop() {
+                if !graph.check_solved(state, config.epsilon) {
+                    break;
+                }
+            }
+        }
+        let converged = graph.solved[root];
+        graph.solution(root, trials, converged)
+    }
+}
+
+/// States met so far, with their values and, once expanded, their actions
+struct Graph<'a, M: Mdp, H> {
+    mdp: &'a M,
+    heuristic: &'a H,
+    states: Vec<M::State>,
+    index: HashMap<M::State, usize>,
+    values: Vec<f64>,
+    actions: Vec<Option<Vec<Choice<M>>>>,
+    /// Whether the greedy policy has converged from every state (labelled
+    /// RTDP)
+    solved: Vec<bool>,
+    backups: usize,
+}
+
+impl<'a, M: Mdp, H: Heuristic<M::State>> Graph<'a, M, H> {
+    fn new(mdp: &'a M, heuristic: &'a H) -> Self {
+        Self {
+            mdp,
+            heuristic,
+            states: Vec::new(),
+            index: HashMap::new(),
+            values: Vec::new(),
+            actions: Vec::new(),
+            solved: Vec::new(),
+            backups: 0,
+        }
+    }
+
+    /// Index of `state`, valued by the heuristic the first time it is met
+    fn add(&mut self, state: M::State) -> usize {
+        if let Some(&index) = self.index.get(&state) {
+            return index;
+        }
+        let terminal = self.mdp.is_terminal(&state);
+        let value = if terminal {
+            0.0
+        } else {
+            -(self.heuristic.estimate(&state) as f64)
+        };
+        self.index.insert(state.clone(), self.states.len());
+        self.states.push(state);
+        self.values.push(value);
+        self.actions.push(None);
+        self.solved.push(terminal);
+        self.states.len() - 1
+    }
+
+    /// Whether `state` is terminal or, once expanded, a dead end
+    fn is_terminal(&self, state: usize) -> bool {
+        self.solved[state] && self.actions[state].as_ref().is_none_or(Vec::is_empty)
+    }
+
+    /// Generate the actions of `state` and their outcomes. Dead ends are
+    /// worth nothing more and solved.
+    fn expand(&mut self, state: usize) {
+        if self.actions[state].is_some() {
+            return;
+        }
+        let current = self.states[state].clone();
+        let actions: Vec<Choice<M>> = current
+            .get_available_actions()
+            .into_iter()
+            .map(|action| {
+                let outcomes = self
+                    .mdp
+                    .transitions(&current, &action)
+                    .into_iter()
+                    .map(|(next, probability)| {
+                        let reward = self.mdp.reward(&current, &action, &next);
+                        (self.add(next), probability, reward)
+                    })
+                    .collect();
+                (action, outcomes)
+            })
+            .collect();
+        if actions.is_empty() {
+            self.values[state] = 0.0;
+            self.solved[state] = true;
+        }
+        self.actions[state] = Some(actions);
+    }
+
+    /// State of the greedy policy met by a pass of LAO*, with its successors
+    /// to visit before backing it up. Tips are expanded and backed up right
+    /// away, their successors wait for the next pass.
+    fn visit(
+        &mut self,
+        state: usize,
+        expanded: &mut bool,
+        residual: &mut f64,
+    ) -> Option<(usize, Vec<usize>)> {
+        if self.is_terminal(state) {
+            None
+        } else if self.actions[state].is_none() {
+            self.expand(state);
+            *expanded = true;
+            *residual = residual.max(self.backup(state));
+            None
+        } else {
+            let (action, _) = self.greedy(state);
+            Some((state, self.successors(state, action)))
+        }
+    }
+
+    /// Best action of an expanded state that isn't a dead end, and its value
+    fn greedy(&self, state: usize) -> (usize, f64) {
+        let discount = self.mdp.discount();
+        let actions = self.actions[state].as_ref().expect("expanded state");
+        let mut best = (0, f64::NEG_INFINITY);
+        for (action, (_, outcomes)) in actions.iter().enumerate() {
+            let value: f64 = outcomes
+                .iter()
+                .map(|&(next, probability, reward)| {
+                    probability * (reward + discount * self.values[next])
+                })
+                .sum();
+            if value > best.1 {
+                best = (action, value);
+            }
+        }
+        best
+    }
+
+    /// Give an expanded state the value of its best action, returning the
+    /// change
+    fn backup(&mut self, state: usize) -> f64 {
+        if self.is_terminal(state) {
+            return 0.0;
+        }
+        self.backups += 1;
+        let (_, value) = self.greedy(state);
+        let change = (value - self.values[state]).abs();
+        self.values[state] = value;
+        change
+    }
+
+    fn successors(&self, state: usize, action: usize) -> Vec<usize> {
+        let actions = self.actions[state].as_ref().expect("expanded state");
+        actions[action].1.iter().map(|&(next, _, _)| next).collect()
+    }
+
+    fn sample(&self, state: usize, action: usize, rng: &mut Rng) -> usize {
+        let actions = self.actions[state].as_ref().expect("expanded state");
+        let outcomes = &actions[action].1;
+        let mut draw = rng.unit();
+        for &(next, probability, _) in outcomes {
+            if draw < probability {
+                return next;
+            }
+            draw -= probability;
+        }
+        outcomes.last().expect("actions have outcomes").0
+    }
+
+    /// Label `state` and the states reachable from it with the greedy policy
+    /// solved if none of their values would change by more than `epsilon`,
+    /// otherwise back them up
+    fn check_solved(&mut self, state: usize, epsilon: f64) -> bool {
+        let mut consistent = true;
+        let mut open = Vec::new();
+        let mut closed = Vec::new();
+        let mut seen = HashSet::from([state]);
+        if !self.solved[state] {
+            open.push(state);
+        }
+        while let Some(state) = open.pop() {
+            closed.push(state);
+            self.expand(state);
+            if self.is_terminal(state) {
+                continue;
+            }
+            let (action, value) = self.greedy(state);
+            if (value - self.values[state]).abs() > epsilon {
+                consistent = false;
+                continue;
+            }
+            for next in self.successors(state, action) {
+                if !self.solved[next] && seen.insert(next) {
+                    open.push(next);
+                }
+            }
+        }
+        if consistent {
+            for state in closed {
+                self.solved[state] = true;
+            }
+        } else {
+            while let Some(state) = closed.pop() {
+                self.backup(state);
+            }
+        }
+        consistent
+    }
+
+    /// Greedy policy of the states reachable from `root` with it
+    fn solution(self, root: usize, iterations: usize, converged: bool) -> MdpSolution<M::State> {
+        let mut policy = HashMap::new();
+        let mut values = HashMap::new();
+        let mut seen = HashSet::from([root]);
+        let mut stack = vec![root];
+        while let Some(state) = stack.pop() {
+            values.insert(self.states[state].clone(), self.values[state]);
+            let Some(actions) = self.actions[state]
+                .as_ref()
+                .filter(|actions| !actions.is_empty())
+            else {
+                continue;
+            };
+            let (action, _) = self.greedy(state);
+            policy.insert(self.states[state].clone(), actions[action].0.clone());
+            for next in self.successors(state, action) {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        MdpSolution {
+            policy,
+            values,
+            iterations,
+            backups: self.backups,
+            converged,
+        }
+    }
+}
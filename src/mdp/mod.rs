@@ -0,0 +1,143 @@
+//! Markov decision processes, where actions have random outcomes:
+//! - value iteration
+//! - policy iteration and modified policy iteration
+//! - heuristic search from the initial state: LAO* and labelled RTDP
+
+mod dynamic_programming;
+mod heuristic_search;
+
+pub use dynamic_programming::{MdpConfig, PolicyIteration, ValueIteration};
+pub use heuristic_search::{LabelledRtdp, LaoStar, RtdpConfig};
+
+use crate::{
+    dup_protection::StateCacheSet,
+    frontiers::{Frontier, QueueFrontier},
+    Node, State,
+};
+use std::collections::HashMap;
+
+/// Decision process whose actions are the available actions of the states.
+/// `State::apply` is not used: the outcome of an action is drawn from
+/// `transitions`.
+pub trait Mdp {
+    type State: State;
+    fn initial_state(&self) -> Self::State;
+    /// States reached by `action` in `state`, with their probabilities
+    /// summing to one
+    fn transitions(
+        &self,
+        state: &Self::State,
+        action: &<Self::State as State>::Action,
+    ) -> Vec<(Self::State, f64)>;
+    /// Reward of reaching `next` by `action` in `state`
+    fn reward(
+        &self,
+        state: &Self::State,
+        action: &<Self::State as State>::Action,
+        next: &Self::State,
+    ) -> f64;
+    /// Weight of the rewards of the next step relative to this one, in
+    /// `(0, 1]`. With 1, every policy must eventually reach a terminal state.
+    fn discount(&self) -> f64;
+    /// Terminal states are worth nothing more. By default, the states
+    /// without actions.
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        state.get_available_actions().is_empty()
+    }
+}
+
+/// Outcome of an action in the explicit model: next state, probability and
+/// reward
+type Outcome = (usize, f64, f64);
+
+/// Action of a state with its outcomes
+type Choice<M> = (<<M as Mdp>::State as State>::Action, Vec<Outcome>);
+
+/// States reachable from the initial state, numbered in breadth-first order,
+/// with the outcomes of their actions
+struct Model<M: Mdp> {
+    states: Vec<M::State>,
+    /// Actions of every state with their outcomes, none for terminal states
+    actions: Vec<Vec<Choice<M>>>,
+    discount: f64,
+}
+
+impl<M: Mdp> Model<M> {
+    fn explore(mdp: &M) -> Self {
+        let mut frontier = QueueFrontier::new(mdp.initial_state());
+        let mut visited = StateCacheSet::new();
+        let mut states = Vec::new();
+        while let Some(node) = frontier.pop() {
+            let state = node.state();
+            if visited.contains(state) {
+                continue;
+            }
+            visited.insert(state.clone());
+            states.push(state.clone());
+            if mdp.is_terminal(state) {
+                continue;
+            }
+            for action in state.get_available_actions() {
+                for (next, _) in mdp.transitions(state, &action) {
+                    frontier.push(Node::new(next));
+                }
+            }
+        }
+
+        let index: HashMap<M::State, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(i, state)| (state.clone(), i))
+            .collect();
+        let actions = states
+            .iter()
+            .map(|state| {
+                if mdp.is_terminal(state) {
+                    return Vec::new();
+                }
+                state
+                    .get_available_actions()
+                    .into_iter()
+                    .map(|action| {
+                        let outcomes = mdp
+                            .transitions(state, &action)
+                            .into_iter()
+                            .map(|(next, probability)| {
+                                let reward = mdp.reward(state, &action, &next);
+                                (index[&next], probability, reward)
+                            })
+                            .collect();
+                        (action, outcomes)
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            states,
+            actions,
+            discount: mdp.discount(),
+        }
+    }
+
+    /// Expected value of `outcomes` given the values of the states
+    fn q_value(&self, outcomes: &[Outcome], values: &[f64]) -> f64 {
+        outcomes
+            .iter()
+            .map(|&(next, probability, reward)| {
+                probability * (reward + self.discount * values[next])
+            })
+            .sum()
+    }
+
+    /// Best action of `state` and its value, `None` for terminal states
+    fn greedy(&self, state: usize, values: &[f64]) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        for (action, (_, outcomes)) in self.actions[state].iter().enumerate() {
+            let value = self.q_value(outcomes, values);
+            if best.is_none_or(|(_, best)| value > best) {
+                best = Some((action, value));
+            }
+        }
+        best
+    }
+}
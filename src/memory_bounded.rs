@@ -0,0 +1,640 @@
+//! Memory-bounded best-first search algorithms:
+//! - IDA* (iterative deepening A*)
+//! - RBFS (recursive best-first search)
+//! - SMA* (simplified memory-bounded A*)
+
+use crate::{
+    output::{MemoryBoundedSolution, SearchResult},
+    CostAction, Heuristic, Node, Space, State,
+};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+
+const INFINITY: usize = usize::MAX;
+
+pub trait IterativeDeepeningAStar<S: Space> {
+    /// Depth-first searches cut off at an f-value bound, raised after each
+    /// one to the lowest f-value beyond it. Optimal with an admissible
+    /// heuristic, using memory linear in the depth of the solution, but each
+    /// iteration expands again the states of the previous ones.
+    fn ida_star_search<H>(&self, heuristic: &H) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> IterativeDeepeningAStar<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn ida_star_search<H>(&self, heuristic: &H) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>,
+    {
+        let mut ida = Ida {
+            space: self,
+            heuristic,
+            ancestors: Vec::new(),
+            previous_bound: None,
+            peak_nodes: 0,
+            generated: 0,
+            expanded: 0,
+            regenerated: 0,
+        };
+        let initial_state = self.initial_state();
+        let mut bound = heuristic.estimate(&initial_state);
+        while bound < INFINITY {
+            match ida.search(Node::new(initial_state.clone()), 0, bound) {
+                Ok(node) => {
+                    return Some(MemoryBoundedSolution {
+                        result: SearchResult::new(node, ida.generated, ida.expanded),
+                        regenerated: ida.regenerated,
+                        peak_nodes: ida.peak_nodes,
+                    })
+                }
+                Err(next_bound) => {
+                    ida.previous_bound = Some(bound);
+                    bound = next_bound;
+                }
+            }
+        }
+        None
+    }
+}
+
+struct Ida<'a, S: Space, H> {
+    space: &'a S,
+    heuristic: &'a H,
+    ancestors: Vec<S::State>,
+    /// Bound of the last iteration, whose states are expanded again
+    previous_bound: Option<usize>,
+    peak_nodes: usize,
+    generated: usize,
+    expanded: usize,
+    regenerated: usize,
+}
+
+impl<'a, S, H> Ida<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    /// Return the goal node, or the lowest f-value beyond `bound` met
+    fn search(
+        &mut self,
+        node: Node<S::State>,
+        g: usize,
+        bound: usize,
+    ) -> Result<Node<S::State>, usize> {
+        // the node and its ancestors
+        self.peak_nodes = self.peak_nodes.max(self.ancestors.len() + 1);
+        let f = g.saturating_add(self.heuristic.estimate(node.state()));
+        if f > bound {
+            return Err(f);
+        }
+        if self.space.is_goal(node.state()) {
+            return Ok(node);
+        }
+        self.expanded += 1;
+        if self.previous_bound.is_some_and(|previous| f <= previous) {
+            self.regenerated += 1;
+        }
+        self.ancestors.push(node.state().clone());
+        let mut next_bound = INFINITY;
+        let mut outcome = None;
+        for action in node.state().get_available_actions() {
+            let child = node.apply(&action);
+            self.generated += 1;
+            if child.state() == node.state() || self.ancestors.contains(child.state()) {
+                continue;
+            }
+            match self.search(child, g + action.cost(), bound) {
+                Ok(goal) => {
+                    outcome = Some(goal);
+                    break;
+                }
+                Err(f) => next_bound = next_bound.min(f),
+            }
+        }
+        self.ancestors.pop();
+        outcome.ok_or(next_bound)
+    }
+}
+
+pub trait RecursiveBestFirstSearch<S: Space> {
+    /// Optimal with an admissible heuristic, using memory linear in the depth
+    /// of the solution
+    fn rbfs_search<H>(&self, heuristic: &H) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> RecursiveBestFirstSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn rbfs_search<H>(&self, heuristic: &H) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>,
+    {
+        let mut rbfs = Rbfs {
+            space: self,
+            heuristic,
+            ancestors: Vec::new(),
+            held: 1,
+            peak_nodes: 1,
+            generated: 0,
+            expanded: 0,
+            regenerated: 0,
+        };
+        let initial_state = self.initial_state();
+        let f = heuristic.estimate(&initial_state);
+        match rbfs.search(Node::new(initial_state), 0, f, INFINITY) {
+            Ok(node) => Some(MemoryBoundedSolution {
+                result: SearchResult::new(node, rbfs.generated, rbfs.expanded),
+                regenerated: rbfs.regenerated,
+                peak_nodes: rbfs.peak_nodes,
+            }),
+            Err(_) => None,
+        }
+    }
+}
+
+struct Rbfs<'a, S: Space, H> {
+    space: &'a S,
+    heuristic: &'a H,
+    ancestors: Vec<S::State>,
+    /// Nodes in memory: the root and the children of the nodes being
+    /// searched
+    held: usize,
+    peak_nodes: usize,
+    generated: usize,
+    expanded: usize,
+    regenerated: usize,
+}
+
+struct RbfsChild<S: State> {
+    node: Node<S>,
+    g: usize,
+    f: usize,
+    explored: bool,
+}
+
+impl<'a, S, H> Rbfs<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    /// Return the goal node, or the lowest f-value beyond `f_limit` to back up
+    fn search(
+        &mut self,
+        node: Node<S::State>,
+        g: usize,
+        f: usize,
+        f_limit: usize,
+    ) -> Result<Node<S::State>, usize> {
+        if self.space.is_goal(node.state()) {
+            return Ok(node);
+        }
+        self.expanded += 1;
+        let mut children = Vec::new();
+        for action in node.state().get_available_actions() {
+            let child = node.apply(&action);
+            self.generated += 1;
+            if child.state() == node.state() || self.ancestors.contains(child.state()) {
+                continue;
+            }
+            let child_g = g + action.cost();
+            let child_f = (child_g.saturating_add(self.heuristic.estimate(child.state()))).max(f);
+            children.push(RbfsChild {
+                node: child,
+                g: child_g,
+                f: child_f,
+                explored: false,
+            });
+        }
+        if children.is_empty() {
+            return Err(INFINITY);
+        }
+        self.held += children.len();
+        self.peak_nodes = self.peak_nodes.max(self.held);
+        self.ancestors.push(node.state().clone());
+        let outcome = loop {
+            children.sort_by_key(|child| child.f);
+            let best_f = children[0].f;
+            if best_f > f_limit || best_f == INFINITY {
+                break Err(best_f);
+            }
+            let alternative = children.get(1).map_or(INFINITY, |child| child.f);
+            if children[0].explored {
+                self.regenerated += 1;
+            }
+            children[0].explored = true;
+            let (child, child_g) = (children[0].node.clone(), children[0].g);
+            match self.search(child, child_g, best_f, f_limit.min(alternative)) {
+                Ok(goal) => break Ok(goal),
+                Err(backed_up) => children[0].f = backed_up,
+            }
+        };
+        self.ancestors.pop();
+        self.held -= children.len();
+        outcome
+    }
+}
+
+pub trait MemoryBoundedAStar<S: Space> {
+    /// Optimal with an admissible heuristic among the solutions that fit in
+    /// `max_nodes` nodes, never holding more. When memory runs out the worst
+    /// leaf is forgotten and its f-value is backed up into its parent; leaves
+    /// whose successors are all cycles or dead ends are forgotten right away.
+    fn sma_star_search<H>(
+        &self,
+        heuristic: &H,
+        max_nodes: usize,
+    ) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>;
+}
+
+impl<S> MemoryBoundedAStar<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+{
+    fn sma_star_search<H>(
+        &self,
+        heuristic: &H,
+        max_nodes: usize,
+    ) -> Option<MemoryBoundedSolution<S::State>>
+    where
+        H: Heuristic<S::State>,
+    {
+        SmaStar::new(self, heuristic, max_nodes.max(2)).search()
+    }
+}
+
+enum Slot {
+    NotGenerated,
+    InMemory(usize),
+    Forgotten(usize),
+    Cycle,
+}
+
+struct SmaNode<S: State> {
+    state: S,
+    parent: Option<(usize, S::Action)>,
+    g: usize,
+    f: usize,
+    depth: usize,
+    successors: Option<Vec<(S::Action, Slot)>>,
+    children: usize,
+    key: Option<(usize, Reverse<usize>, usize)>,
+}
+
+struct SmaStar<'a, S: Space, H> {
+    space: &'a S,
+    heuristic: &'a H,
+    max_nodes: usize,
+    nodes: Vec<Option<SmaNode<S::State>>>,
+    free: Vec<usize>,
+    open: BTreeSet<(usize, Reverse<usize>, usize)>,
+    used: usize,
+    peak_nodes: usize,
+    generated: usize,
+    expanded: usize,
+    regenerated: usize,
+}
+
+impl<'a, S, H> SmaStar<'a, S, H>
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: CostAction,
+    H: Heuristic<S::State>,
+{
+    fn new(space: &'a S, heuristic: &'a H, max_nodes: usize) -> Self {
+        Self {
+            space,
+            heuristic,
+            max_nodes,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            open: BTreeSet::new(),
+            used: 0,
+            peak_nodes: 0,
+            generated: 0,
+            expanded: 0,
+            regenerated: 0,
+        }
+    }
+
+    fn node(&self, id: usize) -> &SmaNode<S::State> {
+        self.nodes[id].as_ref().expect("node is in memory")
+    }
+
+    fn node_mut(&mut self, id: usize) -> &mut SmaNode<S::State> {
+        self.nodes[id].as_mut().expect("node is in memory")
+    }
+
+    fn allocate(&mut self, node: SmaNode<S::State>) -> usize {
+        self.used += 1;
+        self.peak_nodes = self.peak_nodes.max(self.used);
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = Some(node);
+                id
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// A node stays open while some of its successors that may lead to a
+    /// goal are not in memory
+    fn refresh(&mut self, id: usize) {
+        let node = self.node(id);
+        let is_open = match &node.successors {
+            None => true,
+            Some(successors) => successors.iter().any(|(_, slot)| match slot {
+                Slot::NotGenerated => true,
+                Slot::Forgotten(f) => *f < INFINITY,
+                Slot::InMemory(_) | Slot::Cycle => false,
+            }),
+        };
+        let key = is_open.then_some((node.f, Reverse(node.depth), id));
+        let old_key = node.key;
+        if old_key != key {
+            if let Some(old_key) = old_key {
+                self.open.remove(&old_key);
+            }
+            if let Some(key) = key {
+                self.open.insert(key);
+            }
+            self.node_mut(id).key = key;
+        }
+    }
+
+    /// Once every successor has been generated, the f-value of a node is the
+    /// lowest f-value among them, remembered or in memory
+    fn back_up(&mut self, id: usize) {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = self.node(id);
+            let Some(successors) = &node.successors else {
+                break;
+            };
+            if successors
+                .iter()
+                .any(|(_, slot)| matches!(slot, Slot::NotGenerated))
+            {
+                break;
+            }
+            let f = successors
+                .iter()
+                .map(|(_, slot)| match slot {
+                    Slot::InMemory(child) => self.node(*child).f,
+                    Slot::Forgotten(f) => *f,
+                    Slot::NotGenerated | Slot::Cycle => INFINITY,
+                })
+                .min()
+                .unwrap_or(INFINITY);
+            if f == node.f {
+                break;
+            }
+            current = node.parent.as_ref().map(|(parent, _)| *parent);
+            self.node_mut(id).f = f;
+            self.refresh(id);
+        }
+    }
+
+    fn is_cycle(&self, parent: usize, state: &S::State) -> bool {
+        let mut current = Some(parent);
+        while let Some(id) = current {
+            let node = self.node(id);
+            if node.state == *state {
+                return true;
+            }
+            current = node.parent.as_ref().map(|(parent, _)| *parent);
+        }
+        false
+    }
+
+    /// Drop the shallowest of the worst leaves, other than the node being
+    /// expanded, remembering its f-value in its parent
+    fn forget_worst_leaf(&mut self, expanding: usize) -> bool {
+        let worst = self.open.iter().rev().map(|&(_, _, id)| id).find(|&id| {
+            let node = self.node(id);
+            id != expanding && node.children == 0 && node.parent.is_some()
+        });
+        let Some(worst) = worst else {
+            return false;
+        };
+        if let Some(key) = self.node(worst).key {
+            self.open.remove(&key);
+        }
+        let node = self.nodes[worst].take().expect("node is in memory");
+        self.free.push(worst);
+        self.used -= 1;
+        let (parent, _) = node.parent.expect("the root is never forgotten");
+        let parent_node = self.node_mut(parent);
+        parent_node.children -= 1;
+        if let Some(successors) = &mut parent_node.successors {
+            for (_, slot) in successors.iter_mut() {
+                if matches!(slot, Slot::InMemory(child) if *child == worst) {
+                    *slot = Slot::Forgotten(node.f);
+                }
+            }
+        }
+        self.refresh(parent);
+        self.forget_closed(parent);
+        true
+    }
+
+    /// Free `id` and then its ancestors while they are closed leaves, which
+    /// can't lead to a goal, remembering them as such in their parents
+    fn forget_closed(&mut self, mut id: usize) {
+        loop {
+            let node = self.node(id);
+            if node.key.is_some() || node.children > 0 || node.successors.is_none() {
+                return;
+            }
+            let Some((parent, _)) = node.parent else {
+                return;
+            };
+            self.nodes[id] = None;
+            self.free.push(id);
+            self.used -= 1;
+            let parent_node = self.node_mut(parent);
+            parent_node.children -= 1;
+            if let Some(successors) = &mut parent_node.successors {
+                for (_, slot) in successors.iter_mut() {
+                    if matches!(slot, Slot::InMemory(child) if *child == id) {
+                        *slot = Slot::Forgotten(INFINITY);
+                    }
+                }
+            }
+            self.refresh(parent);
+            id = parent;
+        }
+    }
+
+    fn search(mut self) -> Option<MemoryBoundedSolution<S::State>> {
+        let initial_state = self.space.initial_state();
+        let f = self.heuristic.estimate(&initial_state);
+        let root = self.allocate(SmaNode {
+            state: initial_state,
+            parent: None,
+            g: 0,
+            f,
+            depth: 0,
+            successors: None,
+            children: 0,
+            key: None,
+        });
+        self.refresh(root);
+        while let Some(&(f, _, best)) = self.open.first() {
+            if f == INFINITY {
+                return None;
+            }
+            if self.space.is_goal(&self.node(best).state) {
+                return Some(self.solution(best));
+            }
+            if self.node(best).successors.is_none() {
+                self.expanded += 1;
+                let successors = self
+                    .node(best)
+                    .state
+                    .get_available_actions()
+                    .into_iter()
+                    .map(|action| (action, Slot::NotGenerated))
+                    .collect::<Vec<_>>();
+                let dead_end = successors.is_empty();
+                self.node_mut(best).successors = Some(successors);
+                if dead_end {
+                    self.node_mut(best).f = INFINITY;
+                    self.refresh(best);
+                    if let Some((parent, _)) = self.node(best).parent {
+                        self.back_up(parent);
+                    }
+                    self.forget_closed(best);
+                    continue;
+                }
+            }
+            self.generate_successor(best);
+        }
+        None
+    }
+
+    /// Generate the next successor not in memory: a forgotten one whose
+    /// remembered f-value is the node's own, otherwise the next one never
+    /// generated, otherwise the best forgotten one
+    fn generate_successor(&mut self, parent: usize) {
+        let node = self.node(parent);
+        let successors = node.successors.as_ref().expect("expanded");
+        let forgotten = successors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, slot))| match slot {
+                Slot::Forgotten(f) if *f < INFINITY => Some((*f, i)),
+                _ => None,
+            })
+            .min();
+        let fresh = successors
+            .iter()
+            .position(|(_, slot)| matches!(slot, Slot::NotGenerated));
+        let (remembered_f, index) = match (forgotten, fresh) {
+            (Some((f, index)), _) if f <= node.f => (Some(f), index),
+            (_, Some(index)) => (None, index),
+            (Some((f, index)), None) => (Some(f), index),
+            (None, None) => {
+                self.refresh(parent);
+                return;
+            }
+        };
+        let action = successors[index].0.clone();
+        let parent_node = self.node(parent);
+        let state = parent_node.state.apply(&action);
+        let g = parent_node.g + action.cost();
+        let depth = parent_node.depth + 1;
+        let parent_f = parent_node.f;
+        self.generated += 1;
+        if self.is_cycle(parent, &state) {
+            self.set_slot(parent, index, Slot::Cycle);
+        } else {
+            let too_deep = if self.space.is_goal(&state) {
+                depth >= self.max_nodes
+            } else {
+                depth + 1 >= self.max_nodes
+            };
+            let f = if too_deep {
+                // too deep for a solution to fit in memory
+                INFINITY
+            } else {
+                let f = g.saturating_add(self.heuristic.estimate(&state));
+                f.max(parent_f).max(remembered_f.unwrap_or(0))
+            };
+            if remembered_f.is_some() {
+                self.regenerated += 1;
+            }
+            while self.used >= self.max_nodes && self.forget_worst_leaf(parent) {}
+            if self.used >= self.max_nodes {
+                // only the path to the parent is left, so the child can't
+                // fit beside it
+                self.set_slot(parent, index, Slot::Forgotten(f));
+            } else {
+                let child = self.allocate(SmaNode {
+                    state,
+                    parent: Some((parent, action)),
+                    g,
+                    f,
+                    depth,
+                    successors: None,
+                    children: 0,
+                    key: None,
+                });
+                self.node_mut(parent).children += 1;
+                self.set_slot(parent, index, Slot::InMemory(child));
+                self.refresh(child);
+            }
+        }
+        self.refresh(parent);
+        self.back_up(parent);
+        self.forget_closed(parent);
+    }
+
+    fn set_slot(&mut self, id: usize, index: usize, slot: Slot) {
+        if let Some(successors) = &mut self.node_mut(id).successors {
+            successors[index].1 = slot;
+        }
+    }
+
+    fn solution(&self, goal: usize) -> MemoryBoundedSolution<S::State> {
+        let mut path = Vec::new();
+        let mut current = goal;
+        while let Some((parent, action)) = &self.node(current).parent {
+            path.push(action.clone());
+            current = *parent;
+        }
+        path.reverse();
+        MemoryBoundedSolution {
+            result: SearchResult::from_path(
+                self.node(goal).state.clone(),
+                path,
+                self.generated,
+                self.expanded,
+            ),
+            regenerated: self.regenerated,
+            peak_nodes: self.peak_nodes,
+        }
+    }
+}
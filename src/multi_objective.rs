@@ -0,0 +1,71 @@
+//! Multi-objective search:
+//! - Pareto-front Dijkstra (NAMOA* without heuristic)
+
+use crate::{
+    dup_protection::{weakly_dominates, ParetoCacheSet},
+    frontiers::PriorityFrontier,
+    output::{ParetoSolution, SearchResult},
+    MultiCostAction, Node, Space, State,
+};
+
+pub trait ParetoSearch<S: Space> {
+    /// Return every non-dominated path to a goal. Paths with identical cost
+    /// vectors are reported once.
+    fn pareto_search(&self) -> Vec<ParetoSolution<S::State>>;
+}
+
+impl<S> ParetoSearch<S> for S
+where
+    S: Space,
+    S::State: State,
+    <S::State as State>::Action: MultiCostAction,
+{
+    fn pareto_search(&self) -> Vec<ParetoSolution<S::State>> {
+        let mut generated: usize = 0;
+        let mut expanded: usize = 0;
+        let mut solutions: Vec<ParetoSolution<S::State>> = Vec::new();
+        let mut labels = ParetoCacheSet::new();
+        let mut frontier = PriorityFrontier::new();
+        let initial_state = self.initial_state();
+        labels.insert(&initial_state, &[]);
+        frontier.push(Node::new(initial_state), Vec::new());
+        // labels are popped in lexicographic order, so no later label can
+        // dominate one that has already been popped
+        while let Some((node, cost)) = frontier.pop() {
+            let state = node.state();
+            if !labels.contains(state, &cost) {
+                // superseded by a dominating label after being pushed
+                continue;
+            }
+            if solutions
+                .iter()
+                .any(|solution| weakly_dominates(&solution.cost, &cost))
+            {
+                continue;
+            }
+            if self.is_goal(state) {
+                solutions.push(ParetoSolution {
+                    result: SearchResult::new(node, generated, expanded),
+                    cost,
+                });
+                continue;
+            }
+            expanded += 1;
+            for action in state.get_available_actions() {
+                let child_cost = add_costs(&cost, &action.costs());
+                let child = node.apply(&action);
+                generated += 1;
+                if labels.insert(child.state(), &child_cost) {
+                    frontier.push(child, child_cost);
+                }
+            }
+        }
+        solutions
+    }
+}
+
+fn add_costs(a: &[usize], b: &[usize]) -> Vec<usize> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0) + b.get(i).copied().unwrap_or(0))
+        .collect()
+}
@@ -0,0 +1,72 @@
+//! Planning when actions may have several outcomes, without probabilities:
+//! - AND-OR graph search, returning conditional plans
+
+use crate::{output::ConditionalPlan, Space, State};
+
+/// Space whose actions may lead to several states. `State::apply` is not
+/// used: the possible outcomes come from `results`.
+pub trait NondeterministicSpace: Space {
+    fn results(
+        &self,
+        state: &Self::State,
+        action: &<Self::State as State>::Action,
+    ) -> Vec<Self::State>;
+}
+
+pub trait AndOrSearch<S: NondeterministicSpace> {
+    /// Plan reaching a goal from the initial state whatever the outcomes of
+    /// its actions, choosing an action in every state (OR nodes) and
+    /// planning for every outcome (AND nodes). Plans that could revisit a
+    /// state are not considered, so `None` may also mean that only cyclic
+    /// plans exist.
+    fn and_or_search(&self) -> Option<ConditionalPlan<S::State>>;
+}
+
+impl<S: NondeterministicSpace> AndOrSearch<S> for S {
+    fn and_or_search(&self) -> Option<ConditionalPlan<S::State>> {
+        or_search(self, self.initial_state(), &mut Vec::new())
+    }
+}
+
+/// Plan for each outcome of an action
+type Branches<S> = Vec<(S, ConditionalPlan<S>)>;
+
+/// Plan from `state`, whose ancestors are `path`
+fn or_search<S: NondeterministicSpace>(
+    space: &S,
+    state: S::State,
+    path: &mut Vec<S::State>,
+) -> Option<ConditionalPlan<S::State>> {
+    if space.is_goal(&state) {
+        return Some(ConditionalPlan::Done);
+    }
+    if path.contains(&state) {
+        return None;
+    }
+    path.push(state.clone());
+    let plan = state
+        .get_available_actions()
+        .into_iter()
+        .find_map(|action| {
+            let outcomes = space.results(&state, &action);
+            and_search(space, outcomes, path)
+                .map(|branches| ConditionalPlan::Step { action, branches })
+        });
+    path.pop();
+    plan
+}
+
+/// Plan for every state of `outcomes`
+fn and_search<S: NondeterministicSpace>(
+    space: &S,
+    outcomes: Vec<S::State>,
+    path: &mut Vec<S::State>,
+) -> Option<Branches<S::State>> {
+    outcomes
+        .into_iter()
+        .map(|outcome| {
+            let plan = or_search(space, outcome.clone(), path)?;
+            Some((outcome, plan))
+        })
+        .collect()
+}
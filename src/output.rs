@@ -1,4 +1,6 @@
 use crate::{State, Node};
+use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug)]
 pub struct SearchResult<S>
@@ -23,8 +25,536 @@ impl<S: State> SearchResult<S> {
     }
 }
 
+impl<S: State> SearchResult<S> {
+    pub(crate) fn from_path(
+        end_state: S,
+        path: Vec<S::Action>,
+        generated: usize,
+        expanded: usize,
+    ) -> Self {
+        Self {
+            end_state,
+            path,
+            expanded,
+            generated,
+        }
+    }
+}
+
 impl<S: State> From<Node<S>> for SearchResult<S> {
     fn from(node: Node<S>) -> Self {
         SearchResult::new(node, 0, 0)
     }
-}
\ No newline at end of file
+}
+
+/// One point of a Pareto front: a non-dominated path and its cost vector
+pub struct ParetoSolution<S>
+where
+    S: State,
+{
+    pub result: SearchResult<S>,
+    pub cost: Vec<usize>,
+}
+
+// derive would only require `S: Debug`, but `SearchResult<S>` also needs
+// `S::Action: Debug`
+impl<S> fmt::Debug for ParetoSolution<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParetoSolution")
+            .field("result", &self.result)
+            .field("cost", &self.cost)
+            .finish()
+    }
+}
+
+/// Solution reported by an anytime search
+pub struct AnytimeSolution<S>
+where
+    S: State,
+{
+    pub result: SearchResult<S>,
+    pub cost: usize,
+    /// The cost is at most `bound` times the optimal cost, provided the
+    /// heuristic is admissible
+    pub bound: f64,
+}
+
+// derive would only require `S: Debug`, but `SearchResult<S>` also needs
+// `S::Action: Debug`
+impl<S> fmt::Debug for AnytimeSolution<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnytimeSolution")
+            .field("result", &self.result)
+            .field("cost", &self.cost)
+            .field("bound", &self.bound)
+            .finish()
+    }
+}
+
+/// Solution of a memory-bounded search, which may have to generate again
+/// nodes it forgot
+pub struct MemoryBoundedSolution<S>
+where
+    S: State,
+{
+    pub result: SearchResult<S>,
+    /// Times a previously explored subtree was generated again
+    pub regenerated: usize,
+    /// Most nodes held in memory at once
+    pub peak_nodes: usize,
+}
+
+// derive would only require `S: Debug`, but `SearchResult<S>` also needs
+// `S::Action: Debug`
+impl<S> fmt::Debug for MemoryBoundedSolution<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBoundedSolution")
+            .field("result", &self.result)
+            .field("regenerated", &self.regenerated)
+            .field("peak_nodes", &self.peak_nodes)
+            .finish()
+    }
+}
+
+/// Outcome of a local search: the best state found and the effort spent
+#[derive(Debug, Clone)]
+pub struct LocalSearchResult<S> {
+    pub state: S,
+    pub value: f64,
+    /// Moves performed, across all restarts
+    pub steps: usize,
+    /// Calls to the objective, across all restarts
+    pub evaluations: usize,
+    pub restarts: usize,
+    /// Best value found so far, at the start and after every step
+    pub trajectory: Vec<f64>,
+}
+
+/// Move chosen by an adversarial search and its backed-up value for the
+/// player to move
+pub struct GameDecision<S>
+where
+    S: State,
+{
+    /// `None` if the state is terminal or the search didn't look ahead
+    pub best_move: Option<S::Action>,
+    pub value: f64,
+    /// Moves expected from both sides, starting with `best_move`
+    pub principal_variation: Vec<S::Action>,
+    /// Depth of the deepest completed search
+    pub depth: usize,
+    pub expanded: usize,
+}
+
+// no `S` is stored, so derive's `S: Debug` bound would be needless
+impl<S> fmt::Debug for GameDecision<S>
+where
+    S: State,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GameDecision")
+            .field("best_move", &self.best_move)
+            .field("value", &self.value)
+            .field("principal_variation", &self.principal_variation)
+            .field("depth", &self.depth)
+            .field("expanded", &self.expanded)
+            .finish()
+    }
+}
+
+/// Effort spent by a constraint solver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Values tried for a variable
+    pub assignments: usize,
+    /// Times every value of a variable was ruled out
+    pub backtracks: usize,
+    /// Calls to `Constraint::is_consistent`
+    pub checks: usize,
+    /// Calls to `Constraint::propagate`
+    pub propagations: usize,
+    /// Backtracks that skipped over at least one variable
+    pub backjumps: usize,
+    /// Nogoods recorded by the solver
+    pub nogoods: usize,
+}
+
+/// Outcome of a constraint solver
+#[derive(Debug, Clone)]
+pub struct CspResult<V> {
+    /// Value of every variable, `None` if the problem has no solution
+    pub solution: Option<Vec<V>>,
+    pub stats: SolverStats,
+}
+
+/// Effort spent by a SAT solver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SatStats {
+    /// Literals chosen without being implied
+    pub decisions: usize,
+    /// Literals implied by unit propagation
+    pub propagations: usize,
+    pub conflicts: usize,
+    pub learned_clauses: usize,
+    pub restarts: usize,
+}
+
+/// Outcome of a SAT solver
+#[derive(Debug, Clone)]
+pub struct SatResult {
+    /// Value of every variable satisfying the formula, `None` if it is
+    /// unsatisfiable
+    pub model: Option<Vec<bool>>,
+    pub stats: SatStats,
+}
+
+/// Policy of a Markov decision process, with the expected discounted reward
+/// of following it from every state
+#[derive(Debug)]
+pub struct MdpSolution<S: State> {
+    /// Action to take in every non-terminal state
+    pub policy: HashMap<S, S::Action>,
+    pub values: HashMap<S, f64>,
+    /// Sweeps over the states of value iteration, improvement steps of
+    /// policy iteration
+    pub iterations: usize,
+    /// Bellman backups of a state, for any action
+    pub backups: usize,
+    /// Whether the values were within the threshold before the iteration
+    /// limit
+    pub converged: bool,
+}
+
+/// Plan for nondeterministic actions: a tree of actions branching on their
+/// outcomes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConditionalPlan<S: State> {
+    /// A goal is reached
+    Done,
+    /// Take `action`, then follow the plan of the outcome
+    Step {
+        action: S::Action,
+        branches: Vec<(S, ConditionalPlan<S>)>,
+    },
+}
+
+impl<S: State> ConditionalPlan<S> {
+    /// Action to take now, `None` once done
+    pub fn action(&self) -> Option<&S::Action> {
+        match self {
+            ConditionalPlan::Done => None,
+            ConditionalPlan::Step { action, .. } => Some(action),
+        }
+    }
+
+    /// Rest of the plan once the action led to `outcome`, `None` for an
+    /// unplanned outcome
+    pub fn after(&self, outcome: &S) -> Option<&ConditionalPlan<S>> {
+        match self {
+            ConditionalPlan::Done => None,
+            ConditionalPlan::Step { branches, .. } => branches
+                .iter()
+                .find(|(state, _)| state == outcome)
+                .map(|(_, plan)| plan),
+        }
+    }
+
+    /// Most actions taken before reaching a goal
+    pub fn depth(&self) -> usize {
+        match self {
+            ConditionalPlan::Done => 0,
+            ConditionalPlan::Step { branches, .. } => {
+                1 + branches.iter().map(|(_, plan)| plan.depth()).max().unwrap_or(0)
+            }
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result
+    where
+        S: fmt::Debug,
+        S::Action: fmt::Debug,
+    {
+        let ConditionalPlan::Step { action, branches } = self else {
+            return writeln!(f, "{:indent$}done", "");
+        };
+        writeln!(f, "{:indent$}{:?}", "", action)?;
+        match &branches[..] {
+            // a single outcome needs no condition
+            [(_, ConditionalPlan::Done)] => Ok(()),
+            [(_, plan)] => plan.write(f, indent),
+            _ => {
+                for (state, plan) in branches {
+                    writeln!(f, "{:indent$}if {:?}:", "", state)?;
+                    plan.write(f, indent + 4)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One action per line, the branches of an action indented under the
+/// outcome they follow
+impl<S> fmt::Display for ConditionalPlan<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// State of a space where a heuristic misjudges costs, reached from the
+/// initial state by `path`
+pub enum HeuristicViolation<S: State> {
+    /// The estimate of `state` is above the cost of `to_goal`, a path from
+    /// it to a goal
+    Overestimate {
+        path: Vec<S::Action>,
+        state: S,
+        estimate: usize,
+        to_goal: Vec<S::Action>,
+        cost: usize,
+    },
+    /// The estimate drops by more than the cost of `action` when going from
+    /// `state` to `next`
+    Inconsistent {
+        path: Vec<S::Action>,
+        state: S,
+        estimate: usize,
+        action: S::Action,
+        cost: usize,
+        next: S,
+        next_estimate: usize,
+    },
+}
+
+impl<S> fmt::Display for HeuristicViolation<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeuristicViolation::Overestimate {
+                path,
+                state,
+                estimate,
+                to_goal,
+                cost,
+            } => write!(
+                f,
+                "h = {} in {:?}, reached by {:?}, but {:?} reaches a goal for {}",
+                estimate, state, path, to_goal, cost
+            ),
+            HeuristicViolation::Inconsistent {
+                path,
+                state,
+                estimate,
+                action,
+                cost,
+                next,
+                next_estimate,
+            } => write!(
+                f,
+                "h = {} in {:?}, reached by {:?}, but {:?} costing {} leads to {:?} where h = {}",
+                estimate, state, path, action, cost, next, next_estimate
+            ),
+        }
+    }
+}
+
+// the same text as `Display`, which assertion messages use
+impl<S> fmt::Debug for HeuristicViolation<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+/// Violations of admissibility and consistency found in a region of a space
+pub struct HeuristicReport<S: State> {
+    /// States of the region
+    pub states: usize,
+    /// Whether the region holds every state reachable from the initial one
+    pub exhaustive: bool,
+    /// In breadth-first order of their states
+    pub violations: Vec<HeuristicViolation<S>>,
+}
+
+impl<S: State> HeuristicReport<S> {
+    pub fn overestimates(&self) -> impl Iterator<Item = &HeuristicViolation<S>> {
+        self.violations
+            .iter()
+            .filter(|violation| matches!(violation, HeuristicViolation::Overestimate { .. }))
+    }
+
+    pub fn inconsistencies(&self) -> impl Iterator<Item = &HeuristicViolation<S>> {
+        self.violations
+            .iter()
+            .filter(|violation| matches!(violation, HeuristicViolation::Inconsistent { .. }))
+    }
+
+    /// Whether no estimate of the region was found above the cost of a path
+    /// to a goal
+    pub fn is_admissible(&self) -> bool {
+        self.overestimates().next().is_none()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies().next().is_none()
+    }
+
+    /// Panics with the first overestimate, for tests
+    pub fn assert_admissible(&self)
+    where
+        S: fmt::Debug,
+        S::Action: fmt::Debug,
+    {
+        if let Some(violation) = self.overestimates().next() {
+            panic!("heuristic overestimates: {}", violation);
+        }
+    }
+
+    /// Panics with the first inconsistency, for tests
+    pub fn assert_consistent(&self)
+    where
+        S: fmt::Debug,
+        S::Action: fmt::Debug,
+    {
+        if let Some(violation) = self.inconsistencies().next() {
+            panic!("heuristic is inconsistent: {}", violation);
+        }
+    }
+}
+
+// derive would only require `S: Debug`, but the violations also need
+// `S::Action: Debug`
+impl<S> fmt::Debug for HeuristicReport<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeuristicReport")
+            .field("states", &self.states)
+            .field("exhaustive", &self.exhaustive)
+            .field("violations", &self.violations)
+            .finish()
+    }
+}
+
+/// Part of a plan cut out by `shorten_plan`, at step `step`, from zero, of
+/// the plan as it was when the edit was made
+#[derive(Clone, PartialEq, Eq)]
+pub enum PlanEdit<S: State> {
+    /// Actions after the first goal the plan goes through
+    PastGoal {
+        step: usize,
+        removed: Vec<S::Action>,
+    },
+    /// Actions leading back to a state the plan was already in
+    Loop {
+        step: usize,
+        removed: Vec<S::Action>,
+    },
+    /// Actions replaced by cheaper ones reaching the same state
+    Shortcut {
+        step: usize,
+        removed: Vec<S::Action>,
+        replacement: Vec<S::Action>,
+    },
+}
+
+impl<S: State> PlanEdit<S> {
+    pub fn step(&self) -> usize {
+        match self {
+            PlanEdit::PastGoal { step, .. }
+            | PlanEdit::Loop { step, .. }
+            | PlanEdit::Shortcut { step, .. } => *step,
+        }
+    }
+
+    pub fn removed(&self) -> &[S::Action] {
+        match self {
+            PlanEdit::PastGoal { removed, .. }
+            | PlanEdit::Loop { removed, .. }
+            | PlanEdit::Shortcut { removed, .. } => removed,
+        }
+    }
+}
+
+// no `S` is stored, so derive's `S: Debug` bound would be needless
+impl<S> fmt::Debug for PlanEdit<S>
+where
+    S: State,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanEdit::PastGoal { step, removed } => f
+                .debug_struct("PastGoal")
+                .field("step", step)
+                .field("removed", removed)
+                .finish(),
+            PlanEdit::Loop { step, removed } => f
+                .debug_struct("Loop")
+                .field("step", step)
+                .field("removed", removed)
+                .finish(),
+            PlanEdit::Shortcut {
+                step,
+                removed,
+                replacement,
+            } => f
+                .debug_struct("Shortcut")
+                .field("step", step)
+                .field("removed", removed)
+                .field("replacement", replacement)
+                .finish(),
+        }
+    }
+}
+
+/// Plan reaching a goal from the same initial state as the original one, at
+/// a cost no higher
+pub struct ShortenedPlan<S: State> {
+    pub path: Vec<S::Action>,
+    pub cost: usize,
+    pub original_cost: usize,
+    /// In the order they were made
+    pub edits: Vec<PlanEdit<S>>,
+}
+
+// no `S` is stored, so derive's `S: Debug` bound would be needless
+impl<S> fmt::Debug for ShortenedPlan<S>
+where
+    S: State,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShortenedPlan")
+            .field("path", &self.path)
+            .field("cost", &self.cost)
+            .field("original_cost", &self.original_cost)
+            .field("edits", &self.edits)
+            .finish()
+    }
+}
@@ -0,0 +1,226 @@
+//! Pattern databases: exact costs to the goal in an abstraction of a space,
+//! computed once and looked up as a heuristic
+//! - built by exploring the abstract space forward, then searching it
+//!   backward from the abstract goals
+//! - one byte per abstract state, saved to and loaded from files
+//! - additive sums of databases of disjoint patterns
+
+use crate::{CostAction, Heuristic, State};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, io};
+
+/// Larger costs are stored as this one, which keeps the database admissible
+const MAX_DISTANCE: u8 = u8::MAX - 1;
+/// Entry of the keys of unreachable abstract states
+const UNREACHED: u8 = u8::MAX;
+/// Start of the files, before the number of entries
+const MAGIC: &[u8; 4] = b"PDB1";
+
+/// Cost to the goal of every abstract state, by key. Several abstract states
+/// may share a key, which then gets the lowest of their costs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternDatabase {
+    distances: Vec<u8>,
+}
+
+impl PatternDatabase {
+    /// Cheapest costs to a goal from every abstract state reachable from the
+    /// abstractions of `starts`. The abstract space is explored forward from
+    /// them, then searched backward from the states where `is_goal` holds,
+    /// in order of cost, so actions don't need to be reversible. The keys of
+    /// the other abstract states have no distance. `key` numbers the
+    /// abstract states below `size`.
+    ///
+    /// Panics if a key is `size` or more.
+    pub fn build<S, A, F, G, K>(
+        starts: &[S],
+        abstraction: F,
+        is_goal: G,
+        key: K,
+        size: usize,
+    ) -> Self
+    where
+        A: State,
+        A::Action: CostAction,
+        F: Fn(&S) -> A,
+        G: Fn(&A) -> bool,
+        K: Fn(&A) -> usize,
+    {
+        let mut indices: HashMap<A, usize> = HashMap::new();
+        let mut states = Vec::new();
+        for state in starts.iter().map(abstraction) {
+            if !indices.contains_key(&state) {
+                indices.insert(state.clone(), states.len());
+                states.push(state);
+            }
+        }
+        // predecessors of every abstract state, with the costs of their actions
+        let mut reverse: Vec<Vec<(usize, usize)>> = vec![Vec::new(); states.len()];
+        let mut next = 0;
+        while next < states.len() {
+            let state = states[next].clone();
+            for action in state.get_available_actions() {
+                let child = state.apply(&action);
+                let child = match indices.get(&child) {
+                    Some(&index) => index,
+                    None => {
+                        indices.insert(child.clone(), states.len());
+                        states.push(child);
+                        reverse.push(Vec::new());
+                        states.len() - 1
+                    }
+                };
+                reverse[child].push((next, action.cost()));
+            }
+            next += 1;
+        }
+
+        let keys: Vec<usize> = states
+            .iter()
+            .map(|state| {
+                let state_key = key(state);
+                assert!(
+                    state_key < size,
+                    "key {} of an abstract state is out of the {} keys of the database",
+                    state_key,
+                    size
+                );
+                state_key
+            })
+            .collect();
+        let mut distances = vec![UNREACHED; size];
+        let mut best = vec![usize::MAX; states.len()];
+        let goals: Vec<usize> = (0..states.len())
+            .filter(|&index| is_goal(&states[index]))
+            .collect();
+        for &goal in &goals {
+            best[goal] = 0;
+        }
+        // states to expand by cost, zero-cost actions staying in the layer
+        let mut layers = vec![goals];
+        let mut cost = 0;
+        while cost < layers.len() {
+            while let Some(index) = layers[cost].pop() {
+                if best[index] < cost {
+                    continue;
+                }
+                let entry = &mut distances[keys[index]];
+                if *entry == UNREACHED {
+                    *entry = cost.min(MAX_DISTANCE as usize) as u8;
+                }
+                for &(parent, action_cost) in &reverse[index] {
+                    let parent_cost = cost + action_cost;
+                    if best[parent] <= parent_cost {
+                        continue;
+                    }
+                    best[parent] = parent_cost;
+                    if layers.len() <= parent_cost {
+                        layers.resize_with(parent_cost + 1, Vec::new);
+                    }
+                    layers[parent_cost].push(parent);
+                }
+            }
+            cost += 1;
+        }
+        Self { distances }
+    }
+
+    /// Number of keys
+    pub fn size(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Cost to the goal of the abstract states with `key`, `None` if they
+    /// can't reach it. Panics if `key` is not below `size`.
+    pub fn distance(&self, key: usize) -> Option<usize> {
+        assert!(
+            key < self.distances.len(),
+            "key {} is out of the {} keys of the database",
+            key,
+            self.distances.len()
+        );
+        match self.distances[key] {
+            UNREACHED => None,
+            distance => Some(distance as usize),
+        }
+    }
+
+    /// Write the database to `path`, in the format read by `load`
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 8 + self.distances.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(self.distances.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.distances);
+        fs::write(path, bytes)
+    }
+
+    /// Database written by `save`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let Some(header) = bytes.strip_prefix(MAGIC) else {
+            return Err(invalid("not a pattern database"));
+        };
+        if header.len() < 8 {
+            return Err(invalid("truncated pattern database"));
+        }
+        let (size, distances) = header.split_at(8);
+        let size = u64::from_le_bytes(size.try_into().expect("eight bytes"));
+        if distances.len() as u64 != size {
+            return Err(invalid("truncated pattern database"));
+        }
+        Ok(Self {
+            distances: distances.to_vec(),
+        })
+    }
+}
+
+/// Key of the abstract state of a state in a database
+type Key<'a, S> = Box<dyn Fn(&S) -> usize + 'a>;
+
+/// Sum of pattern databases, each looked up with the key of the abstract
+/// state of a state. Admissible when every action costs something in at
+/// most one of the abstractions, as with disjoint patterns of tiles where
+/// only the moves of the tiles of the pattern cost something. A single
+/// database is always admissible.
+pub struct AdditivePdb<'a, S> {
+    databases: Vec<(PatternDatabase, Key<'a, S>)>,
+}
+
+impl<'a, S: State> AdditivePdb<'a, S> {
+    pub fn new() -> Self {
+        Self {
+            databases: Vec::new(),
+        }
+    }
+
+    /// Add `database`, where a state is looked up with `key`
+    pub fn with_database<K>(mut self, database: PatternDatabase, key: K) -> Self
+    where
+        K: Fn(&S) -> usize + 'a,
+    {
+        self.databases.push((database, Box::new(key)));
+        self
+    }
+}
+
+impl<S: State> Default for AdditivePdb<'_, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: State> Heuristic<S> for AdditivePdb<'_, S> {
+    /// `usize::MAX` when a database can't reach the goal
+    fn estimate(&self, state: &S) -> usize {
+        self.databases
+            .iter()
+            .try_fold(0, |total, (database, key)| {
+                database
+                    .distance(key(state))
+                    .map(|distance| total + distance)
+            })
+            .unwrap_or(usize::MAX)
+    }
+}
@@ -0,0 +1,310 @@
+use super::{Fact, Operator, StripsState, StripsTask};
+use crate::{Heuristic, Space};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Estimate of the states from which the goal can't be reached
+const INFINITY: usize = usize::MAX;
+
+/// How the costs of the preconditions of an action add up
+#[derive(Clone, Copy)]
+enum Combine {
+    Max,
+    Sum,
+}
+
+impl Combine {
+    fn apply(self, a: usize, b: usize) -> usize {
+        match self {
+            Combine::Max => a.max(b),
+            Combine::Sum => a.saturating_add(b),
+        }
+    }
+}
+
+/// Relaxed cost of every fact from a state, with the action reaching it the
+/// cheapest
+struct Costs {
+    facts: Vec<usize>,
+    supporters: Vec<Option<usize>>,
+}
+
+impl Costs {
+    /// Reach the facts added by `action`, whose preconditions cost `cost`
+    fn reach(
+        &mut self,
+        action: usize,
+        add: &[Fact],
+        cost: usize,
+        queue: &mut BinaryHeap<Reverse<(usize, Fact)>>,
+    ) {
+        let cost = cost.saturating_add(1);
+        for &fact in add {
+            if cost < self.facts[fact] {
+                self.facts[fact] = cost;
+                self.supporters[fact] = Some(action);
+                queue.push(Reverse((cost, fact)));
+            }
+        }
+    }
+}
+
+/// Delete relaxation of a task: actions never make facts false
+struct Relaxation {
+    operators: Vec<Operator>,
+    preconditions: Vec<Vec<Fact>>,
+    add: Vec<Vec<Fact>>,
+    /// Actions with each fact among their preconditions
+    consumers: Vec<Vec<usize>>,
+    goal: Vec<Fact>,
+}
+
+impl Relaxation {
+    fn new(task: &StripsTask) -> Self {
+        let mut consumers = vec![Vec::new(); task.facts().len()];
+        for (index, action) in task.actions().iter().enumerate() {
+            for &fact in &action.preconditions {
+                consumers[fact].push(index);
+            }
+        }
+        Self {
+            operators: (0..task.actions().len())
+                .map(|index| task.operator(index))
+                .collect(),
+            preconditions: task
+                .actions()
+                .iter()
+                .map(|action| action.preconditions.clone())
+                .collect(),
+            add: task
+                .actions()
+                .iter()
+                .map(|action| action.add.clone())
+                .collect(),
+            consumers,
+            goal: task.goal().to_vec(),
+        }
+    }
+
+    /// Generalised Dijkstra: an action is reached once all its preconditions
+    /// are, at the combined cost of its preconditions plus one
+    fn costs(&self, state: &StripsState, combine: Combine) -> Costs {
+        let mut costs = Costs {
+            facts: vec![INFINITY; self.consumers.len()],
+            supporters: vec![None; self.consumers.len()],
+        };
+        let mut unsatisfied: Vec<usize> = self.preconditions.iter().map(Vec::len).collect();
+        let mut action_costs = vec![0; self.operators.len()];
+        let mut queue = BinaryHeap::new();
+        for fact in state.facts() {
+            costs.facts[fact] = 0;
+            queue.push(Reverse((0, fact)));
+        }
+        for action in (0..self.operators.len()).filter(|&action| unsatisfied[action] == 0) {
+            costs.reach(action, &self.add[action], 0, &mut queue);
+        }
+        while let Some(Reverse((cost, fact))) = queue.pop() {
+            if cost > costs.facts[fact] {
+                continue;
+            }
+            for &action in &self.consumers[fact] {
+                action_costs[action] = combine.apply(action_costs[action], cost);
+                unsatisfied[action] -= 1;
+                if unsatisfied[action] == 0 {
+                    costs.reach(action, &self.add[action], action_costs[action], &mut queue);
+                }
+            }
+        }
+        costs
+    }
+
+    fn goal_cost(&self, costs: &Costs, combine: Combine) -> usize {
+        self.goal.iter().fold(0, |total, &fact| {
+            if costs.facts[fact] == INFINITY {
+                INFINITY
+            } else {
+                combine.apply(total, costs.facts[fact])
+            }
+        })
+    }
+
+    /// Actions of a plan of the relaxation reaching the goal, found by
+    /// following the supporters back from the goal
+    fn relaxed_plan(&self, costs: &Costs) -> Option<Vec<usize>> {
+        let mut plan = Vec::new();
+        let mut in_plan = vec![false; self.operators.len()];
+        let mut visited = vec![false; self.consumers.len()];
+        let mut open = self.goal.clone();
+        while let Some(fact) = open.pop() {
+            if std::mem::replace(&mut visited[fact], true) || costs.facts[fact] == 0 {
+                continue;
+            }
+            let action = costs.supporters[fact]?;
+            if !std::mem::replace(&mut in_plan[action], true) {
+                plan.push(action);
+                open.extend(&self.preconditions[action]);
+            }
+        }
+        Some(plan)
+    }
+}
+
+/// Relaxed cost of the most expensive goal fact, reaching every fact by its
+/// cheapest action. Admissible.
+pub struct HMax {
+    relaxation: Relaxation,
+}
+
+impl HMax {
+    pub fn new(task: &StripsTask) -> Self {
+        Self {
+            relaxation: Relaxation::new(task),
+        }
+    }
+}
+
+impl Heuristic<StripsState> for HMax {
+    /// `usize::MAX` when the goal is out of reach
+    fn estimate(&self, state: &StripsState) -> usize {
+        let costs = self.relaxation.costs(state, Combine::Max);
+        self.relaxation.goal_cost(&costs, Combine::Max)
+    }
+}
+
+/// Sum of the relaxed costs of the goal facts, as if they were reached
+/// independently. Not admissible, but better informed than `HMax`.
+pub struct HAdd {
+    relaxation: Relaxation,
+}
+
+impl HAdd {
+    pub fn new(task: &StripsTask) -> Self {
+        Self {
+            relaxation: Relaxation::new(task),
+        }
+    }
+}
+
+impl Heuristic<StripsState> for HAdd {
+    /// `usize::MAX` when the goal is out of reach
+    fn estimate(&self, state: &StripsState) -> usize {
+        let costs = self.relaxation.costs(state, Combine::Sum);
+        self.relaxation.goal_cost(&costs, Combine::Sum)
+    }
+}
+
+/// Length of a plan of the delete relaxation, made of the cheapest actions
+/// according to `HAdd`. Not admissible, but counts each action once.
+pub struct FfHeuristic {
+    relaxation: Relaxation,
+}
+
+impl FfHeuristic {
+    pub fn new(task: &StripsTask) -> Self {
+        Self {
+            relaxation: Relaxation::new(task),
+        }
+    }
+
+    /// Actions of the relaxed plan that are applicable in `state`: the
+    /// preferred operators, which usually lead towards the goal
+    pub fn helpful_actions(&self, state: &StripsState) -> Vec<Operator> {
+        let costs = self.relaxation.costs(state, Combine::Sum);
+        let mut plan = self.relaxation.relaxed_plan(&costs).unwrap_or_default();
+        plan.retain(|&action| {
+            self.relaxation.preconditions[action]
+                .iter()
+                .all(|&fact| state.holds(fact))
+        });
+        plan.sort_unstable();
+        plan.into_iter()
+            .map(|action| self.relaxation.operators[action].clone())
+            .collect()
+    }
+}
+
+impl Heuristic<StripsState> for FfHeuristic {
+    /// `usize::MAX` when the goal is out of reach
+    fn estimate(&self, state: &StripsState) -> usize {
+        let costs = self.relaxation.costs(state, Combine::Sum);
+        self.relaxation
+            .relaxed_plan(&costs)
+            .map_or(INFINITY, |plan| plan.len())
+    }
+}
+
+/// Number of landmarks, facts that every plan makes true at some point, that
+/// are false and still needed. The landmarks are found backwards from the
+/// goal: the preconditions shared by every action adding a landmark are
+/// landmarks too, and must hold before it. Not admissible, and blind to
+/// dead ends. Only valid in the states reachable from the initial state.
+pub struct LandmarkCount {
+    goal: Vec<Fact>,
+    landmarks: Vec<Fact>,
+    /// Landmarks that must hold before each fact can be made true
+    needed: Vec<Vec<Fact>>,
+}
+
+impl LandmarkCount {
+    pub fn new(task: &StripsTask) -> Self {
+        let relaxation = Relaxation::new(task);
+        let initial = task.initial_state();
+        let costs = relaxation.costs(&initial, Combine::Max);
+        // actions that can ever be applied
+        let reachable: Vec<usize> = (0..relaxation.operators.len())
+            .filter(|&action| {
+                relaxation.preconditions[action]
+                    .iter()
+                    .all(|&fact| costs.facts[fact] < INFINITY)
+            })
+            .collect();
+        let mut is_landmark = vec![false; task.facts().len()];
+        let mut needed = vec![Vec::new(); task.facts().len()];
+        let mut open = task.goal().to_vec();
+        while let Some(fact) = open.pop() {
+            if std::mem::replace(&mut is_landmark[fact], true) || initial.holds(fact) {
+                continue;
+            }
+            let mut achievers = reachable
+                .iter()
+                .filter(|&&action| relaxation.add[action].contains(&fact));
+            let Some(&first) = achievers.next() else {
+                continue;
+            };
+            let mut shared = relaxation.preconditions[first].clone();
+            for &action in achievers {
+                shared.retain(|fact| relaxation.preconditions[action].contains(fact));
+            }
+            open.extend(&shared);
+            needed[fact] = shared;
+        }
+        Self {
+            goal: task.goal().to_vec(),
+            landmarks: (0..is_landmark.len())
+                .filter(|&fact| is_landmark[fact])
+                .collect(),
+            needed,
+        }
+    }
+
+    /// Landmarks of the task, in increasing order
+    pub fn landmarks(&self) -> &[Fact] {
+        &self.landmarks
+    }
+}
+
+impl Heuristic<StripsState> for LandmarkCount {
+    fn estimate(&self, state: &StripsState) -> usize {
+        let mut counted = vec![false; self.needed.len()];
+        let mut open: Vec<Fact> = self.goal.clone();
+        let mut count = 0;
+        while let Some(fact) = open.pop() {
+            if state.holds(fact) || std::mem::replace(&mut counted[fact], true) {
+                continue;
+            }
+            count += 1;
+            open.extend(&self.needed[fact]);
+        }
+        count
+    }
+}
@@ -0,0 +1,88 @@
+use super::{FfHeuristic, StripsState, StripsTask};
+use crate::{
+    frontiers::{Frontier, QueueFrontier},
+    output::SearchResult,
+    Heuristic, Node, Space, State,
+};
+use std::collections::HashSet;
+
+pub trait EnforcedHillClimbing {
+    /// FF's search: from the current state, search breadth-first for a state
+    /// with a lower h_FF and move there, until a goal. With `helpful_only`,
+    /// each breadth-first search first tries only the helpful actions, then
+    /// every action if that fails. Incomplete: `None` may be returned when
+    /// a plan exists but climbing led to a dead end.
+    fn enforced_hill_climbing(
+        &self,
+        heuristic: &FfHeuristic,
+        helpful_only: bool,
+    ) -> Option<SearchResult<StripsState>>;
+}
+
+impl EnforcedHillClimbing for StripsTask {
+    fn enforced_hill_climbing(
+        &self,
+        heuristic: &FfHeuristic,
+        helpful_only: bool,
+    ) -> Option<SearchResult<StripsState>> {
+        let mut state = self.initial_state();
+        let mut h = heuristic.estimate(&state);
+        let mut path = Vec::new();
+        let mut generated = 0;
+        let mut expanded = 0;
+        while !self.is_goal(&state) {
+            let mut improved = None;
+            if helpful_only {
+                improved = self.improve(heuristic, &state, h, true, &mut generated, &mut expanded);
+            }
+            if improved.is_none() {
+                improved = self.improve(heuristic, &state, h, false, &mut generated, &mut expanded);
+            }
+            let (node, better) = improved?;
+            path.extend(node.path().iter().cloned());
+            state = node.state().clone();
+            h = better;
+        }
+        Some(SearchResult::from_path(state, path, generated, expanded))
+    }
+}
+
+impl StripsTask {
+    /// Closest state to `from` with an estimate below `h`, or a goal, and its
+    /// estimate
+    fn improve(
+        &self,
+        heuristic: &FfHeuristic,
+        from: &StripsState,
+        h: usize,
+        helpful_only: bool,
+        generated: &mut usize,
+        expanded: &mut usize,
+    ) -> Option<(Node<StripsState>, usize)> {
+        let mut queue = QueueFrontier::new(from.clone());
+        let mut visited = HashSet::from([from.clone()]);
+        while let Some(node) = queue.pop() {
+            *expanded += 1;
+            let actions = if helpful_only {
+                heuristic.helpful_actions(node.state())
+            } else {
+                node.state().get_available_actions()
+            };
+            for action in actions {
+                let child = node.apply(&action);
+                *generated += 1;
+                if !visited.insert(child.state().clone()) {
+                    continue;
+                }
+                let child_h = heuristic.estimate(child.state());
+                if child_h < h || self.is_goal(child.state()) {
+                    return Some((child, child_h));
+                }
+                if child_h < usize::MAX {
+                    queue.push(child);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,284 @@
+//! Classical planning over propositional states:
+//! - ground STRIPS tasks, whose states are sets of facts
+//! - PDDL input (STRIPS with typing), grounded into STRIPS tasks
+//! - heuristics of the delete relaxation: h_max, h_add and h_FF, and a
+//!   landmark count
+//! - enforced hill-climbing, pruned to the helpful actions
+
+mod heuristics;
+mod hill_climbing;
+mod pddl;
+
+pub use heuristics::{FfHeuristic, HAdd, HMax, LandmarkCount};
+pub use hill_climbing::EnforcedHillClimbing;
+pub use pddl::PddlError;
+
+use crate::{Action, CostAction, Space, State};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Index of a proposition of a task, from zero
+pub type Fact = usize;
+
+/// Action of a ground task: applicable when its preconditions hold, it makes
+/// its delete effects false then its add effects true
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroundAction {
+    pub name: String,
+    pub preconditions: Vec<Fact>,
+    pub add: Vec<Fact>,
+    pub delete: Vec<Fact>,
+}
+
+impl GroundAction {
+    fn is_applicable(&self, facts: &[u64]) -> bool {
+        self.preconditions.iter().all(|&fact| contains(facts, fact))
+    }
+}
+
+/// What the states and actions of a task share
+struct Ground {
+    facts: Vec<String>,
+    actions: Vec<GroundAction>,
+}
+
+/// Action of a task, by its index in `StripsTask::actions`. Every action
+/// costs one.
+#[derive(Clone)]
+pub struct Operator {
+    index: usize,
+    ground: Arc<Ground>,
+}
+
+impl Operator {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn name(&self) -> &str {
+        &self.ground.actions[self.index].name
+    }
+}
+
+impl PartialEq for Operator {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for Operator {}
+
+impl Hash for Operator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl fmt::Debug for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Action for Operator {}
+
+impl CostAction for Operator {
+    fn cost(&self) -> usize {
+        1
+    }
+}
+
+/// Facts that hold, every other one is false
+#[derive(Clone)]
+pub struct StripsState {
+    /// One bit per fact
+    facts: Vec<u64>,
+    ground: Arc<Ground>,
+}
+
+impl StripsState {
+    pub fn holds(&self, fact: Fact) -> bool {
+        contains(&self.facts, fact)
+    }
+
+    /// Facts that hold, in increasing order
+    pub fn facts(&self) -> impl Iterator<Item = Fact> + '_ {
+        (0..self.ground.facts.len()).filter(|&fact| self.holds(fact))
+    }
+}
+
+impl PartialEq for StripsState {
+    fn eq(&self, other: &Self) -> bool {
+        self.facts == other.facts
+    }
+}
+
+impl Eq for StripsState {}
+
+impl Hash for StripsState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.facts.hash(state);
+    }
+}
+
+impl fmt::Debug for StripsState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        for fact in self.facts() {
+            set.entry(&format_args!("{}", self.ground.facts[fact]));
+        }
+        set.finish()
+    }
+}
+
+impl State for StripsState {
+    type Action = Operator;
+
+    fn get_available_actions(&self) -> Vec<Self::Action> {
+        self.ground
+            .actions
+            .iter()
+            .enumerate()
+            .filter(|(_, action)| action.is_applicable(&self.facts))
+            .map(|(index, _)| Operator {
+                index,
+                ground: self.ground.clone(),
+            })
+            .collect()
+    }
+
+    fn apply(&self, action: &Self::Action) -> Self {
+        let action = &self.ground.actions[action.index];
+        let mut facts = self.facts.clone();
+        for &fact in &action.delete {
+            facts[fact / 64] &= !(1 << (fact % 64));
+        }
+        for &fact in &action.add {
+            facts[fact / 64] |= 1 << (fact % 64);
+        }
+        Self {
+            facts,
+            ground: self.ground.clone(),
+        }
+    }
+}
+
+fn contains(facts: &[u64], fact: Fact) -> bool {
+    facts[fact / 64] & (1 << (fact % 64)) != 0
+}
+
+/// Ground STRIPS planning task: reach a state where every goal fact holds
+pub struct StripsTask {
+    ground: Arc<Ground>,
+    initial: Vec<Fact>,
+    goal: Vec<Fact>,
+}
+
+impl StripsTask {
+    /// Task over the facts named `facts`. Panics if a fact is out of range.
+    pub fn new(
+        facts: Vec<String>,
+        actions: Vec<GroundAction>,
+        initial: Vec<Fact>,
+        goal: Vec<Fact>,
+    ) -> Self {
+        let in_range = |fact: &Fact| *fact < facts.len();
+        assert!(initial.iter().all(in_range) && goal.iter().all(in_range));
+        assert!(actions.iter().all(|action| {
+            [&action.preconditions, &action.add, &action.delete]
+                .into_iter()
+                .all(|facts| facts.iter().all(in_range))
+        }));
+        Self {
+            ground: Arc::new(Ground { facts, actions }),
+            initial,
+            goal,
+        }
+    }
+
+    /// Ground a PDDL domain and problem. Only the actions reachable from the
+    /// initial state when ignoring delete effects are kept, and the facts
+    /// true in every reachable state are left out.
+    pub fn from_pddl(domain: &str, problem: &str) -> Result<Self, PddlError> {
+        pddl::ground(domain, problem)
+    }
+
+    /// Names of the facts, such as `(on a b)`
+    pub fn facts(&self) -> &[String] {
+        &self.ground.facts
+    }
+
+    pub fn fact(&self, name: &str) -> Option<Fact> {
+        self.ground.facts.iter().position(|fact| fact == name)
+    }
+
+    pub fn actions(&self) -> &[GroundAction] {
+        &self.ground.actions
+    }
+
+    pub fn initial_facts(&self) -> &[Fact] {
+        &self.initial
+    }
+
+    pub fn goal(&self) -> &[Fact] {
+        &self.goal
+    }
+
+    /// State where exactly `facts` hold
+    pub fn state(&self, facts: &[Fact]) -> StripsState {
+        let mut bits = vec![0; self.ground.facts.len().div_ceil(64)];
+        for &fact in facts {
+            bits[fact / 64] |= 1 << (fact % 64);
+        }
+        StripsState {
+            facts: bits,
+            ground: self.ground.clone(),
+        }
+    }
+
+    pub fn operator(&self, index: usize) -> Operator {
+        assert!(index < self.ground.actions.len());
+        Operator {
+            index,
+            ground: self.ground.clone(),
+        }
+    }
+
+    /// Names of the actions of a plan, such as `(stack a b)`
+    pub fn action_names(&self, plan: &[Operator]) -> Vec<String> {
+        plan.iter()
+            .map(|action| action.name().to_string())
+            .collect()
+    }
+}
+
+impl fmt::Debug for StripsTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StripsTask")
+            .field("facts", &self.ground.facts.len())
+            .field("actions", &self.ground.actions.len())
+            .field("initial", &self.initial)
+            .field("goal", &self.goal)
+            .finish()
+    }
+}
+
+impl Space for StripsTask {
+    type State = StripsState;
+    type Action = Operator;
+
+    fn initial_state(&self) -> Self::State {
+        self.state(&self.initial)
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        self.goal.iter().all(|&fact| state.holds(fact))
+    }
+}
@@ -0,0 +1,723 @@
+use super::{Fact, GroundAction, StripsTask};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Malformed PDDL, or outside of the supported subset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PddlError {
+    /// `"domain"` or `"problem"`
+    pub file: &'static str,
+    /// From 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PddlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, line {}: {}", self.file, self.line, self.message)
+    }
+}
+
+impl std::error::Error for PddlError {}
+
+/// Line and message of an error in the file being read
+type Parsed<T> = Result<T, (usize, String)>;
+
+const REQUIREMENTS: [&str; 2] = [":strips", ":typing"];
+
+/// Parenthesised expression, with the line where it starts
+struct Expr {
+    line: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Symbol(String),
+    List(Vec<Expr>),
+}
+
+impl Expr {
+    fn symbol(&self) -> Option<&str> {
+        match &self.kind {
+            Kind::Symbol(symbol) => Some(symbol),
+            Kind::List(_) => None,
+        }
+    }
+
+    fn list(&self) -> Parsed<&[Expr]> {
+        match &self.kind {
+            Kind::List(items) => Ok(items),
+            Kind::Symbol(symbol) => {
+                Err((self.line, format!("expected a list, found `{}`", symbol)))
+            }
+        }
+    }
+
+    /// Items of a list starting with `head`
+    fn tagged(&self, head: &str) -> Option<&[Expr]> {
+        match &self.kind {
+            Kind::List(items) if items.first().and_then(Expr::symbol) == Some(head) => {
+                Some(&items[1..])
+            }
+            _ => None,
+        }
+    }
+}
+
+fn expect_symbol(expr: &Expr) -> Parsed<&str> {
+    expr.symbol()
+        .ok_or_else(|| (expr.line, "expected a name, found a list".to_string()))
+}
+
+/// Single expression of a file, ignoring case and `;` comments
+fn read(input: &str) -> Parsed<Expr> {
+    let mut stack: Vec<(usize, Vec<Expr>)> = Vec::new();
+    let mut done = None;
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let code = line.split(';').next().unwrap_or_default().to_lowercase();
+        let spaced = code.replace('(', " ( ").replace(')', " ) ");
+        for token in spaced.split_whitespace() {
+            if done.is_some() {
+                return Err((line_number, format!("unexpected `{}` after the end", token)));
+            }
+            match token {
+                "(" => stack.push((line_number, Vec::new())),
+                ")" => {
+                    let (line, items) = stack
+                        .pop()
+                        .ok_or_else(|| (line_number, "unbalanced `)`".to_string()))?;
+                    let expr = Expr {
+                        line,
+                        kind: Kind::List(items),
+                    };
+                    match stack.last_mut() {
+                        Some((_, parent)) => parent.push(expr),
+                        None => done = Some(expr),
+                    }
+                }
+                symbol => match stack.last_mut() {
+                    Some((_, parent)) => parent.push(Expr {
+                        line: line_number,
+                        kind: Kind::Symbol(symbol.to_string()),
+                    }),
+                    None => return Err((line_number, format!("`{}` outside of a list", symbol))),
+                },
+            }
+        }
+    }
+    if let Some((line, _)) = stack.last() {
+        return Err((*line, "unclosed `(`".to_string()));
+    }
+    done.ok_or_else(|| (input.lines().count().max(1), "empty file".to_string()))
+}
+
+/// Sections of `(define (<kind> <name>) ...)`, after the name
+fn definition<'a>(expr: &'a Expr, kind: &str) -> Parsed<(String, &'a [Expr])> {
+    let items = expr
+        .tagged("define")
+        .ok_or_else(|| (expr.line, "expected `(define ...)`".to_string()))?;
+    let name = items
+        .first()
+        .and_then(|header| header.tagged(kind))
+        .and_then(|header| match header {
+            [name] => name.symbol(),
+            _ => None,
+        })
+        .ok_or_else(|| (expr.line, format!("expected `({} <name>)`", kind)))?;
+    Ok((name.to_string(), &items[1..]))
+}
+
+/// `a b - t c` gives `a` and `b` the type `t`, and `c` the type `object`
+fn typed_list(items: &[Expr]) -> Parsed<Vec<(String, String)>> {
+    let mut typed = Vec::new();
+    let mut pending = Vec::new();
+    let mut items = items.iter();
+    while let Some(item) = items.next() {
+        let name = expect_symbol(item)?;
+        if name != "-" {
+            pending.push(name.to_string());
+            continue;
+        }
+        let kind = items
+            .next()
+            .ok_or_else(|| (item.line, "missing type after `-`".to_string()))?;
+        let kind = kind
+            .symbol()
+            .ok_or_else(|| (kind.line, "`either` types are not supported".to_string()))?;
+        typed.extend(pending.drain(..).map(|name| (name, kind.to_string())));
+    }
+    typed.extend(pending.into_iter().map(|name| (name, "object".to_string())));
+    Ok(typed)
+}
+
+/// Predicate applied to variables and objects
+struct Atom {
+    line: usize,
+    predicate: String,
+    terms: Vec<String>,
+}
+
+impl Atom {
+    fn parse(expr: &Expr) -> Parsed<Self> {
+        let items = expr.list()?;
+        let Some(predicate) = items.first() else {
+            return Err((expr.line, "empty atom".to_string()));
+        };
+        let predicate = expect_symbol(predicate)?;
+        if matches!(
+            predicate,
+            "not" | "or" | "imply" | "exists" | "forall" | "when" | "="
+        ) {
+            return Err((
+                expr.line,
+                format!("`{}` is not supported in STRIPS", predicate),
+            ));
+        }
+        let terms = items[1..]
+            .iter()
+            .map(|term| expect_symbol(term).map(str::to_string))
+            .collect::<Parsed<_>>()?;
+        Ok(Self {
+            line: expr.line,
+            predicate: predicate.to_string(),
+            terms,
+        })
+    }
+
+    /// Name of the fact once the variables are replaced by `binding`
+    fn ground(&self, binding: &HashMap<&str, &str>) -> String {
+        let mut name = format!("({}", self.predicate);
+        for term in &self.terms {
+            name.push(' ');
+            name.push_str(binding.get(term.as_str()).copied().unwrap_or(term));
+        }
+        name.push(')');
+        name
+    }
+}
+
+/// Atoms of `(and ...)`, or of a single atom
+fn conjunction(expr: &Expr) -> Parsed<Vec<Atom>> {
+    if let Some(items) = expr.tagged("and") {
+        let mut atoms = Vec::new();
+        for item in items {
+            atoms.extend(conjunction(item)?);
+        }
+        return Ok(atoms);
+    }
+    if expr.list()?.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![Atom::parse(expr)?])
+}
+
+/// Add and delete effects
+fn effects(expr: &Expr) -> Parsed<(Vec<Atom>, Vec<Atom>)> {
+    if let Some(items) = expr.tagged("and") {
+        let (mut add, mut delete) = (Vec::new(), Vec::new());
+        for item in items {
+            let (more_add, more_delete) = effects(item)?;
+            add.extend(more_add);
+            delete.extend(more_delete);
+        }
+        return Ok((add, delete));
+    }
+    if let Some(items) = expr.tagged("not") {
+        return match items {
+            [atom] => Ok((Vec::new(), vec![Atom::parse(atom)?])),
+            _ => Err((expr.line, "`not` takes one atom".to_string())),
+        };
+    }
+    if expr.list()?.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    Ok((vec![Atom::parse(expr)?], Vec::new()))
+}
+
+struct Schema {
+    name: String,
+    parameters: Vec<(String, String)>,
+    preconditions: Vec<Atom>,
+    add: Vec<Atom>,
+    delete: Vec<Atom>,
+}
+
+struct Domain {
+    name: String,
+    /// Parent of every type but `object`
+    types: HashMap<String, String>,
+    constants: Vec<(String, String)>,
+    /// Types of the arguments
+    predicates: HashMap<String, Vec<String>>,
+    schemas: Vec<Schema>,
+}
+
+impl Domain {
+    fn parse(input: &str) -> Parsed<Self> {
+        let expr = read(input)?;
+        let (name, sections) = definition(&expr, "domain")?;
+        let mut domain = Domain {
+            name,
+            types: HashMap::new(),
+            constants: Vec::new(),
+            predicates: HashMap::new(),
+            schemas: Vec::new(),
+        };
+        let mut actions = Vec::new();
+        let mut types_line = expr.line;
+        // types of the arguments of the predicates, with their lines
+        let mut argument_types = Vec::new();
+        for section in sections {
+            let items = section.list()?;
+            let Some(keyword) = items.first().and_then(Expr::symbol) else {
+                return Err((section.line, "expected a section".to_string()));
+            };
+            match keyword {
+                ":requirements" => {
+                    for requirement in &items[1..] {
+                        let requirement = expect_symbol(requirement)?;
+                        if !REQUIREMENTS.contains(&requirement) {
+                            return Err((
+                                section.line,
+                                format!("requirement `{}` is not supported", requirement),
+                            ));
+                        }
+                    }
+                }
+                ":types" => {
+                    types_line = section.line;
+                    domain.types.extend(typed_list(&items[1..])?);
+                }
+                ":constants" => domain.constants.extend(typed_list(&items[1..])?),
+                ":predicates" => {
+                    for predicate in &items[1..] {
+                        let declaration = predicate.list()?;
+                        let Some(name) = declaration.first() else {
+                            return Err((predicate.line, "empty predicate".to_string()));
+                        };
+                        let arguments = typed_list(&declaration[1..])?;
+                        argument_types.extend(
+                            arguments
+                                .iter()
+                                .map(|(_, kind)| (kind.clone(), predicate.line)),
+                        );
+                        domain.predicates.insert(
+                            expect_symbol(name)?.to_string(),
+                            arguments.into_iter().map(|(_, kind)| kind).collect(),
+                        );
+                    }
+                }
+                ":action" => actions.push(section),
+                other => {
+                    return Err((section.line, format!("unknown section `{}`", other)));
+                }
+            }
+        }
+        domain.check_types(types_line)?;
+        for (_, kind) in &domain.constants {
+            domain.check_type(kind, expr.line)?;
+        }
+        for (kind, line) in &argument_types {
+            domain.check_type(kind, *line)?;
+        }
+        for action in actions {
+            let schema = domain.schema(action)?;
+            domain.schemas.push(schema);
+        }
+        Ok(domain)
+    }
+
+    /// Every type descends from `object`
+    fn check_types(&self, line: usize) -> Parsed<()> {
+        for (kind, parent) in &self.types {
+            let mut ancestor = parent;
+            for _ in 0..=self.types.len() {
+                if ancestor == "object" {
+                    break;
+                }
+                ancestor = self
+                    .types
+                    .get(ancestor)
+                    .ok_or_else(|| (line, format!("type `{}` is not declared", ancestor)))?;
+            }
+            if ancestor != "object" {
+                return Err((line, format!("type `{}` is its own ancestor", kind)));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_type(&self, kind: &str, line: usize) -> Parsed<()> {
+        if kind == "object" || self.types.contains_key(kind) {
+            Ok(())
+        } else {
+            Err((line, format!("type `{}` is not declared", kind)))
+        }
+    }
+
+    fn is_subtype<'a>(&'a self, mut kind: &'a str, of: &str) -> bool {
+        loop {
+            if kind == of {
+                return true;
+            }
+            match self.types.get(kind) {
+                Some(parent) => kind = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn schema(&self, expr: &Expr) -> Parsed<Schema> {
+        let items = &expr.list()?[1..];
+        let name = items
+            .first()
+            .and_then(Expr::symbol)
+            .ok_or_else(|| (expr.line, "expected the name of the action".to_string()))?;
+        let mut schema = Schema {
+            name: name.to_string(),
+            parameters: Vec::new(),
+            preconditions: Vec::new(),
+            add: Vec::new(),
+            delete: Vec::new(),
+        };
+        for pair in items[1..].chunks(2) {
+            let [key, value] = pair else {
+                return Err((pair[0].line, "missing value after the last key".to_string()));
+            };
+            match expect_symbol(key)? {
+                ":parameters" => schema.parameters = typed_list(value.list()?)?,
+                ":precondition" => schema.preconditions = conjunction(value)?,
+                ":effect" => (schema.add, schema.delete) = effects(value)?,
+                other => return Err((key.line, format!("unknown key `{}`", other))),
+            }
+        }
+        for (parameter, kind) in &schema.parameters {
+            if !parameter.starts_with('?') {
+                return Err((
+                    expr.line,
+                    format!("parameter `{}` should start with `?`", parameter),
+                ));
+            }
+            self.check_type(kind, expr.line)?;
+        }
+        let variables: HashMap<&str, &str> = schema
+            .parameters
+            .iter()
+            .map(|(name, kind)| (name.as_str(), kind.as_str()))
+            .collect();
+        let constants: HashMap<&str, &str> = self
+            .constants
+            .iter()
+            .map(|(name, kind)| (name.as_str(), kind.as_str()))
+            .collect();
+        for atom in schema
+            .preconditions
+            .iter()
+            .chain(&schema.add)
+            .chain(&schema.delete)
+        {
+            self.check_atom(atom, &variables, &constants)?;
+        }
+        Ok(schema)
+    }
+
+    /// The predicate is declared and every term has a type it accepts
+    fn check_atom(
+        &self,
+        atom: &Atom,
+        variables: &HashMap<&str, &str>,
+        objects: &HashMap<&str, &str>,
+    ) -> Parsed<()> {
+        let Some(arguments) = self.predicates.get(&atom.predicate) else {
+            return Err((
+                atom.line,
+                format!("predicate `{}` is not declared", atom.predicate),
+            ));
+        };
+        if arguments.len() != atom.terms.len() {
+            return Err((
+                atom.line,
+                format!(
+                    "`{}` takes {} arguments, not {}",
+                    atom.predicate,
+                    arguments.len(),
+                    atom.terms.len()
+                ),
+            ));
+        }
+        for (term, argument) in atom.terms.iter().zip(arguments) {
+            let kind = if term.starts_with('?') {
+                variables.get(term.as_str())
+            } else {
+                objects.get(term.as_str())
+            };
+            let Some(kind) = kind else {
+                return Err((atom.line, format!("`{}` is not defined", term)));
+            };
+            if !self.is_subtype(kind, argument) {
+                return Err((
+                    atom.line,
+                    format!(
+                        "`{}` is a {}, `{}` expects a {}",
+                        term, kind, atom.predicate, argument
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Problem {
+    objects: Vec<(String, String)>,
+    init: Vec<Atom>,
+    goal: Vec<Atom>,
+}
+
+impl Problem {
+    fn parse(input: &str, domain: &Domain) -> Parsed<Self> {
+        let expr = read(input)?;
+        let (_, sections) = definition(&expr, "problem")?;
+        let mut problem = Problem {
+            objects: domain.constants.clone(),
+            init: Vec::new(),
+            goal: Vec::new(),
+        };
+        for section in sections {
+            let items = section.list()?;
+            let Some(keyword) = items.first().and_then(Expr::symbol) else {
+                return Err((section.line, "expected a section".to_string()));
+            };
+            match (keyword, &items[1..]) {
+                (":domain", [name]) => {
+                    let name = expect_symbol(name)?;
+                    if name != domain.name {
+                        return Err((
+                            section.line,
+                            format!("problem of `{}`, not of `{}`", name, domain.name),
+                        ));
+                    }
+                }
+                (":requirements", _) => {}
+                (":objects", objects) => {
+                    for (name, kind) in typed_list(objects)? {
+                        domain.check_type(&kind, section.line)?;
+                        problem.objects.push((name, kind));
+                    }
+                }
+                (":init", atoms) => {
+                    for atom in atoms {
+                        problem.init.push(Atom::parse(atom)?);
+                    }
+                }
+                (":goal", [goal]) => problem.goal = conjunction(goal)?,
+                (other, _) => {
+                    return Err((section.line, format!("unexpected section `{}`", other)));
+                }
+            }
+        }
+        let mut objects = HashMap::new();
+        for (name, kind) in &problem.objects {
+            if objects.insert(name.as_str(), kind.as_str()).is_some() {
+                return Err((expr.line, format!("object `{}` is defined twice", name)));
+            }
+        }
+        for atom in problem.init.iter().chain(&problem.goal) {
+            domain.check_atom(atom, &HashMap::new(), &objects)?;
+        }
+        Ok(problem)
+    }
+}
+
+/// Action with facts named rather than numbered
+struct Named {
+    name: String,
+    preconditions: Vec<String>,
+    add: Vec<String>,
+    delete: Vec<String>,
+}
+
+/// Actions of `schema` whose static preconditions hold initially. A
+/// predicate is static when no action changes it.
+fn instantiate(
+    domain: &Domain,
+    problem: &Problem,
+    schema: &Schema,
+    init: &HashSet<String>,
+    actions: &mut Vec<Named>,
+) {
+    let is_static = |atom: &Atom| {
+        !domain.schemas.iter().any(|schema| {
+            schema
+                .add
+                .iter()
+                .chain(&schema.delete)
+                .any(|effect| effect.predicate == atom.predicate)
+        })
+    };
+    let position = |parameter: &str| {
+        schema
+            .parameters
+            .iter()
+            .position(|(name, _)| name == parameter)
+    };
+    // static preconditions, checked once their last variable is bound
+    let mut checks: Vec<Vec<&Atom>> = (0..=schema.parameters.len()).map(|_| Vec::new()).collect();
+    for atom in schema.preconditions.iter().filter(|atom| is_static(atom)) {
+        let last = atom.terms.iter().filter_map(|term| position(term)).max();
+        checks[last.map_or(0, |last| last + 1)].push(atom);
+    }
+    let candidates: Vec<Vec<&str>> = schema
+        .parameters
+        .iter()
+        .map(|(_, kind)| {
+            problem
+                .objects
+                .iter()
+                .filter(|(_, object_kind)| domain.is_subtype(object_kind, kind))
+                .map(|(name, _)| name.as_str())
+                .collect()
+        })
+        .collect();
+
+    let holds = |binding: &HashMap<&str, &str>, bound: usize| {
+        checks[bound]
+            .iter()
+            .all(|atom| init.contains(&atom.ground(binding)))
+    };
+    if !holds(&HashMap::new(), 0) {
+        return;
+    }
+    // depth-first enumeration of the bindings, with the index of the next
+    // candidate of every bound parameter
+    let mut binding = HashMap::new();
+    let mut next = vec![0];
+    while let Some(&candidate) = next.last() {
+        let parameter = next.len() - 1;
+        if parameter == schema.parameters.len() {
+            let ground = |atoms: &[Atom]| -> Vec<String> {
+                atoms.iter().map(|atom| atom.ground(&binding)).collect()
+            };
+            let mut name = format!("({}", schema.name);
+            for (parameter, _) in &schema.parameters {
+                name.push(' ');
+                name.push_str(binding[parameter.as_str()]);
+            }
+            name.push(')');
+            actions.push(Named {
+                name,
+                preconditions: schema
+                    .preconditions
+                    .iter()
+                    .filter(|atom| !is_static(atom))
+                    .map(|atom| atom.ground(&binding))
+                    .collect(),
+                add: ground(&schema.add),
+                delete: ground(&schema.delete),
+            });
+            next.pop();
+            continue;
+        }
+        let Some(&object) = candidates[parameter].get(candidate) else {
+            next.pop();
+            continue;
+        };
+        *next.last_mut().unwrap() += 1;
+        binding.insert(schema.parameters[parameter].0.as_str(), object);
+        if holds(&binding, parameter + 1) {
+            next.push(0);
+        }
+    }
+}
+
+pub(super) fn ground(domain: &str, problem: &str) -> Result<StripsTask, PddlError> {
+    let in_file = |file| {
+        move |(line, message)| PddlError {
+            file,
+            line,
+            message,
+        }
+    };
+    let domain = Domain::parse(domain).map_err(in_file("domain"))?;
+    let problem = Problem::parse(problem, &domain).map_err(in_file("problem"))?;
+
+    let no_variables = HashMap::new();
+    let init: HashSet<String> = problem
+        .init
+        .iter()
+        .map(|atom| atom.ground(&no_variables))
+        .collect();
+    let goal: Vec<String> = problem
+        .goal
+        .iter()
+        .map(|atom| atom.ground(&no_variables))
+        .collect();
+    let mut actions = Vec::new();
+    for schema in &domain.schemas {
+        instantiate(&domain, &problem, schema, &init, &mut actions);
+    }
+
+    // relaxed reachability, ignoring the delete effects
+    let mut reached = init.clone();
+    let mut applicable = vec![false; actions.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (action, applicable) in actions.iter().zip(&mut applicable) {
+            if !*applicable
+                && action
+                    .preconditions
+                    .iter()
+                    .all(|fact| reached.contains(fact))
+            {
+                *applicable = true;
+                changed = true;
+                reached.extend(action.add.iter().cloned());
+            }
+        }
+    }
+    let actions: Vec<Named> = actions
+        .into_iter()
+        .zip(applicable)
+        .filter_map(|(action, applicable)| applicable.then_some(action))
+        .collect();
+
+    // facts true initially and never deleted hold in every reachable state
+    let deleted: HashSet<&String> = actions.iter().flat_map(|action| &action.delete).collect();
+    let mut facts: Vec<String> = reached
+        .iter()
+        .chain(&goal)
+        .filter(|fact| !init.contains(*fact) || deleted.contains(fact))
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    facts.sort();
+    let index: HashMap<&str, Fact> = facts
+        .iter()
+        .enumerate()
+        .map(|(index, fact)| (fact.as_str(), index))
+        .collect();
+    let numbers = |names: &[String]| -> Vec<Fact> {
+        let mut numbers: Vec<Fact> = names
+            .iter()
+            .filter_map(|name| index.get(name.as_str()).copied())
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    };
+    let ground_actions = actions
+        .iter()
+        .map(|action| GroundAction {
+            name: action.name.clone(),
+            preconditions: numbers(&action.preconditions),
+            add: numbers(&action.add),
+            delete: numbers(&action.delete),
+        })
+        .collect();
+    let initial = numbers(&init.iter().cloned().collect::<Vec<_>>());
+    let goal = numbers(&goal);
+    Ok(StripsTask::new(facts, ground_actions, initial, goal))
+}
@@ -0,0 +1,330 @@
+//! Tools for the plans found by searches, paths of actions from the initial
+//! state:
+//! - validation, reporting the first step that fails
+//! - replay of the states a plan goes through
+//! - shortening, cutting loops and replacing stretches by cheaper shortcuts
+
+use crate::{
+    frontiers::PriorityFrontier,
+    output::{PlanEdit, ShortenedPlan},
+    CostAction, Node, Space, State,
+};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a plan doesn't reach a goal
+#[derive(Clone, PartialEq, Eq)]
+pub enum PlanError<S: State> {
+    /// The action of step `step`, from zero, is not among the actions
+    /// available in the state reached before it
+    Unavailable {
+        step: usize,
+        state: S,
+        action: S::Action,
+        available: Vec<S::Action>,
+    },
+    /// Every action was applied but the last state is not a goal
+    NotAGoal { state: S },
+}
+
+impl<S> fmt::Display for PlanError<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::Unavailable {
+                step,
+                state,
+                action,
+                available,
+            } => write!(
+                f,
+                "step {}: {:?} is not available in {:?}, only {:?} are",
+                step, action, state, available
+            ),
+            PlanError::NotAGoal { state } => write!(f, "the plan ends in {:?}, not a goal", state),
+        }
+    }
+}
+
+impl<S> fmt::Debug for PlanError<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl<S> std::error::Error for PlanError<S>
+where
+    S: State + fmt::Debug,
+    S::Action: fmt::Debug,
+{
+}
+
+/// Replay `path` from the initial state of `space`, checking that every
+/// action is available when it is taken and that the last state is a goal,
+/// which is returned
+pub fn validate_plan<S>(
+    space: &S,
+    path: &[<S::State as State>::Action],
+) -> Result<S::State, PlanError<S::State>>
+where
+    S: Space,
+    <S::State as State>::Action: PartialEq,
+{
+    let mut state = space.initial_state();
+    for (step, action) in path.iter().enumerate() {
+        let available = state.get_available_actions();
+        if !available.contains(action) {
+            return Err(PlanError::Unavailable {
+                step,
+                state,
+                action: action.clone(),
+                available,
+            });
+        }
+        state = state.apply(action);
+    }
+    if space.is_goal(&state) {
+        Ok(state)
+    } else {
+        Err(PlanError::NotAGoal { state })
+    }
+}
+
+/// States `path` goes through from the initial state of `space`, the
+/// initial state first. The actions are applied without any check.
+pub fn replay<'a, S: Space>(
+    space: &S,
+    path: &'a [<S::State as State>::Action],
+) -> Replay<'a, S::State> {
+    Replay {
+        state: Some(space.initial_state()),
+        path: path.iter(),
+    }
+}
+
+/// Iterator of the states of a plan, made by `replay`
+pub struct Replay<'a, S: State> {
+    /// State to yield next
+    state: Option<S>,
+    path: std::slice::Iter<'a, S::Action>,
+}
+
+impl<S: State> Iterator for Replay<'_, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.state.take()?;
+        self.state = self.path.next().map(|action| state.apply(action));
+        Some(state)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = usize::from(self.state.is_some()) + self.path.len();
+        (left, Some(left))
+    }
+}
+
+impl<S: State> ExactSizeIterator for Replay<'_, S> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShortcutConfig {
+    /// Most actions of the plan a single shortcut replaces
+    pub max_window: usize,
+    /// States expanded by the search for each shortcut
+    pub max_expanded: usize,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            max_window: 10,
+            max_expanded: 1_000,
+        }
+    }
+}
+
+pub trait PlanShortening<S: Space> {
+    /// Shorten `path`, a plan from the initial state of the space: cut the
+    /// actions after the first goal it reaches and the loops back to states
+    /// it was already in, then, from each of its states, search for the
+    /// cheapest path to one of the next states of the plan and take it when
+    /// it costs less than the plan does. The result is at most as expensive
+    /// and reaches a goal, but is only optimal within the searched windows.
+    fn shorten_plan(
+        &self,
+        path: &[<S::State as State>::Action],
+        config: ShortcutConfig,
+    ) -> Result<ShortenedPlan<S::State>, PlanError<S::State>>;
+}
+
+impl<S> PlanShortening<S> for S
+where
+    S: Space,
+    <S::State as State>::Action: CostAction + PartialEq,
+{
+    fn shorten_plan(
+        &self,
+        path: &[<S::State as State>::Action],
+        config: ShortcutConfig,
+    ) -> Result<ShortenedPlan<S::State>, PlanError<S::State>> {
+        validate_plan(self, path)?;
+        let mut plan = path.to_vec();
+        let mut states: Vec<S::State> = replay(self, &plan).collect();
+        let mut edits = Vec::new();
+        cut_past_goal(self, &mut plan, &mut states, &mut edits);
+        remove_loops(&mut plan, &mut states, &mut edits);
+        let mut step = 0;
+        while step < plan.len() {
+            if let Some((to, replacement)) = shortcut(&plan, &states, step, config) {
+                let through: Vec<S::State> = replacement
+                    .iter()
+                    .scan(states[step].clone(), |state, action| {
+                        *state = state.apply(action);
+                        Some(state.clone())
+                    })
+                    .collect();
+                states.splice(step + 1..=to, through);
+                let removed = plan.splice(step..to, replacement.clone()).collect();
+                edits.push(PlanEdit::Shortcut {
+                    step,
+                    removed,
+                    replacement,
+                });
+            }
+            step += 1;
+        }
+        // shortcuts may go through goals or later states of the plan
+        cut_past_goal(self, &mut plan, &mut states, &mut edits);
+        remove_loops(&mut plan, &mut states, &mut edits);
+        Ok(ShortenedPlan {
+            cost: plan.iter().map(CostAction::cost).sum(),
+            path: plan,
+            original_cost: path.iter().map(CostAction::cost).sum(),
+            edits,
+        })
+    }
+}
+
+/// Drop the actions after the first goal among `states`, the states `plan`
+/// goes through
+fn cut_past_goal<S: Space>(
+    space: &S,
+    plan: &mut Vec<<S::State as State>::Action>,
+    states: &mut Vec<S::State>,
+    edits: &mut Vec<PlanEdit<S::State>>,
+) {
+    if let Some(step) = states.iter().position(|state| space.is_goal(state)) {
+        if step < plan.len() {
+            states.truncate(step + 1);
+            edits.push(PlanEdit::PastGoal {
+                step,
+                removed: plan.split_off(step),
+            });
+        }
+    }
+}
+
+/// Jump from every state of the plan to its last visit, so that no state is
+/// visited twice
+fn remove_loops<S: State>(
+    plan: &mut Vec<S::Action>,
+    states: &mut Vec<S>,
+    edits: &mut Vec<PlanEdit<S>>,
+) {
+    let last: HashMap<&S, usize> = states
+        .iter()
+        .enumerate()
+        .map(|(step, state)| (state, step))
+        .collect();
+    let mut kept_plan = Vec::with_capacity(plan.len());
+    let mut kept_states = Vec::with_capacity(states.len());
+    let mut step = 0;
+    loop {
+        let back = last[&states[step]];
+        if back > step {
+            edits.push(PlanEdit::Loop {
+                step: kept_plan.len(),
+                removed: plan[step..back].to_vec(),
+            });
+            step = back;
+        }
+        kept_states.push(states[step].clone());
+        if step == plan.len() {
+            break;
+        }
+        kept_plan.push(plan[step].clone());
+        step += 1;
+    }
+    *plan = kept_plan;
+    *states = kept_states;
+}
+
+/// Cheapest path from the state of `step` to one of the next states of the
+/// plan in the window, saving the most over the plan, and the step of that
+/// state
+fn shortcut<S>(
+    plan: &[S::Action],
+    states: &[S],
+    step: usize,
+    config: ShortcutConfig,
+) -> Option<(usize, Vec<S::Action>)>
+where
+    S: State,
+    S::Action: CostAction,
+{
+    let end = plan.len().min(step + config.max_window);
+    // cost of the plan from `step` to each state of the window
+    let mut targets = HashMap::new();
+    let mut along = 0;
+    for to in step + 1..=end {
+        along += plan[to - 1].cost();
+        targets.insert(&states[to], (to, along));
+    }
+    // no path costing as much as the whole window can save anything
+    let bound = along;
+
+    let mut best = None;
+    let mut best_saving = 0;
+    let mut costs = HashMap::from([(states[step].clone(), 0)]);
+    let mut frontier = PriorityFrontier::new();
+    frontier.push(Node::new(states[step].clone()), 0);
+    let mut expanded = 0;
+    while let Some((node, cost)) = frontier.pop() {
+        if cost > costs[node.state()] {
+            continue;
+        }
+        if let Some(&(to, along)) = targets.get(node.state()) {
+            if cost < along && along - cost > best_saving {
+                best_saving = along - cost;
+                best = Some((to, node.path().clone()));
+            }
+        }
+        if expanded == config.max_expanded {
+            break;
+        }
+        expanded += 1;
+        for action in node.state().get_available_actions() {
+            let child_cost = cost + action.cost();
+            if child_cost >= bound {
+                continue;
+            }
+            let child = node.apply(&action);
+            if costs
+                .get(child.state())
+                .is_some_and(|&old| old <= child_cost)
+            {
+                continue;
+            }
+            costs.insert(child.state().clone(), child_cost);
+            frontier.push(child, child_cost);
+        }
+    }
+    best
+}
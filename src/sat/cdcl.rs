@@ -0,0 +1,398 @@
+use super::{BoolVar, Cnf, Lit};
+use crate::output::{SatResult, SatStats};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CdclConfig {
+    /// Conflicts before the first restart, the following intervals grow
+    /// with the Luby sequence
+    pub restart_interval: usize,
+    /// Factor applied to the activity of every variable after each conflict
+    pub activity_decay: f64,
+    /// Value given to a variable the first time it is decided, afterwards
+    /// it takes its last value again
+    pub initial_phase: bool,
+}
+
+impl Default for CdclConfig {
+    fn default() -> Self {
+        Self {
+            restart_interval: 100,
+            activity_decay: 0.95,
+            initial_phase: false,
+        }
+    }
+}
+
+pub trait CdclSolver {
+    /// Model of the formula, found by conflict-driven clause learning: unit
+    /// propagation with two watched literals, decisions on the most active
+    /// variable (VSIDS), a clause learned from the first unique implication
+    /// point of every conflict, and restarts
+    fn solve_cdcl(&self, config: CdclConfig) -> SatResult;
+}
+
+impl CdclSolver for Cnf {
+    fn solve_cdcl(&self, config: CdclConfig) -> SatResult {
+        let mut solver = Solver::new(self.variables(), config);
+        let satisfiable = self
+            .clauses()
+            .iter()
+            .all(|clause| solver.add_clause(clause.clone()))
+            && solver.search();
+        SatResult {
+            model: satisfiable.then(|| solver.model()),
+            stats: solver.stats,
+        }
+    }
+}
+
+/// Variable waiting for a decision
+#[derive(Debug, PartialEq)]
+struct Candidate {
+    activity: f64,
+    var: BoolVar,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    /// Most active first, then lowest variable
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.activity
+            .total_cmp(&other.activity)
+            .then_with(|| other.var.cmp(&self.var))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct Solver {
+    config: CdclConfig,
+    /// Clauses of the formula with at least two literals, then the learned
+    /// ones. The first two literals of a clause are watched.
+    clauses: Vec<Vec<Lit>>,
+    /// Clauses watching every literal, visited when it becomes false
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    /// Decision level at which every variable was assigned
+    level: Vec<usize>,
+    /// Clause that implied the value of every variable, `None` for the
+    /// decisions and the units of the formula
+    reason: Vec<Option<usize>>,
+    /// True literals, in the order they were assigned
+    trail: Vec<Lit>,
+    /// Start of every decision level in the trail
+    levels: Vec<usize>,
+    /// Literals of the trail already propagated
+    propagated: usize,
+    activity: Vec<f64>,
+    /// Activity added to the variables of a conflict, grows instead of
+    /// decaying every activity
+    bump: f64,
+    /// Unassigned variables, with stale entries for the ones assigned or
+    /// bumped since
+    order: BinaryHeap<Candidate>,
+    /// Last value of every variable
+    phase: Vec<bool>,
+    /// Variables met during the analysis of a conflict
+    seen: Vec<bool>,
+    stats: SatStats,
+}
+
+impl Solver {
+    fn new(variables: usize, config: CdclConfig) -> Self {
+        Self {
+            config,
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2 * variables],
+            values: vec![None; variables],
+            level: vec![0; variables],
+            reason: vec![None; variables],
+            trail: Vec::new(),
+            levels: Vec::new(),
+            propagated: 0,
+            activity: vec![0.0; variables],
+            bump: 1.0,
+            order: (0..variables)
+                .map(|var| Candidate { activity: 0.0, var })
+                .collect(),
+            phase: vec![config.initial_phase; variables],
+            seen: vec![false; variables],
+            stats: SatStats::default(),
+        }
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var()].map(|value| lit.holds(value))
+    }
+
+    /// Add a clause before the search, `false` if the formula is found
+    /// unsatisfiable
+    fn add_clause(&mut self, mut clause: Vec<Lit>) -> bool {
+        clause.sort();
+        clause.dedup();
+        if clause.windows(2).any(|pair| pair[1] == !pair[0])
+            || clause.iter().any(|&lit| self.value(lit) == Some(true))
+        {
+            return true;
+        }
+        clause.retain(|&lit| self.value(lit).is_none());
+        match clause[..] {
+            [] => false,
+            [lit] => {
+                self.assign(lit, None);
+                self.propagate().is_none()
+            }
+            _ => {
+                self.attach(clause);
+                true
+            }
+        }
+    }
+
+    fn attach(&mut self, clause: Vec<Lit>) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause[0].index()].push(index);
+        self.watches[clause[1].index()].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var();
+        self.values[var] = Some(!lit.is_negated());
+        self.level[var] = self.levels.len();
+        self.reason[var] = reason;
+        self.trail.push(lit);
+    }
+
+    /// Assign the literals implied by the clauses whose other literals are
+    /// false, returning a clause whose literals are all false if there is one
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = !self.trail[self.propagated];
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[false_lit.index()]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            for index in watching {
+                if conflict.is_some() {
+                    kept.push(index);
+                    continue;
+                }
+                let clause = &mut self.clauses[index];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                let values = &self.values;
+                let value = |lit: Lit| values[lit.var()].map(|value| lit.holds(value));
+                if value(first) == Some(true) {
+                    kept.push(index);
+                    continue;
+                }
+                if let Some(other) = (2..clause.len()).find(|&k| value(clause[k]) != Some(false)) {
+                    clause.swap(1, other);
+                    self.watches[clause[1].index()].push(index);
+                    continue;
+                }
+                kept.push(index);
+                if value(first) == Some(false) {
+                    conflict = Some(index);
+                } else {
+                    self.stats.propagations += 1;
+                    self.assign(first, Some(index));
+                }
+            }
+            self.watches[false_lit.index()] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Clause learned from a conflict, with the literal it asserts first and
+    /// a literal of the level to go back to second, and that level
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let current = self.levels.len();
+        // the asserting literal is found last
+        let mut learned = vec![Lit::positive(0)];
+        let mut pending = 0;
+        let mut implied: Option<Lit> = None;
+        let mut clause = conflict;
+        let mut position = self.trail.len();
+        loop {
+            for k in 0..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                let var = lit.var();
+                if implied.is_some_and(|implied| implied.var() == var)
+                    || self.seen[var]
+                    || self.level[var] == 0
+                {
+                    continue;
+                }
+                self.seen[var] = true;
+                self.bump_activity(var);
+                if self.level[var] == current {
+                    pending += 1;
+                } else {
+                    learned.push(lit);
+                }
+            }
+            // latest literal of the current level in the conflict
+            loop {
+                position -= 1;
+                if self.seen[self.trail[position].var()] {
+                    break;
+                }
+            }
+            let lit = self.trail[position];
+            self.seen[lit.var()] = false;
+            pending -= 1;
+            if pending == 0 {
+                learned[0] = !lit;
+                break;
+            }
+            implied = Some(lit);
+            clause = self.reason[lit.var()].expect("only the decision has no reason");
+        }
+        for lit in &learned[1..] {
+            self.seen[lit.var()] = false;
+        }
+
+        let mut level = 0;
+        for k in 1..learned.len() {
+            if self.level[learned[k].var()] > level {
+                level = self.level[learned[k].var()];
+                learned.swap(1, k);
+            }
+        }
+        (learned, level)
+    }
+
+    fn bump_activity(&mut self, var: BoolVar) {
+        self.activity[var] += self.bump;
+        if self.activity[var] > 1e100 {
+            self.activity
+                .iter_mut()
+                .for_each(|activity| *activity *= 1e-100);
+            self.bump *= 1e-100;
+            let unassigned = (0..self.values.len()).filter(|&var| self.values[var].is_none());
+            self.order = unassigned
+                .map(|var| Candidate {
+                    activity: self.activity[var],
+                    var,
+                })
+                .collect();
+        } else if self.values[var].is_none() {
+            self.order.push(Candidate {
+                activity: self.activity[var],
+                var,
+            });
+        }
+    }
+
+    /// Undo the decision levels above `level`
+    fn backtrack(&mut self, level: usize) {
+        if self.levels.len() <= level {
+            return;
+        }
+        let start = self.levels[level];
+        for lit in self.trail.drain(start..) {
+            let var = lit.var();
+            self.phase[var] = !lit.is_negated();
+            self.values[var] = None;
+            self.reason[var] = None;
+            self.order.push(Candidate {
+                activity: self.activity[var],
+                var,
+            });
+        }
+        self.levels.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    /// Most active unassigned variable
+    fn pick(&mut self) -> Option<BoolVar> {
+        while let Some(candidate) = self.order.pop() {
+            let var = candidate.var;
+            if self.values[var].is_none() && candidate.activity == self.activity[var] {
+                return Some(var);
+            }
+        }
+        None
+    }
+
+    fn search(&mut self) -> bool {
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.conflicts += 1;
+                conflicts += 1;
+                if self.levels.is_empty() {
+                    return false;
+                }
+                let (learned, level) = self.analyze(conflict);
+                self.backtrack(level);
+                self.stats.learned_clauses += 1;
+                if learned.len() == 1 {
+                    self.assign(learned[0], None);
+                } else {
+                    let asserting = learned[0];
+                    let index = self.attach(learned);
+                    self.assign(asserting, Some(index));
+                }
+                self.bump /= self.config.activity_decay;
+            } else if conflicts >= self.config.restart_interval.max(1) * luby(restarts) {
+                restarts += 1;
+                conflicts = 0;
+                self.stats.restarts += 1;
+                self.backtrack(0);
+            } else {
+                let Some(var) = self.pick() else {
+                    return true;
+                };
+                self.stats.decisions += 1;
+                self.levels.push(self.trail.len());
+                let lit = if self.phase[var] {
+                    Lit::positive(var)
+                } else {
+                    Lit::negative(var)
+                };
+                self.assign(lit, None);
+            }
+        }
+    }
+
+    fn model(&self) -> Vec<bool> {
+        self.values
+            .iter()
+            .map(|value| value.expect("every variable is assigned"))
+            .collect()
+    }
+}
+
+/// Element `index` of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ...
+fn luby(mut index: usize) -> usize {
+    // smallest complete subsequence containing the index, of size 2^k - 1
+    let mut size = 1;
+    let mut power = 0;
+    while size < index + 1 {
+        power += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != index {
+        size = (size - 1) / 2;
+        power -= 1;
+        index %= size;
+    }
+    1 << power
+}
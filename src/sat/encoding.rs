@@ -0,0 +1,81 @@
+use super::{BoolVar, Cnf, Lit};
+use crate::csp::{Csp, Var};
+
+/// CNF of a CSP, with a boolean variable for every value of every variable
+#[derive(Debug, Clone)]
+pub struct CspEncoding<V> {
+    pub cnf: Cnf,
+    /// Values of every variable of the CSP with their boolean variable
+    literals: Vec<Vec<(V, BoolVar)>>,
+}
+
+impl<V: Clone + PartialEq> CspEncoding<V> {
+    /// Boolean variable true when `var` takes `value`, if it is in its
+    /// domain
+    pub fn literal(&self, var: Var, value: &V) -> Option<BoolVar> {
+        self.literals[var]
+            .iter()
+            .find(|(other, _)| other == value)
+            .map(|&(_, literal)| literal)
+    }
+
+    /// Value of every variable of the CSP in a model of the CNF
+    pub fn decode(&self, model: &[bool]) -> Vec<V> {
+        self.literals
+            .iter()
+            .map(|values| {
+                let (value, _) = values
+                    .iter()
+                    .find(|&&(_, literal)| model[literal])
+                    .expect("every variable has a value in a model");
+                value.clone()
+            })
+            .collect()
+    }
+}
+
+pub trait ToCnf<V> {
+    /// Direct encoding: every variable takes exactly one value of its
+    /// domain, and each nogood of the constraints is a clause
+    fn to_cnf(&self) -> CspEncoding<V>;
+}
+
+impl<V: Clone + PartialEq> ToCnf<V> for Csp<V> {
+    fn to_cnf(&self) -> CspEncoding<V> {
+        let mut cnf = Cnf::new();
+        let literals: Vec<Vec<(V, BoolVar)>> = (0..self.variables())
+            .map(|var| {
+                self.domain(var)
+                    .iter()
+                    .map(|value| (value.clone(), cnf.new_variable()))
+                    .collect()
+            })
+            .collect();
+        for values in &literals {
+            cnf.add_clause(values.iter().map(|&(_, var)| Lit::positive(var)).collect());
+            for (i, &(_, first)) in values.iter().enumerate() {
+                for &(_, second) in &values[i + 1..] {
+                    cnf.add_clause(vec![Lit::negative(first), Lit::negative(second)]);
+                }
+            }
+        }
+        let mut encoding = CspEncoding { cnf, literals };
+
+        let domains: Vec<Vec<V>> = (0..self.variables())
+            .map(|var| self.domain(var).to_vec())
+            .collect();
+        for constraint in self.constraints() {
+            for nogood in constraint.nogoods(&domains) {
+                // values outside of the domains can't be taken anyway
+                let clause: Option<Vec<Lit>> = nogood
+                    .iter()
+                    .map(|(var, value)| encoding.literal(*var, value).map(Lit::negative))
+                    .collect();
+                if let Some(clause) = clause {
+                    encoding.cnf.add_clause(clause);
+                }
+            }
+        }
+        encoding
+    }
+}
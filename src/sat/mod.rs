@@ -0,0 +1,239 @@
+//! Boolean satisfiability of formulas in conjunctive normal form:
+//! - CDCL solver with two watched literals, VSIDS, Luby restarts and
+//!   first-UIP clause learning
+//! - DIMACS CNF input and output
+//! - direct encoding of finite-domain CSPs
+
+mod cdcl;
+mod encoding;
+
+pub use cdcl::{CdclConfig, CdclSolver};
+pub use encoding::{CspEncoding, ToCnf};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Index of a boolean variable, from zero
+pub type BoolVar = usize;
+
+/// Variable or its negation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Lit {
+    /// Twice the variable, plus one if negated
+    code: usize,
+}
+
+impl Lit {
+    pub fn positive(var: BoolVar) -> Self {
+        Self { code: 2 * var }
+    }
+
+    pub fn negative(var: BoolVar) -> Self {
+        Self { code: 2 * var + 1 }
+    }
+
+    pub fn var(self) -> BoolVar {
+        self.code / 2
+    }
+
+    pub fn is_negated(self) -> bool {
+        self.code % 2 == 1
+    }
+
+    /// Value of the literal when its variable has `value`
+    pub fn holds(self, value: bool) -> bool {
+        value != self.is_negated()
+    }
+
+    /// Literal of DIMACS, where variables start from 1 and negative numbers
+    /// are negations. Zero has no literal.
+    pub fn from_dimacs(number: i64) -> Option<Self> {
+        let var = usize::try_from(number.unsigned_abs())
+            .ok()?
+            .checked_sub(1)?;
+        Some(if number > 0 {
+            Self::positive(var)
+        } else {
+            Self::negative(var)
+        })
+    }
+
+    pub fn to_dimacs(self) -> i64 {
+        let number = self.var() as i64 + 1;
+        if self.is_negated() {
+            -number
+        } else {
+            number
+        }
+    }
+
+    fn index(self) -> usize {
+        self.code
+    }
+}
+
+impl std::ops::Not for Lit {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self {
+            code: self.code ^ 1,
+        }
+    }
+}
+
+/// Conjunction of clauses, which are disjunctions of literals
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cnf {
+    variables: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl Cnf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_variable(&mut self) -> BoolVar {
+        self.variables += 1;
+        self.variables - 1
+    }
+
+    /// The variables of the clause are added if needed
+    pub fn add_clause(&mut self, clause: Vec<Lit>) {
+        if let Some(last) = clause.iter().map(|lit| lit.var()).max() {
+            self.variables = self.variables.max(last + 1);
+        }
+        self.clauses.push(clause);
+    }
+
+    pub fn variables(&self) -> usize {
+        self.variables
+    }
+
+    pub fn clauses(&self) -> &[Vec<Lit>] {
+        &self.clauses
+    }
+
+    /// Whether every clause has a true literal under `model`, the value of
+    /// every variable
+    pub fn is_satisfied_by(&self, model: &[bool]) -> bool {
+        model.len() == self.variables
+            && self
+                .clauses
+                .iter()
+                .all(|clause| clause.iter().any(|lit| lit.holds(model[lit.var()])))
+    }
+}
+
+/// DIMACS CNF, with a `p cnf` header
+impl fmt::Display for Cnf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(f, "{} ", lit.to_dimacs())?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// Malformed DIMACS CNF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimacsError {
+    /// From 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DimacsError {}
+
+/// Parse DIMACS CNF: comment lines start with `c`, the `p cnf <variables>
+/// <clauses>` header comes before the clauses, and every clause ends with
+/// `0`. A `%` line ends the formula.
+impl FromStr for Cnf {
+    type Err = DimacsError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut cnf = Cnf::new();
+        let mut header = None;
+        let mut clause = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            let error = |message: String| DimacsError {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            if line.starts_with('%') {
+                break;
+            }
+            if line.starts_with('p') {
+                if header.is_some() {
+                    return Err(error("second header".to_string()));
+                }
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let counts = match fields[..] {
+                    ["p", "cnf", variables, clauses] => variables
+                        .parse::<usize>()
+                        .ok()
+                        .zip(clauses.parse::<usize>().ok()),
+                    _ => None,
+                };
+                let Some((variables, clauses)) = counts else {
+                    return Err(error(format!(
+                        "expected `p cnf <variables> <clauses>`, found `{}`",
+                        line
+                    )));
+                };
+                cnf.variables = variables;
+                header = Some(clauses);
+                continue;
+            }
+            if header.is_none() {
+                return Err(error("clause before the header".to_string()));
+            }
+            for token in line.split_whitespace() {
+                let number: i64 = token
+                    .parse()
+                    .map_err(|_| error(format!("expected a literal, found `{}`", token)))?;
+                match Lit::from_dimacs(number) {
+                    None => cnf.clauses.push(std::mem::take(&mut clause)),
+                    Some(lit) if lit.var() < cnf.variables => clause.push(lit),
+                    Some(_) => {
+                        return Err(error(format!(
+                            "literal {} beyond the {} variables of the header",
+                            number, cnf.variables
+                        )))
+                    }
+                }
+            }
+        }
+        let Some(clauses) = header else {
+            return Err(DimacsError {
+                line: input.lines().count(),
+                message: "no header".to_string(),
+            });
+        };
+        if !clause.is_empty() {
+            cnf.clauses.push(clause);
+        }
+        if cnf.clauses.len() != clauses {
+            return Err(DimacsError {
+                line: input.lines().count(),
+                message: format!("{} clauses instead of {}", cnf.clauses.len(), clauses),
+            });
+        }
+        Ok(cnf)
+    }
+}